@@ -0,0 +1,7 @@
+//! Client for the [example API](https://example.com/api-docs).
+
+/// Creates a new [`Client`](crate::Client).
+pub fn client() -> &'static str {
+    // See https://example.com/comment
+    "https://example.com/string-literal"
+}
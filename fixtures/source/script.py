@@ -0,0 +1,3 @@
+"""Download data, see https://example.com/docstring"""
+
+URL = "https://example.com/string-literal"  # https://example.com/comment
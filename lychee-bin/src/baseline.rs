@@ -0,0 +1,253 @@
+//! Known broken links which don't fail a run (`--baseline`).
+//!
+//! A baseline maps input sources to the URLs which are known to be broken in
+//! them. Broken links listed in the baseline are still reported, but only new
+//! failures affect the exit code. This allows gating CI on lychee while
+//! working through the known broken links incrementally.
+//!
+//! The dedicated baseline format is written with `--update-baseline`:
+//!
+//! ```json
+//! {
+//!   "docs/README.md": [
+//!     "https://example.com/dead"
+//!   ]
+//! }
+//! ```
+//!
+//! The JSON report of a previous run (`--format json`) can be used as a
+//! baseline, too.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use lychee_lib::{InputSource, ResponseBody};
+use serde::{Deserialize, Serialize};
+
+use crate::formatters::stats::ResponseStats;
+
+/// Broken links which are known and accepted, by input source
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct Baseline {
+    links: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// The formats which can be loaded as a [`Baseline`]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BaselineFile {
+    /// The JSON report of a previous run
+    Report {
+        error_map: HashMap<String, Vec<ReportEntry>>,
+        #[serde(default)]
+        timeout_map: HashMap<String, Vec<ReportEntry>>,
+    },
+    Baseline(Baseline),
+}
+
+#[derive(Deserialize)]
+struct ReportEntry {
+    url: String,
+}
+
+impl Baseline {
+    /// Load a baseline or a JSON report from the given file.
+    /// A missing file is treated as an empty baseline.
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            warn!(
+                "Baseline file {} does not exist, so all broken links fail the run",
+                path.display()
+            );
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Cannot read baseline file {}", path.display()))?;
+        let file: BaselineFile = serde_json::from_str(&content)
+            .with_context(|| format!("Cannot parse baseline file {}", path.display()))?;
+
+        Ok(match file {
+            BaselineFile::Report {
+                error_map,
+                timeout_map,
+            } => {
+                let mut baseline = Self::default();
+                for (source, entries) in error_map.into_iter().chain(timeout_map) {
+                    baseline
+                        .links
+                        .entry(source)
+                        .or_default()
+                        .extend(entries.into_iter().map(|entry| entry.url));
+                }
+                baseline
+            }
+            BaselineFile::Baseline(baseline) => baseline,
+        })
+    }
+
+    /// Create a baseline from all broken links of a run
+    pub(crate) fn from_stats(stats: &ResponseStats, accept_timeouts: bool) -> Self {
+        let mut baseline = Self::default();
+        for (source, body) in broken_links(stats, accept_timeouts) {
+            baseline
+                .links
+                .entry(source.to_string())
+                .or_default()
+                .insert(body.uri.to_string());
+        }
+        baseline
+    }
+
+    /// Write the baseline to the given file, replacing its contents
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Cannot serialize baseline")?;
+        fs::write(path, json + "\n")
+            .with_context(|| format!("Cannot write baseline file {}", path.display()))
+    }
+
+    fn contains(&self, source: &InputSource, body: &ResponseBody) -> bool {
+        self.links
+            .get(&source.to_string())
+            .is_some_and(|urls| urls.contains(body.uri.as_str()))
+    }
+
+    /// Check if the run was successful, i.e. if all broken links are known
+    pub(crate) fn is_success(&self, stats: &ResponseStats, accept_timeouts: bool) -> bool {
+        let (known, new): (Vec<_>, Vec<_>) = broken_links(stats, accept_timeouts)
+            .partition(|(source, body)| self.contains(source, body));
+
+        if !known.is_empty() {
+            info!(
+                "{} broken link(s) are known from the baseline and don't fail the run",
+                known.len()
+            );
+        }
+        new.is_empty()
+    }
+}
+
+/// All broken links of a run, i.e. errors and (unless accepted) timeouts
+fn broken_links(
+    stats: &ResponseStats,
+    accept_timeouts: bool,
+) -> impl Iterator<Item = (&InputSource, &ResponseBody)> {
+    let timeouts = (!accept_timeouts).then_some(&stats.timeout_map);
+    stats
+        .error_map
+        .iter()
+        .chain(timeouts.into_iter().flatten())
+        .flat_map(|(source, bodies)| bodies.iter().map(move |body| (source, body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use http::StatusCode;
+    use lychee_lib::{ErrorKind, Response, Status, Uri};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn broken(url: &str, source: &str) -> Response {
+        Response::new(
+            Uri::try_from(url).unwrap(),
+            Status::Error(ErrorKind::RejectedStatusCode(StatusCode::NOT_FOUND)),
+            None,
+            None,
+            InputSource::FsPath(PathBuf::from(source)),
+            None,
+            None,
+        )
+    }
+
+    fn timeout(url: &str, source: &str) -> Response {
+        Response::new(
+            Uri::try_from(url).unwrap(),
+            Status::Timeout(None),
+            None,
+            None,
+            InputSource::FsPath(PathBuf::from(source)),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_only_new_failures_fail_the_run() {
+        let mut stats = ResponseStats::default();
+        stats.add(broken("https://example.com/known", "README.md"));
+
+        let baseline = Baseline::from_stats(&stats, false);
+        assert!(baseline.is_success(&stats, false));
+
+        // The same URL in another file is a new failure
+        stats.add(broken("https://example.com/known", "docs/index.md"));
+        assert!(!baseline.is_success(&stats, false));
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let mut stats = ResponseStats::default();
+        stats.add(timeout("https://example.com/slow", "README.md"));
+
+        assert!(Baseline::default().is_success(&stats, true));
+        assert!(!Baseline::default().is_success(&stats, false));
+        assert_eq!(Baseline::from_stats(&stats, true), Baseline::default());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+
+        let mut stats = ResponseStats::default();
+        stats.add(broken("https://example.com/b", "README.md"));
+        stats.add(broken("https://example.com/a", "README.md"));
+        let baseline = Baseline::from_stats(&stats, false);
+        baseline.save(&path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"README.md\": [\n    \"https://example.com/a\",\n    \"https://example.com/b\"\n  ]\n}\n"
+        );
+        assert_eq!(Baseline::load(&path).unwrap(), baseline);
+    }
+
+    #[test]
+    fn test_load_json_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        fs::write(
+            &path,
+            r#"{
+                "total": 2,
+                "error_map": {
+                    "README.md": [{ "url": "https://example.com/a", "status": { "text": "404 Not Found" } }]
+                },
+                "timeout_map": {
+                    "README.md": [{ "url": "https://example.com/slow" }]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mut stats = ResponseStats::default();
+        stats.add(broken("https://example.com/a", "README.md"));
+        stats.add(timeout("https://example.com/slow", "README.md"));
+
+        assert!(Baseline::load(&path).unwrap().is_success(&stats, false));
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let baseline = Baseline::load(Path::new("does-not-exist.json")).unwrap();
+        assert_eq!(baseline, Baseline::default());
+    }
+}
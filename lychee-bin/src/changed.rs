@@ -0,0 +1,327 @@
+//! Restricting local inputs to changed files.
+//!
+//! With `--changed-since`, only the files which were added or modified since
+//! a git commit are checked. `--watch` checks the files again which changed
+//! on disk. In both cases, the local inputs are resolved into the changed
+//! files they contain, honoring `--exclude-path`, `--extensions`, `--hidden`
+//! and ignore files just like in a regular run.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result, bail};
+use futures::StreamExt;
+use log::info;
+use lychee_lib::{Input, InputResolver, InputSource, ResolvedInputSource, filter::PathExcludes};
+
+use crate::config::Config;
+
+/// Returns `true` for inputs which are resolved to local files
+pub(crate) const fn is_local(input: &Input) -> bool {
+    matches!(
+        input.source,
+        InputSource::FsPath(_) | InputSource::FsGlob { .. }
+    )
+}
+
+/// The files of the given local inputs which are among the `changed` paths.
+/// The changed paths must be canonical.
+///
+/// Directories are only traversed on the way to the changed paths, so this
+/// is cheap even for large inputs.
+pub(crate) async fn input_files<'a>(
+    inputs: impl IntoIterator<Item = &'a Input>,
+    changed: &HashSet<PathBuf>,
+    config: &Config,
+) -> Result<HashSet<Input>> {
+    let mut files = HashSet::new();
+    if changed.is_empty() {
+        return Ok(files);
+    }
+
+    let excluded_paths = PathExcludes::new(config.exclude_path.clone())?;
+    for input in inputs.into_iter().filter(|input| is_local(input)) {
+        let mut sources = InputResolver::resolve_files(
+            input,
+            changed,
+            config.extensions(),
+            !config.hidden(),
+            // be aware that "no ignore" means do *not* ignore files
+            !config.no_ignore(),
+            &excluded_paths,
+        );
+        while let Some(source) = sources.next().await {
+            if let ResolvedInputSource::FsPath(path) = source? {
+                files.insert(Input {
+                    source: InputSource::FsPath(path),
+                    file_type_hint: input.file_type_hint,
+                });
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Restrict the local inputs to the files which were added or modified since
+/// the given git commit, including staged changes (`--changed-since`).
+/// All other inputs are kept.
+///
+/// The changes are looked up in the git repository of each input, so inputs
+/// don't have to be in the repository of the current directory. If no local
+/// input file changed, only the other inputs are left, which may be none.
+pub(crate) async fn changed_since(
+    inputs: HashSet<Input>,
+    git_ref: &str,
+    config: &Config,
+) -> Result<HashSet<Input>> {
+    if !inputs.iter().any(is_local) {
+        return Ok(inputs);
+    }
+
+    let mut repositories = HashSet::new();
+    let mut changed = HashSet::new();
+    for dir in inputs.iter().filter_map(|input| input_dir(&input.source)) {
+        let repository = repository_root(&dir)?;
+        if repositories.insert(repository.clone()) {
+            changed.extend(git_changed_files(git_ref, &repository)?);
+        }
+    }
+
+    let mut restricted = input_files(&inputs, &changed, config).await?;
+    if restricted.is_empty() {
+        info!("No local input files changed since `{git_ref}`");
+    }
+    restricted.extend(inputs.into_iter().filter(|input| !is_local(input)));
+    Ok(restricted)
+}
+
+/// The directory or file which contains the files of a local input.
+/// Globs start at the directory before their first wildcard.
+pub(crate) fn local_root(source: &InputSource) -> Option<PathBuf> {
+    match source {
+        InputSource::FsPath(path) => Some(path.clone()),
+        InputSource::FsGlob { pattern, .. } => {
+            let root: PathBuf = Path::new(pattern.as_str())
+                .components()
+                .take_while(|component| {
+                    !component
+                        .as_os_str()
+                        .to_string_lossy()
+                        .contains(['*', '?', '[', '{'])
+                })
+                .collect();
+            Some(if root.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                root
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The directory to run git in for a local input
+fn input_dir(source: &InputSource) -> Option<PathBuf> {
+    let root = local_root(source)?;
+    if root.is_dir() {
+        return Some(root);
+    }
+    Some(match root.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    })
+}
+
+/// The root directory of the git repository which contains `dir`
+fn repository_root(dir: &Path) -> Result<PathBuf> {
+    let root = git(dir, &["rev-parse", "--show-toplevel"])
+        .with_context(|| format!("Cannot find the git repository of {}", dir.display()))?;
+    Ok(PathBuf::from(root.trim_end_matches(['\n', '\r'])))
+}
+
+/// The canonical paths of all files which were added or modified in the git
+/// repository at `root` since the given commit, including staged changes
+fn git_changed_files(git_ref: &str, root: &Path) -> Result<HashSet<PathBuf>> {
+    // Compare the commit with the index: this covers the commits since then
+    // and the staged changes
+    let diff = git(
+        root,
+        &[
+            "diff",
+            "--cached",
+            "--name-only",
+            "--no-renames",
+            "--diff-filter=AM",
+            "-z",
+            git_ref,
+            "--",
+        ],
+    )
+    .with_context(|| format!("Cannot find files changed since `{git_ref}`"))?;
+
+    Ok(diff
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .filter_map(|path| fs::canonicalize(root.join(path)).ok())
+        .collect())
+}
+
+/// Run git in the given directory and return its output
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Cannot run git")?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).context("git returned invalid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn git_in(dir: &Path, args: &[&str]) {
+        git(dir, args).unwrap();
+    }
+
+    #[test]
+    fn test_git_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = fs::canonicalize(dir.path()).unwrap();
+        git_in(&dir, &["init", "--quiet"]);
+        git_in(&dir, &["config", "user.email", "lychee@example.com"]);
+        git_in(&dir, &["config", "user.name", "lychee"]);
+
+        fs::write(dir.join("old.md"), "").unwrap();
+        fs::write(dir.join("modified.md"), "").unwrap();
+        git_in(&dir, &["add", "."]);
+        git_in(&dir, &["commit", "--quiet", "-m", "Initial commit"]);
+
+        fs::create_dir(dir.join("docs")).unwrap();
+        fs::write(dir.join("docs/committed.md"), "").unwrap();
+        git_in(&dir, &["add", "."]);
+        git_in(&dir, &["commit", "--quiet", "-m", "Add docs"]);
+        fs::write(dir.join("modified.md"), "changed").unwrap();
+        fs::write(dir.join("staged.md"), "").unwrap();
+        git_in(&dir, &["add", "modified.md", "staged.md"]);
+        fs::write(dir.join("untracked.md"), "").unwrap();
+
+        let repository = repository_root(&dir.join("docs")).unwrap();
+        assert_eq!(fs::canonicalize(&repository).unwrap(), dir);
+        let changed = git_changed_files("HEAD~1", &repository).unwrap();
+        let expected: HashSet<_> = ["docs/committed.md", "modified.md", "staged.md"]
+            .into_iter()
+            .map(|path| dir.join(path))
+            .collect();
+        assert_eq!(changed, expected);
+
+        assert!(git_changed_files("does-not-exist", &dir).is_err());
+    }
+
+    fn glob(pattern: &str) -> InputSource {
+        let source = Input::from_value(pattern).unwrap().source;
+        assert!(matches!(source, InputSource::FsGlob { .. }));
+        source
+    }
+
+    #[test]
+    fn test_local_root() {
+        assert_eq!(local_root(&glob("docs/**/*.md")), Some("docs".into()));
+        assert_eq!(local_root(&glob("*.md")), Some(".".into()));
+        assert_eq!(
+            local_root(&InputSource::FsPath("README.md".into())),
+            Some("README.md".into())
+        );
+        assert_eq!(local_root(&InputSource::Stdin), None);
+    }
+
+    #[test]
+    fn test_input_dir() {
+        assert_eq!(
+            input_dir(&InputSource::FsPath("README.md".into())),
+            Some(".".into())
+        );
+        assert_eq!(
+            input_dir(&InputSource::FsPath("docs/README.md".into())),
+            Some("docs".into())
+        );
+        assert_eq!(
+            input_dir(&InputSource::FsPath("src".into())),
+            Some("src".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_input_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = fs::canonicalize(dir.path()).unwrap();
+        fs::write(dir.join("changed.md"), "").unwrap();
+        fs::write(dir.join("unchanged.md"), "").unwrap();
+        fs::write(dir.join("changed.png"), "").unwrap();
+
+        let inputs = [Input::from_value(dir.to_str().unwrap()).unwrap()];
+        let changed = HashSet::from([dir.join("changed.md"), dir.join("changed.png")]);
+        let files = input_files(&inputs, &changed, &Config::default())
+            .await
+            .unwrap();
+
+        let expected = Input::from_value(dir.join("changed.md").to_str().unwrap()).unwrap();
+        assert_eq!(files, HashSet::from([expected]));
+    }
+
+    #[tokio::test]
+    async fn test_input_files_in_subdirectories_and_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = fs::canonicalize(dir.path()).unwrap();
+        for subdir in ["docs/guide", ".hidden", "blog"] {
+            fs::create_dir_all(dir.join(subdir)).unwrap();
+        }
+        let changed: HashSet<_> = [
+            "docs/guide/changed.md",
+            ".hidden/changed.md",
+            "blog/post.md",
+        ]
+        .into_iter()
+        .map(|path| dir.join(path))
+        .collect();
+        for path in &changed {
+            fs::write(path, "").unwrap();
+        }
+        fs::write(dir.join("docs/unchanged.md"), "").unwrap();
+
+        let inputs = [
+            Input::from_value(dir.to_str().unwrap()).unwrap(),
+            Input::from_value(&format!("{}/blog/*.md", dir.display())).unwrap(),
+        ];
+        let files = input_files(&inputs, &changed, &Config::default())
+            .await
+            .unwrap();
+
+        let paths: HashSet<_> = files
+            .into_iter()
+            .map(|input| match input.source {
+                InputSource::FsPath(path) => path,
+                source => panic!("unexpected input {source:?}"),
+            })
+            .collect();
+        let expected: HashSet<_> = ["docs/guide/changed.md", "blog/post.md"]
+            .into_iter()
+            .map(|path| dir.join(path))
+            .collect();
+        assert_eq!(paths, expected);
+    }
+}
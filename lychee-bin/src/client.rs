@@ -2,14 +2,21 @@ use crate::config::{Config, HeaderMapExt};
 use crate::parse::parse_remaps;
 use anyhow::{Context, Result};
 use http::{HeaderMap, StatusCode};
-use lychee_lib::{Client, ClientBuilder, ratelimit::RateLimitConfig};
+use lychee_lib::{
+    Client, ClientBuilder,
+    ratelimit::{RateLimitConfig, Recording},
+};
 use regex::RegexSet;
 use reqwest_cookie_store::CookieStoreMutex;
 use std::sync::Arc;
 use std::{collections::HashSet, str::FromStr};
 
 /// Creates a client according to the command-line config
-pub(crate) fn create(cfg: &Config, cookie_jar: Option<&Arc<CookieStoreMutex>>) -> Result<Client> {
+pub(crate) fn create(
+    cfg: &Config,
+    cookie_jar: Option<&Arc<CookieStoreMutex>>,
+    recording: Option<Arc<Recording>>,
+) -> Result<Client> {
     let timeout = cfg.timeout();
     let retry_wait_time = cfg.retry_wait_time();
     let method: reqwest::Method = reqwest::Method::from_str(&cfg.method().to_uppercase())?;
@@ -61,6 +68,7 @@ pub(crate) fn create(cfg: &Config, cookie_jar: Option<&Arc<CookieStoreMutex>>) -
             cfg.host_request_interval,
         ))
         .hosts(cfg.hosts.clone())
+        .recording(recording)
         .build()
         .client()
         .context("Failed to create request client")
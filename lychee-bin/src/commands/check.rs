@@ -15,8 +15,9 @@ use lychee_lib::RequestError;
 use lychee_lib::Status;
 use lychee_lib::archive::Archive;
 use lychee_lib::waiter::{WaitGroup, WaitGuard};
-use lychee_lib::{Client, ErrorKind, Request, Response};
+use lychee_lib::{Client, Crawler, ErrorKind, Request, Response};
 
+use crate::fix::Fixer;
 use crate::formatters::stats::ResponseStats;
 use crate::formatters::suggestion::Suggestion;
 use crate::progress::Progress;
@@ -71,6 +72,8 @@ where
         waiter,
         progress.clone(),
         stats,
+        params.crawler,
+        params.fixer.clone(),
     ));
 
     // Send requests into the channel. Note that this will run within the main task
@@ -110,7 +113,13 @@ where
         .await;
     }
 
-    let is_success = if accept_timeouts {
+    if let Some(fixer) = &params.fixer {
+        fixer.add_suggestions(&stats);
+    }
+
+    let is_success = if let Some(baseline) = &params.baseline {
+        baseline.is_success(&stats, accept_timeouts)
+    } else if accept_timeouts {
         stats.is_success_ignoring_timeouts()
     } else {
         stats.is_success()
@@ -178,13 +187,19 @@ async fn send_requests(
     Ok(())
 }
 
-/// Reads from the request channel and updates the progress bar status
+/// Reads from the request channel and updates the progress bar status.
+///
+/// If a [`Crawler`] is given, the links of crawled pages are sent back into
+/// the request channel. If a [`Fixer`] is given, it collects the permanent
+/// redirects.
 async fn collect_responses(
     recv_resp: mpsc::Receiver<(WaitGuard, Result<Response, ErrorKind>)>,
     send_req: mpsc::Sender<(WaitGuard, Result<Request, RequestError>)>,
     waiter: WaitGroup,
     progress: Progress,
     mut stats: ResponseStats,
+    crawler: Option<Crawler>,
+    fixer: Option<Fixer>,
 ) -> Result<ResponseStats, ErrorKind> {
     // Wrap recv_resp until the WaitGroup finishes, at which time the
     // recv_resp_until_done stream will be closed. The correctness of
@@ -194,16 +209,33 @@ async fn collect_responses(
         .take_until(waiter.wait())
         .boxed();
 
-    while let Some((_guard, response)) = recv_resp_until_done.next().await {
+    while let Some((guard, response)) = recv_resp_until_done.next().await {
         let response = response?;
+
+        if let Some(requests) = crawler.as_ref().and_then(|c| c.crawl(&response)) {
+            // Sending happens in a separate task, because the request channel
+            // can only make progress while we keep receiving responses.
+            // The cloned guard keeps the WaitGroup alive until all links of
+            // the crawled page are sent.
+            let send_req = send_req.clone();
+            let progress = progress.clone();
+            tokio::spawn(async move {
+                let _ = send_requests(requests, guard, send_req, &progress).await;
+            });
+        }
+
+        if let Some(fixer) = &fixer {
+            fixer.add_response(&response);
+        }
+
         progress.update(Some(response.body()));
         stats.add(response);
     }
 
-    // unused for now, but will be used for recursion eventually. by holding
-    // an extra `send_req` endpoint, we prevent the natural termination when
-    // each channel finishes and closes. instead, we rely on the WaitGroup to
-    // break the cyclic channels.
+    // By holding an extra `send_req` endpoint, we prevent the natural
+    // termination when each channel finishes and closes. Instead, we rely on
+    // the WaitGroup to break the cyclic channels, which allows crawled pages
+    // to send more requests.
     let _ = send_req;
     Ok(stats)
 }
@@ -273,7 +305,7 @@ async fn check_url(client: &Client, request: Request) -> Response {
 /// a *user-provided* input argument. Other errors, including errors in
 /// link resolution and in resolved inputs, will be returned as Ok with
 /// a failed response.
-async fn handle(
+pub(crate) async fn handle(
     client: &Client,
     cache: &Cache,
     cache_exclude_status: HashSet<StatusCode>,
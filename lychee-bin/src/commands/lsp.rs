@@ -0,0 +1,560 @@
+//! Language server for editor diagnostics (`lychee lsp`).
+//!
+//! The server speaks the Language Server Protocol over stdin and stdout. It
+//! checks the links of open Markdown and HTML documents and publishes a
+//! diagnostic for every broken or permanently redirected link, so that editors
+//! can underline them while typing. Code actions replace links with their
+//! redirect target or, with `--suggest`, with their archived version.
+//!
+//! Links are checked with the same client and cache as regular runs, so all
+//! options (e.g. `--accept` or `--exclude`) apply to the editor, too.
+
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow};
+use dashmap::DashMap;
+use futures::StreamExt;
+use http::StatusCode;
+use log::warn;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, Position, PublishDiagnosticsParams,
+    Range, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri,
+    WorkspaceEdit,
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Exit, Notification as _,
+        PublishDiagnostics,
+    },
+    request::{CodeActionRequest, Request as _, Shutdown},
+};
+use lychee_lib::{
+    Client, Collector, FileType, InputContent, RawUriSpan, ResolvedInputSource,
+    Response as LinkResponse, archive::Archive,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use url::Url;
+
+use super::check::handle;
+use crate::cache::Cache;
+use crate::config::{Config, LYCHEE_CACHE_FILE};
+
+/// Time to wait after a change before checking a document,
+/// so that links aren't checked on every keystroke
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Name of the server in diagnostics
+const SOURCE: &str = "lychee";
+
+/// An open document
+struct Document {
+    version: i32,
+    language_id: String,
+    text: String,
+}
+
+/// The URL a code action replaces a link with.
+/// It is stored in the `data` of the diagnostic of the link.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Replacement {
+    replacement: Url,
+}
+
+#[derive(Clone)]
+struct Server {
+    connection: Arc<Connection>,
+    client: Client,
+    cache: Arc<Cache>,
+    collector: Collector,
+    cache_exclude_status: HashSet<StatusCode>,
+    accept: HashSet<StatusCode>,
+    max_concurrency: usize,
+    /// Archive and timeout to look up replacements for broken links with
+    archive: Option<(Archive, Duration)>,
+    /// Archived versions of broken URLs, to look each up only once
+    suggestions: Arc<DashMap<Url, Option<Url>>>,
+    documents: Arc<Mutex<HashMap<Uri, Document>>>,
+}
+
+/// Run the language server until the editor asks it to exit
+pub(crate) async fn lsp(
+    client: Client,
+    cache: Cache,
+    collector: Collector,
+    cfg: &Config,
+) -> Result<()> {
+    let (connection, _io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    })?;
+    let connection = tokio::task::spawn_blocking(move || {
+        connection
+            .initialize(capabilities)
+            .map(|_| connection)
+            .map_err(|e| anyhow!("{e}"))
+    })
+    .await?
+    .context("Cannot initialize language server")?;
+
+    let server = Server {
+        connection: Arc::new(connection),
+        client,
+        cache: Arc::new(cache),
+        collector,
+        cache_exclude_status: cfg.cache_exclude_status().into(),
+        accept: cfg.accept().into(),
+        max_concurrency: cfg.max_concurrency().get(),
+        archive: cfg.suggest().then(|| (cfg.archive(), cfg.timeout())),
+        suggestions: Arc::default(),
+        documents: Arc::default(),
+    };
+
+    // The connection is blocking, so messages are received on a separate thread
+    let (send_message, mut messages) = mpsc::unbounded_channel();
+    let receiver = server.connection.receiver.clone();
+    thread::spawn(move || {
+        for message in receiver {
+            if send_message.send(message).is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(message) = messages.recv().await {
+        match message {
+            Message::Request(request) => server.handle_request(request),
+            Message::Notification(notification) if notification.method == Exit::METHOD => break,
+            Message::Notification(notification) => server.handle_notification(notification),
+            Message::Response(_) => {}
+        }
+    }
+
+    if cfg.cache() {
+        server.cache.store(LYCHEE_CACHE_FILE)?;
+    }
+    Ok(())
+}
+
+impl Server {
+    fn send(&self, message: impl Into<Message>) {
+        if self.connection.sender.send(message.into()).is_err() {
+            warn!("Cannot send message to the language client");
+        }
+    }
+
+    fn handle_request(&self, request: Request) {
+        let id = request.id.clone();
+        let response = match request.method.as_str() {
+            Shutdown::METHOD => Response::new_ok(id, ()),
+            CodeActionRequest::METHOD => match request.extract(CodeActionRequest::METHOD) {
+                Ok((id, params)) => Response::new_ok(id, code_actions(params)),
+                Err(e) => Response::new_err(id, ErrorCode::InvalidParams as i32, e.to_string()),
+            },
+            _ => Response::new_err(
+                id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {}", request.method),
+            ),
+        };
+        self.send(response);
+    }
+
+    fn handle_notification(&self, notification: Notification) {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                if let Ok(params) =
+                    notification.extract::<DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)
+                {
+                    let document = params.text_document;
+                    self.update(
+                        document.uri,
+                        Document {
+                            version: document.version,
+                            language_id: document.language_id,
+                            text: document.text,
+                        },
+                        Duration::ZERO,
+                    );
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Ok(mut params) = notification
+                    .extract::<DidChangeTextDocumentParams>(DidChangeTextDocument::METHOD)
+                    && let Some(change) = params.content_changes.pop()
+                {
+                    let uri = params.text_document.uri;
+                    let language_id = self
+                        .documents
+                        .lock()
+                        .unwrap()
+                        .get(&uri)
+                        .map(|document| document.language_id.clone())
+                        .unwrap_or_default();
+                    self.update(
+                        uri,
+                        Document {
+                            version: params.text_document.version,
+                            language_id,
+                            text: change.text,
+                        },
+                        DEBOUNCE,
+                    );
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Ok(params) =
+                    notification.extract::<DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)
+                {
+                    let uri = params.text_document.uri;
+                    self.documents.lock().unwrap().remove(&uri);
+                    self.publish(uri, Vec::new(), None);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Store the new state of a document and check its links after `delay`,
+    /// unless it changed again in the meantime
+    fn update(&self, uri: Uri, document: Document, delay: Duration) {
+        let version = document.version;
+        let Some(content) = input_content(&uri, &document) else {
+            return;
+        };
+        self.documents.lock().unwrap().insert(uri.clone(), document);
+
+        let server = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if !server.is_current(&uri, version) {
+                return;
+            }
+            let diagnostics = server.diagnostics(&content).await;
+            if server.is_current(&uri, version) {
+                server.publish(uri, diagnostics, Some(version));
+            }
+        });
+    }
+
+    fn is_current(&self, uri: &Uri, version: i32) -> bool {
+        self.documents
+            .lock()
+            .unwrap()
+            .get(uri)
+            .is_some_and(|document| document.version == version)
+    }
+
+    fn publish(&self, uri: Uri, diagnostics: Vec<Diagnostic>, version: Option<i32>) {
+        self.send(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version,
+            },
+        ));
+    }
+
+    /// Check all links of a document
+    async fn diagnostics(&self, content: &InputContent) -> Vec<Diagnostic> {
+        let requests = self.collector.collect_links_from_content(content);
+        futures::stream::iter(requests)
+            .map(|request| async {
+                let response = handle(
+                    &self.client,
+                    &self.cache,
+                    self.cache_exclude_status.clone(),
+                    request,
+                    &self.accept,
+                )
+                .await
+                .ok()?;
+                self.diagnostic(&response, &content.content).await
+            })
+            .buffer_unordered(self.max_concurrency)
+            .filter_map(|diagnostic| async { diagnostic })
+            .collect()
+            .await
+    }
+
+    /// Create a diagnostic for broken and permanently redirected links
+    async fn diagnostic(&self, response: &LinkResponse, text: &str) -> Option<Diagnostic> {
+        let status = response.status();
+        let body = response.body();
+        let url = Url::parse(body.uri.as_str()).ok();
+
+        let (severity, message, replacement) = if status.is_error() {
+            let severity = if status.is_timeout() {
+                DiagnosticSeverity::WARNING
+            } else {
+                DiagnosticSeverity::ERROR
+            };
+            let replacement = match &url {
+                Some(url) => self.suggestion(url).await,
+                None => None,
+            };
+            (
+                severity,
+                format!("{} | {}", body.uri, status.details()),
+                replacement,
+            )
+        } else if status.is_success()
+            && let Some(target) = response.redirects().and_then(|r| r.permanent_destination())
+        {
+            let message = format!("{} is permanently redirected to {target}", body.uri);
+            (DiagnosticSeverity::HINT, message, Some(target.clone()))
+        } else {
+            return None;
+        };
+
+        let replacement = replacement.map(|mut replacement| {
+            if replacement.fragment().is_none() {
+                replacement.set_fragment(url.as_ref().and_then(Url::fragment));
+            }
+            Replacement { replacement }
+        });
+
+        Some(Diagnostic {
+            range: link_range(text, body.span?, url.as_ref()),
+            severity: Some(severity),
+            source: Some(SOURCE.to_string()),
+            message,
+            data: replacement.and_then(|r| serde_json::to_value(r).ok()),
+            ..Diagnostic::default()
+        })
+    }
+
+    /// Look up the archived version of a broken URL, if `--suggest` is enabled
+    async fn suggestion(&self, url: &Url) -> Option<Url> {
+        let (archive, timeout) = self.archive.as_ref()?;
+        if let Some(suggestion) = self.suggestions.get(url) {
+            return suggestion.clone();
+        }
+        let suggestion = archive
+            .get_archive_snapshot(url, *timeout)
+            .await
+            .ok()
+            .flatten();
+        self.suggestions.insert(url.clone(), suggestion.clone());
+        suggestion
+    }
+}
+
+/// The content of a document, if it is a Markdown or HTML document
+fn input_content(uri: &Uri, document: &Document) -> Option<InputContent> {
+    let path = Url::parse(uri.as_str())
+        .ok()
+        .filter(|url| url.scheme() == "file")
+        .and_then(|url| url.to_file_path().ok());
+
+    let file_type = match document.language_id.as_str() {
+        "markdown" => FileType::Markdown,
+        "html" => FileType::Html,
+        _ => path
+            .as_deref()
+            .and_then(Path::extension)
+            .and_then(|extension| FileType::from_extension(&extension.to_string_lossy()))
+            .filter(|file_type| matches!(file_type, FileType::Markdown | FileType::Html))?,
+    };
+
+    // Unsaved documents don't have a path to resolve relative links against
+    let source = path.map_or_else(
+        || ResolvedInputSource::String(document.text.clone().into()),
+        ResolvedInputSource::FsPath,
+    );
+
+    Some(InputContent::new(source, file_type, document.text.clone()))
+}
+
+/// The range of a link in the document.
+///
+/// The link is searched for in its line, starting at the column of its span.
+/// If it cannot be found (e.g. because it is a relative link), the range
+/// covers the text up to the next character which usually ends a link.
+fn link_range(text: &str, span: RawUriSpan, url: Option<&Url>) -> Range {
+    let line_index = span.line.get() - 1;
+    let line = text.lines().nth(line_index).unwrap_or_default();
+    let start = span
+        .column
+        .and_then(|column| line.char_indices().nth(column.get() - 1))
+        .map_or(0, |(offset, _)| offset);
+
+    let found = url.and_then(|url| {
+        // The URL may have been normalized when it was parsed,
+        // e.g. `https://example.com` becomes `https://example.com/`.
+        // The first occurrence wins, so that a following link to a subpage
+        // isn't mistaken for the link.
+        let text = url.as_str();
+        [Some(text), text.strip_suffix('/')]
+            .into_iter()
+            .flatten()
+            .filter_map(|candidate| {
+                line[start..]
+                    .find(candidate)
+                    .map(|offset| (start + offset, start + offset + candidate.len()))
+            })
+            .min_by_key(|&(start, end)| (start, Reverse(end)))
+    });
+    let (start, end) = found.unwrap_or_else(|| {
+        let len = line[start..]
+            .find(|c: char| c.is_whitespace() || "()[]<>\"'`".contains(c))
+            .unwrap_or(line.len() - start);
+        (start, start + len)
+    });
+
+    // Positions are counted in UTF-16 code units
+    let character =
+        |offset: usize| u32::try_from(line[..offset].encode_utf16().count()).unwrap_or(0);
+    let line = u32::try_from(line_index).unwrap_or(u32::MAX);
+    Range::new(
+        Position::new(line, character(start)),
+        Position::new(line, character(end)),
+    )
+}
+
+/// Quick fixes for all diagnostics with a replacement
+fn code_actions(params: CodeActionParams) -> Vec<CodeActionOrCommand> {
+    let uri = params.text_document.uri;
+    params
+        .context
+        .diagnostics
+        .into_iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some(SOURCE))
+        .filter_map(|diagnostic| {
+            let Replacement { replacement } =
+                serde_json::from_value(diagnostic.data.clone()?).ok()?;
+            let edit = TextEdit::new(diagnostic.range, replacement.to_string());
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Replace with {replacement}"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic]),
+                edit: Some(WorkspaceEdit::new(HashMap::from([(
+                    uri.clone(),
+                    vec![edit],
+                )]))),
+                is_preferred: Some(true),
+                ..CodeAction::default()
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use lsp_types::{CodeActionContext, TextDocumentIdentifier};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn span(line: usize, column: Option<usize>) -> RawUriSpan {
+        RawUriSpan::new(
+            NonZeroUsize::new(line).unwrap(),
+            column.and_then(NonZeroUsize::new),
+        )
+    }
+
+    fn range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[test]
+    fn test_link_range() {
+        let text = "# Links\n\nSee [docs](https://example.com) and <https://example.com/a>\n";
+        let url = Url::parse("https://example.com").unwrap();
+        assert_eq!(
+            link_range(text, span(3, Some(5)), Some(&url)),
+            range(2, 11, 30)
+        );
+
+        let url = Url::parse("https://example.com/a").unwrap();
+        assert_eq!(
+            link_range(text, span(3, None), Some(&url)),
+            range(2, 37, 58)
+        );
+    }
+
+    #[test]
+    fn test_link_range_fallback() {
+        // Relative links are resolved, so they're not found in the text
+        let text = "[ä](./missing.md)";
+        let url = Url::parse("file:///docs/missing.md").unwrap();
+        assert_eq!(
+            link_range(text, span(1, Some(5)), Some(&url)),
+            range(0, 4, 16)
+        );
+    }
+
+    #[test]
+    fn test_code_actions() {
+        let uri: Uri = "file:///README.md".parse().unwrap();
+        let replacement = Url::parse("https://example.com/new").unwrap();
+        let diagnostic = |source: &str| Diagnostic {
+            range: range(0, 1, 20),
+            source: Some(source.to_string()),
+            data: serde_json::to_value(Replacement {
+                replacement: replacement.clone(),
+            })
+            .ok(),
+            ..Diagnostic::default()
+        };
+
+        let actions = code_actions(CodeActionParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            range: range(0, 0, 0),
+            context: CodeActionContext {
+                diagnostics: vec![diagnostic(SOURCE), diagnostic("other")],
+                ..CodeActionContext::default()
+            },
+            work_done_progress_params: lsp_types::WorkDoneProgressParams::default(),
+            partial_result_params: lsp_types::PartialResultParams::default(),
+        });
+
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected a code action");
+        };
+        assert_eq!(action.title, "Replace with https://example.com/new");
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(
+            edits,
+            &vec![TextEdit::new(range(0, 1, 20), replacement.to_string())]
+        );
+    }
+
+    #[test]
+    fn test_input_content() {
+        let document = |language_id: &str| Document {
+            version: 1,
+            language_id: language_id.to_string(),
+            text: "https://example.com".to_string(),
+        };
+
+        let uri: Uri = "file:///docs/index.txt".parse().unwrap();
+        assert!(input_content(&uri, &document("plaintext")).is_none());
+
+        let content = input_content(&uri, &document("markdown")).unwrap();
+        assert_eq!(content.file_type, FileType::Markdown);
+        assert_eq!(
+            content.source,
+            ResolvedInputSource::FsPath("/docs/index.txt".into())
+        );
+
+        let uri: Uri = "untitled:Untitled-1".parse().unwrap();
+        let content = input_content(&uri, &document("html")).unwrap();
+        assert_eq!(content.file_type, FileType::Html);
+        assert!(matches!(content.source, ResolvedInputSource::String(_)));
+    }
+}
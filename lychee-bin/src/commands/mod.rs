@@ -2,19 +2,25 @@ pub(crate) mod check;
 pub(crate) mod dump;
 pub(crate) mod dump_inputs;
 pub(crate) mod generate;
+pub(crate) mod lsp;
+pub(crate) mod serve;
 
 pub(crate) use check::check;
 pub(crate) use dump::dump;
 pub(crate) use dump_inputs::dump_inputs;
+pub(crate) use lsp::lsp;
+pub(crate) use serve::serve;
 
 use std::fs;
 use std::io::{self, Write};
 use std::path::PathBuf;
 
+use crate::baseline::Baseline;
 use crate::cache::Cache;
 use crate::config::Config;
+use crate::fix::Fixer;
 use lychee_lib::RequestError;
-use lychee_lib::{Client, Request};
+use lychee_lib::{Client, Crawler, Request};
 
 /// Parameters passed to every command
 pub(crate) struct CommandParams<S: futures::Stream<Item = Result<Request, RequestError>>> {
@@ -23,6 +29,12 @@ pub(crate) struct CommandParams<S: futures::Stream<Item = Result<Request, Reques
     pub(crate) requests: S,
     pub(crate) cfg: Config,
     pub(crate) is_stdin_input: bool,
+    /// Crawls remote inputs recursively if `--crawl` is enabled
+    pub(crate) crawler: Option<Crawler>,
+    /// Collects links to rewrite if `--fix` is enabled
+    pub(crate) fixer: Option<Fixer>,
+    /// Known broken links which don't fail the run (`--baseline`)
+    pub(crate) baseline: Option<Baseline>,
 }
 
 /// Creates a writer that outputs to a file or stdout.
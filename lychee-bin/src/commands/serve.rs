@@ -0,0 +1,283 @@
+//! HTTP service which checks links on request (`lychee serve`).
+//!
+//! Other tools can validate links without spawning lychee for every check.
+//! All requests share one client and cache, so the rate limits of each host
+//! are respected across requests and links are only checked once while they
+//! are cached.
+//!
+//! The service has a single endpoint, `POST /check`, which accepts either a
+//! list of URLs:
+//!
+//! ```json
+//! { "urls": ["https://example.com", "https://example.com/missing"] }
+//! ```
+//!
+//! or a document to extract the links from:
+//!
+//! ```json
+//! {
+//!   "content": "See [the docs](https://example.com/docs/)",
+//!   "file_type": "md",
+//!   "base_url": "https://example.com/"
+//! }
+//! ```
+//!
+//! It responds with the results of all links, in the same format as the
+//! JSON output of regular runs:
+//!
+//! ```json
+//! { "results": [{ "url": "https://example.com/", "status": { "text": "200 OK", "code": 200 } }] }
+//! ```
+//!
+//! Only `http` and `https` links are checked, so that clients can't probe the
+//! files of the machine lychee runs on. Other URLs and base URLs are rejected,
+//! and other links in documents are reported as excluded.
+
+use std::{collections::HashSet, net::SocketAddr, sync::Arc};
+
+use anyhow::{Context, Result};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode as HttpStatus,
+    response::{IntoResponse, Response as HttpResponse},
+    routing::post,
+};
+use futures::StreamExt;
+use http::StatusCode;
+use log::info;
+use lychee_lib::{
+    Client, Collector, FileType, InputContent, Request, RequestError, ResolvedInputSource,
+    Response, Status, Uri,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::check::handle;
+use crate::cache::Cache;
+use crate::config::{Config, LYCHEE_CACHE_FILE};
+
+/// State shared by all requests
+struct Service {
+    client: Client,
+    cache: Cache,
+    collector: Collector,
+    cache_exclude_status: HashSet<StatusCode>,
+    accept: HashSet<StatusCode>,
+    max_concurrency: usize,
+}
+
+/// The links to check
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CheckRequest {
+    /// A list of URLs
+    Urls { urls: Vec<String> },
+    /// A document to extract the links from
+    Document {
+        content: String,
+        /// File extension or type of the document, e.g. `md` or `html`.
+        /// Documents are treated as plain text by default.
+        #[serde(default)]
+        file_type: Option<String>,
+        /// URL of the document, which relative links are resolved against
+        #[serde(default)]
+        base_url: Option<Url>,
+    },
+}
+
+#[derive(Serialize)]
+struct CheckResponse {
+    results: Vec<Response>,
+}
+
+/// A request which cannot be handled, e.g. because of an invalid URL
+struct BadRequest(String);
+
+impl IntoResponse for BadRequest {
+    fn into_response(self) -> HttpResponse {
+        let body = Json(serde_json::json!({ "error": self.0 }));
+        (HttpStatus::BAD_REQUEST, body).into_response()
+    }
+}
+
+/// Serve link checks over HTTP until lychee is stopped with Ctrl-C
+pub(crate) async fn serve(
+    listen: SocketAddr,
+    client: Client,
+    cache: Cache,
+    collector: Collector,
+    cfg: &Config,
+) -> Result<()> {
+    let service = Arc::new(Service {
+        client,
+        cache,
+        collector,
+        cache_exclude_status: cfg.cache_exclude_status().into(),
+        accept: cfg.accept().into(),
+        max_concurrency: cfg.max_concurrency().get(),
+    });
+    let app = Router::new()
+        .route("/check", post(check))
+        .with_state(service.clone());
+
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Cannot listen on {listen}"))?;
+    info!("Listening on http://{}", listener.local_addr()?);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
+    if cfg.cache() {
+        service.cache.store(LYCHEE_CACHE_FILE)?;
+    }
+    Ok(())
+}
+
+async fn check(
+    State(service): State<Arc<Service>>,
+    Json(request): Json<CheckRequest>,
+) -> Result<Json<CheckResponse>, BadRequest> {
+    let requests = requests(&service.collector, request)?;
+    let results = futures::stream::iter(requests)
+        .map(async |request| match request {
+            Ok(request) if !is_http(&request.uri) => Ok(Response::new(
+                request.uri,
+                Status::Excluded,
+                None,
+                None,
+                request.source.into(),
+                request.span,
+                None,
+            )),
+            request => {
+                handle(
+                    &service.client,
+                    &service.cache,
+                    service.cache_exclude_status.clone(),
+                    request,
+                    &service.accept,
+                )
+                .await
+            }
+        })
+        .buffered(service.max_concurrency)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .map_err(|e| BadRequest(e.to_string()))?;
+    Ok(Json(CheckResponse { results }))
+}
+
+/// Whether the URI is checked by the service, see the module documentation
+fn is_http(uri: &Uri) -> bool {
+    matches!(uri.scheme(), "http" | "https")
+}
+
+/// Create the requests for all links to check.
+/// Requests for URLs other than `http` and `https` are rejected.
+fn requests(
+    collector: &Collector,
+    request: CheckRequest,
+) -> Result<Vec<Result<Request, RequestError>>, BadRequest> {
+    match request {
+        CheckRequest::Urls { urls } => urls
+            .iter()
+            .map(|url| {
+                let request = Request::try_from(url.as_str())
+                    .map_err(|e| BadRequest(format!("Invalid URL '{url}': {e}")))?;
+                if !is_http(&request.uri) {
+                    return Err(BadRequest(format!(
+                        "Invalid URL '{url}': only http and https URLs can be checked"
+                    )));
+                }
+                Ok(Ok(request))
+            })
+            .collect(),
+        CheckRequest::Document {
+            content,
+            file_type,
+            base_url,
+        } => {
+            let file_type = match file_type {
+                Some(file_type) => FileType::from_extension(&file_type)
+                    .ok_or_else(|| BadRequest(format!("Unknown file type '{file_type}'")))?,
+                None => FileType::Plaintext,
+            };
+            let source = match base_url {
+                Some(url) if !matches!(url.scheme(), "http" | "https") => {
+                    return Err(BadRequest(format!(
+                        "Invalid base URL '{url}': only http and https URLs are supported"
+                    )));
+                }
+                Some(url) => ResolvedInputSource::RemoteUrl(Box::new(url)),
+                None => ResolvedInputSource::String(content.clone().into()),
+            };
+            Ok(
+                collector
+                    .collect_links_from_content(&InputContent::new(source, file_type, content)),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lychee_lib::BaseInfo;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn collector() -> Collector {
+        Collector::new(None, BaseInfo::none()).unwrap()
+    }
+
+    fn urls(request: &str) -> Vec<String> {
+        let request = serde_json::from_str(request).unwrap();
+        requests(&collector(), request)
+            .unwrap_or_else(|e| panic!("{}", e.0))
+            .into_iter()
+            .map(|request| request.unwrap().uri.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_url_list() {
+        assert_eq!(
+            urls(r#"{"urls": ["https://example.com/b", "https://example.com/a"]}"#),
+            ["https://example.com/b", "https://example.com/a"]
+        );
+
+        for url in ["not a url", "file:///etc/passwd", "/etc/passwd"] {
+            let request = serde_json::from_value(serde_json::json!({ "urls": [url] })).unwrap();
+            assert!(requests(&collector(), request).is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn test_document() {
+        assert_eq!(
+            urls(
+                r#"{
+                    "content": "[docs](docs/) <https://example.com/other>",
+                    "file_type": "md",
+                    "base_url": "https://example.com/index.html"
+                }"#
+            ),
+            ["https://example.com/docs/", "https://example.com/other"]
+        );
+
+        let request = serde_json::from_str(r#"{"content": "", "file_type": "unknown"}"#).unwrap();
+        assert!(requests(&collector(), request).is_err());
+
+        let request =
+            serde_json::from_str(r#"{"content": "[a](a.md)", "base_url": "file:///etc/"}"#)
+                .unwrap();
+        assert!(requests(&collector(), request).is_err());
+    }
+}
@@ -19,20 +19,22 @@ use crate::files_from::FilesFrom;
 use crate::generate::GenerateMode;
 use crate::parse::parse_base_info;
 use crate::verbosity::Verbosity;
-use anyhow::{Context, Result, anyhow};
-use clap::Parser;
+use anyhow::{Context, Result, anyhow, bail};
 use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Parser, Subcommand};
 use const_format::formatcp;
 use lychee_lib::ratelimit::HostConfigs;
 use lychee_lib::{
-    BaseInfo, BasicAuthSelector, DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_RETRIES,
+    BaseInfo, BasicAuthSelector, CrawlScope, DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_RETRIES,
     DEFAULT_RETRY_WAIT_TIME_SECS, DEFAULT_TIMEOUT_SECS, FileExtensions, FileType,
     FragmentCheckerOptions, Input, StatusCodeSelector, archive::Archive,
+    crawl::DEFAULT_CRAWL_DEPTH, extract::XmlSelector,
 };
 use lychee_lib::{DEFAULT_USER_AGENT, Preprocessor};
 use secrecy::SecretString;
 use serde::{Deserialize, Deserializer};
 use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::path::Path;
 use std::{path::PathBuf, time::Duration};
@@ -110,7 +112,7 @@ impl From<FragmentMode> for FragmentCheckerOptions {
 ///
 /// lychee is powered by lychee-lib, the Rust library for link checking.
 #[derive(Parser, Debug)]
-#[command(version, about, next_display_order = None)]
+#[command(version, about, next_display_order = None, subcommand_negates_reqs = true, disable_help_subcommand = true)]
 pub(crate) struct LycheeOptions {
     /// Inputs for link checking (where to get links to check from).
     /// These can be: files (e.g. `README.md`), file globs (e.g. `'~/git/*/README.md'`),
@@ -133,6 +135,34 @@ pub(crate) struct LycheeOptions {
 
     #[clap(flatten)]
     pub(crate) config: Config,
+
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+/// Commands which run lychee in another mode than checking the given inputs
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Command {
+    /// Run a language server for editors
+    ///
+    /// The server checks the links of the Markdown and HTML documents open in
+    /// an editor and reports broken links as diagnostics. It communicates over
+    /// stdin and stdout. Options are passed before the command, e.g.
+    /// `lychee --suggest lsp`.
+    Lsp,
+
+    /// Run an HTTP service which checks links on request
+    ///
+    /// Clients send a list of URLs or a document to `POST /check` and receive
+    /// the results as JSON. All requests share one client and cache, so rate
+    /// limits are respected across requests. Only `http` and `https` links
+    /// are checked. Options are passed before the command, e.g.
+    /// `lychee --cache serve --listen 127.0.0.1:8080`.
+    Serve {
+        /// Address to listen on, e.g. `127.0.0.1:8080`
+        #[arg(long)]
+        listen: SocketAddr,
+    },
 }
 
 impl LycheeOptions {
@@ -224,7 +254,7 @@ pub(crate) struct Config {
     /// This is useful when the default extensions are not enough and you don't
     /// want to provide a long list of inputs (e.g. file1.html, file2.md, etc.)
     ///
-    /// [default: md,mkd,mdx,mdown,mdwn,mkdn,mkdown,markdown,html,htm,css,txt,xml]
+    /// [default: md,mkd,mdx,mdown,mdwn,mkdn,mkdown,markdown,html,htm,css,txt,xml,svg,rst,adoc,asciidoc,ipynb]
     #[arg(long, verbatim_doc_comment)]
     extensions: Option<FileExtensions>,
 
@@ -232,6 +262,8 @@ pub(crate) struct Config {
     ///
     /// This is useful for files without extensions or with unknown extensions.
     /// The extension will be used to determine the file type for processing.
+    /// Without it, the file type of these files and of stdin is guessed from
+    /// their content (HTML, XML, CSS or Markdown), falling back to plaintext.
     ///
     /// Examples:
     ///   --default-extension md
@@ -239,6 +271,37 @@ pub(crate) struct Config {
     #[arg(long, value_name = "EXTENSION", verbatim_doc_comment)]
     default_extension: Option<String>,
 
+    /// Only check the links of values with the given key paths in JSON, YAML
+    /// and TOML files. By default, the links of all values are checked.
+    ///
+    /// Key paths are keys separated by dots, where `*` matches any key and
+    /// `**` any number of keys. Array indices are not part of key paths.
+    /// Values nested below a matching key are checked as well.
+    ///
+    /// Examples:
+    ///   --key-path homepage --key-path repository
+    ///   --key-path 'servers.url' --key-path '**.externalDocs.url'
+    ///
+    /// These files are not checked by default.
+    /// Add their extensions with `--extensions` to check them in directories.
+    #[arg(long, value_name = "KEY_PATH", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) key_path: Vec<String>,
+
+    /// Extract links from the given XML elements or attributes, for XML formats
+    /// other than sitemaps, RSS and Atom feeds, and SVG.
+    ///
+    /// Use `ELEMENT@ATTRIBUTE` to select an attribute, or `ELEMENT` to select
+    /// the text of an element. Elements match with or without namespace prefix.
+    ///
+    /// Examples:
+    ///   --xml-selector ulink@url --xml-selector uri    (DocBook)
+    ///   --xml-selector xref@href                       (DITA)
+    ///   --xml-selector url                             (Maven POM)
+    #[arg(long, value_name = "ELEMENT[@ATTRIBUTE]", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) xml_selector: Vec<XmlSelector>,
+
     #[arg(help = HELP_MSG_CACHE)]
     #[arg(long, optional_bool_flag())]
     #[serde(default)]
@@ -268,6 +331,32 @@ pub(crate) struct Config {
     #[arg(long, verbatim_doc_comment)]
     cache_exclude_status: Option<StatusCodeSelector>,
 
+    /// Recursively crawl websites given as input URLs.
+    ///
+    /// Every HTML page that is linked from a remote input, lies within the
+    /// `--crawl-scope`, and could be fetched successfully is used as a new input,
+    /// so that its links get checked as well. Each page is only crawled once.
+    #[arg(long, verbatim_doc_comment, optional_bool_flag())]
+    #[serde(default)]
+    crawl: Option<bool>,
+
+    /// Maximum link depth when crawling websites with `--crawl`.
+    /// Links on the input pages have a depth of 1.
+    ///
+    /// [default: 5]
+    #[arg(long, verbatim_doc_comment)]
+    crawl_depth: Option<usize>,
+
+    /// Which pages to follow when crawling websites with `--crawl`.
+    ///
+    /// - origin: pages with the same scheme, host, and port as an input URL
+    /// - prefix: pages below the directory of an input URL
+    ///   (e.g. `https://example.com/docs/` only follows `/docs/...` pages)
+    ///
+    /// [default: prefix]
+    #[arg(long, verbatim_doc_comment, value_parser = PossibleValuesParser::new(CrawlScope::VARIANTS).map(|s| s.parse::<CrawlScope>().unwrap()))]
+    crawl_scope: Option<CrawlScope>,
+
     /// Don't perform any link checking.
     /// Instead, dump all the links extracted from inputs that would be checked
     #[arg(long, optional_bool_flag())]
@@ -292,6 +381,50 @@ pub(crate) struct Config {
     #[serde(default)]
     suggest: Option<bool>,
 
+    /// Rewrite links in local input files.
+    ///
+    /// Permanently redirected URLs (301, 308) are replaced with their final
+    /// target. Together with `--suggest`, broken URLs are replaced with their
+    /// archived version. Files which changed since their links were extracted
+    /// are left untouched.
+    #[arg(long, verbatim_doc_comment, optional_bool_flag())]
+    #[serde(default)]
+    fix: Option<bool>,
+
+    /// Print the changes of `--fix` as a diff to stderr instead of modifying any files
+    #[arg(long, optional_bool_flag())]
+    #[serde(default)]
+    dry_run: Option<bool>,
+
+    /// Don't fail on broken links which are listed in the given baseline file.
+    ///
+    /// The baseline maps input sources to known broken URLs. It can be created
+    /// with `--update-baseline`; the JSON report of a previous run
+    /// (`--format json`) works as a baseline, too. Known broken links are
+    /// still reported, but only new failures affect the exit code.
+    #[arg(long, verbatim_doc_comment)]
+    pub(crate) baseline: Option<PathBuf>,
+
+    /// Write all broken links of this run to the `--baseline` file,
+    /// replacing its contents. The run doesn't fail on broken links then.
+    #[arg(long, optional_bool_flag())]
+    #[serde(default)]
+    update_baseline: Option<bool>,
+
+    /// Keep running and check the links of local input files again whenever
+    /// they are created or modified. Only the changed files are checked, and
+    /// responses are cached across runs. Stop with Ctrl-C.
+    #[arg(long, verbatim_doc_comment, optional_bool_flag())]
+    #[serde(default)]
+    watch: Option<bool>,
+
+    /// Only check the local input files which were added or modified since
+    /// the given git commit, including staged changes, e.g. `main` or `HEAD~3`.
+    /// Each input is compared in its own git repository. Other inputs, such as
+    /// URLs, are checked as usual. If no local files changed, only those are checked.
+    #[arg(long, value_name = "REF", verbatim_doc_comment)]
+    pub(crate) changed_since: Option<String>,
+
     /// Maximum number of allowed redirects
     ///
     /// [default: 10]
@@ -658,6 +791,19 @@ pub(crate) struct Config {
     #[arg(long, verbatim_doc_comment)]
     pub(crate) cookie_jar: Option<PathBuf>,
 
+    /// Save all HTTP responses of this run to the given file,
+    /// so that they can be served with `--replay` later.
+    /// Together with `--replay`, only the responses missing from the replayed
+    /// file are requested and the updated recording is saved.
+    #[arg(long, verbatim_doc_comment)]
+    pub(crate) record: Option<PathBuf>,
+
+    /// Serve HTTP responses from a file written with `--record` instead of
+    /// sending requests. Links without a recorded response fail.
+    /// This makes runs reproducible and allows checking without network access.
+    #[arg(long, verbatim_doc_comment)]
+    pub(crate) replay: Option<PathBuf>,
+
     #[allow(clippy::doc_markdown)]
     /// Check WikiLinks in Markdown files, this requires specifying --base-url
     #[clap(requires = "base_url")]
@@ -681,7 +827,7 @@ pub(crate) struct Config {
     /// *.pdf)
     ///     exec pdftohtml -i -s -stdout "$1"
     ///     ;;
-    /// *.odt|*.docx|*.epub|*.ipynb)
+    /// *.odt|*.docx|*.epub)
     ///     exec pandoc "$1" --to=html --wrap=none
     ///     ;;
     /// *)
@@ -717,6 +863,22 @@ impl Config {
         loaders::load_from_file(path)
     }
 
+    /// Reject combinations of options which can't work together.
+    /// Must be called after all configuration files are merged.
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.watch() {
+            if self.dump() || self.dump_inputs() {
+                bail!("`--watch` cannot be combined with `--dump` or `--dump-inputs`");
+            }
+            if self.update_baseline() {
+                bail!(
+                    "`--watch` cannot be combined with `--update-baseline`, because only the links of changed files are checked again"
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Request timeout
     pub(crate) fn timeout(&self) -> Duration {
         let seconds = self.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS);
@@ -805,6 +967,20 @@ impl Config {
         self.cache.unwrap_or(false)
     }
 
+    pub(crate) fn crawl(&self) -> bool {
+        self.crawl.unwrap_or(false)
+    }
+
+    /// Maximum link depth when crawling
+    pub(crate) fn crawl_depth(&self) -> usize {
+        self.crawl_depth.unwrap_or(DEFAULT_CRAWL_DEPTH)
+    }
+
+    /// Which pages to follow when crawling
+    pub(crate) fn crawl_scope(&self) -> CrawlScope {
+        self.crawl_scope.unwrap_or_default()
+    }
+
     pub(crate) fn dump(&self) -> bool {
         self.dump.unwrap_or(false)
     }
@@ -881,6 +1057,22 @@ impl Config {
         self.skip_missing.unwrap_or(false)
     }
 
+    pub(crate) fn fix(&self) -> bool {
+        self.fix.unwrap_or(false)
+    }
+
+    pub(crate) fn dry_run(&self) -> bool {
+        self.dry_run.unwrap_or(false)
+    }
+
+    pub(crate) fn update_baseline(&self) -> bool {
+        self.update_baseline.unwrap_or(false)
+    }
+
+    pub(crate) fn watch(&self) -> bool {
+        self.watch.unwrap_or(false)
+    }
+
     pub(crate) fn suggest(&self) -> bool {
         self.suggest.unwrap_or(false)
     }
@@ -932,6 +1124,11 @@ impl Config {
                 cache,
                 cache_exclude_status,
                 cookie_jar,
+                record,
+                replay,
+                crawl,
+                crawl_depth,
+                crawl_scope,
                 default_extension,
                 dump,
                 dump_inputs,
@@ -963,6 +1160,12 @@ impl Config {
                 root_dir,
                 skip_missing,
                 suggest,
+                fix,
+                dry_run,
+                baseline,
+                update_baseline,
+                watch,
+                changed_since,
                 threads,
                 extensions,
                 format,
@@ -982,6 +1185,8 @@ impl Config {
                 exclude_file,
                 exclude_path,
                 include,
+                key_path,
+                xml_selector,
                 fallback_extensions,
                 remap,
                 scheme,
@@ -1103,6 +1308,8 @@ This convention also simplifies our default value testing."
         check_default_values!(
             accept,
             archive,
+            crawl_depth,
+            crawl_scope,
             extensions,
             format,
             max_concurrency,
@@ -1142,6 +1349,20 @@ This convention also simplifies our default value testing."
         assert_eq!(default.config.dump(), false);
     }
 
+    #[test]
+    fn test_validate_watch() {
+        assert!(
+            parse_options(vec!["lychee", ".", "--watch"])
+                .config
+                .validate()
+                .is_ok()
+        );
+        for conflicting in ["--dump", "--dump-inputs", "--update-baseline"] {
+            let options = parse_options(vec!["lychee", ".", "--watch", conflicting]);
+            assert!(options.config.validate().is_err(), "{conflicting}");
+        }
+    }
+
     #[test]
     fn test_fragment_option_values() {
         let p = parse_options(vec!["lychee", "-"]);
@@ -20,6 +20,7 @@ pub(crate) enum StatsFormat {
     Json,
     Junit,
     Markdown,
+    Sarif,
 }
 
 impl FromStr for StatsFormat {
@@ -32,6 +33,7 @@ impl FromStr for StatsFormat {
             "json" => Ok(StatsFormat::Json),
             "junit" => Ok(StatsFormat::Junit),
             "markdown" | "md" => Ok(StatsFormat::Markdown),
+            "sarif" => Ok(StatsFormat::Sarif),
             _ => Err(anyhow!("Unknown format {format}")),
         }
     }
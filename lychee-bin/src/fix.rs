@@ -0,0 +1,437 @@
+//! Automatic fixing of links in local input files (`--fix`).
+//!
+//! While checking, the [`Fixer`] remembers which links can be replaced:
+//! permanently redirected URLs are replaced with their final target and, with
+//! `--suggest`, broken URLs are replaced with their archived version. Links
+//! are located in the files with the [`RawUriSpan`] of the response.
+//!
+//! To avoid corrupting files, a file is only modified if its contents are
+//! still the same as when its links were extracted.
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use futures::{Stream, StreamExt};
+use log::{info, warn};
+use lychee_lib::{
+    InputSource, RawUriSpan, Request, RequestError, ResolvedInputSource, Response, Uri,
+};
+use url::Url;
+
+use crate::formatters::stats::ResponseStats;
+
+/// A link that should be replaced in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fix {
+    span: RawUriSpan,
+    original: Url,
+    replacement: Url,
+}
+
+#[derive(Debug, Default)]
+struct FileFixes {
+    /// Hash of the file contents at the time its links were extracted
+    extracted: Option<u64>,
+    fixes: Vec<Fix>,
+}
+
+/// Collects replacements for links in local files and applies them.
+///
+/// Cloning a `Fixer` is cheap and all clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Fixer {
+    files: Arc<DashMap<PathBuf, FileFixes>>,
+}
+
+impl Fixer {
+    /// Remember the state of the file a request was extracted from.
+    ///
+    /// Must be called right after extraction, i.e. for every request
+    /// produced by the collector.
+    pub(crate) fn track(&self, request: &Request) {
+        let ResolvedInputSource::FsPath(path) = &request.source else {
+            return;
+        };
+
+        let mut file = self.files.entry(path.clone()).or_default();
+        if file.extracted.is_none() {
+            file.extracted = fs::read(path).ok().map(|content| hash(&content));
+        }
+    }
+
+    /// Remember to replace the URL of the response with the target of its
+    /// permanent redirects, if there are any.
+    pub(crate) fn add_response(&self, response: &Response) {
+        if !response.status().is_success() || response.remap().is_some() {
+            return;
+        }
+
+        if let Some(target) = response.redirects().and_then(|r| r.permanent_destination()) {
+            let body = response.body();
+            self.add(response.source(), body.span, &body.uri, target.clone());
+        }
+    }
+
+    /// Remember to replace broken URLs with the archived versions
+    /// found with `--suggest`.
+    pub(crate) fn add_suggestions(&self, stats: &ResponseStats) {
+        for (source, suggestions) in &stats.suggestion_map {
+            let Some(responses) = stats.error_map.get(source) else {
+                continue;
+            };
+
+            for suggestion in suggestions {
+                for body in responses
+                    .iter()
+                    .filter(|body| body.uri.as_str() == suggestion.original.as_str())
+                {
+                    self.add(source, body.span, &body.uri, suggestion.suggestion.clone());
+                }
+            }
+        }
+    }
+
+    fn add(&self, source: &InputSource, span: Option<RawUriSpan>, original: &Uri, target: Url) {
+        let (InputSource::FsPath(path), Some(span)) = (source, span) else {
+            return;
+        };
+        // Lines of notebook cells aren't lines of the file
+        if span.cell.is_some() {
+            return;
+        }
+        let Ok(original) = Url::parse(original.as_str()) else {
+            return;
+        };
+
+        let mut replacement = target;
+        if replacement.fragment().is_none() {
+            replacement.set_fragment(original.fragment());
+        }
+        if replacement == original {
+            return;
+        }
+
+        self.files.entry(path.clone()).or_default().fixes.push(Fix {
+            span,
+            original,
+            replacement,
+        });
+    }
+
+    /// Rewrite all files with fixable links.
+    ///
+    /// With `dry_run`, the files are left untouched and the changes are
+    /// written to `writer` as a unified diff instead.
+    pub(crate) fn apply(&self, dry_run: bool, writer: &mut impl Write) -> Result<()> {
+        let mut paths: Vec<PathBuf> = self
+            .files
+            .iter()
+            .filter(|file| !file.fixes.is_empty())
+            .map(|file| file.key().clone())
+            .collect();
+        paths.sort();
+
+        let mut fixed_links = 0;
+        let mut fixed_files = 0;
+        for path in paths {
+            let Some((_, file)) = self.files.remove(&path) else {
+                continue;
+            };
+            // Files which couldn't be read when their links were extracted,
+            // like files in archives, aren't fixed
+            if file.extracted.is_none() {
+                continue;
+            }
+
+            let content = fs::read(&path)
+                .with_context(|| format!("Cannot read {} to fix links", path.display()))?;
+            if file.extracted != Some(hash(&content)) {
+                warn!(
+                    "Not fixing links in {}, because it changed since its links were extracted",
+                    path.display()
+                );
+                continue;
+            }
+            let Ok(content) = String::from_utf8(content) else {
+                continue;
+            };
+
+            let (fixed, count) = fix_content(&path, &content, &file.fixes);
+            if count == 0 {
+                continue;
+            }
+
+            if dry_run {
+                write!(writer, "{}", diff(&path, &content, &fixed))?;
+            } else {
+                fs::write(&path, fixed)
+                    .with_context(|| format!("Cannot write fixed links to {}", path.display()))?;
+            }
+            fixed_links += count;
+            fixed_files += 1;
+        }
+
+        if !dry_run && fixed_links > 0 {
+            info!("Fixed {fixed_links} link(s) in {fixed_files} file(s)");
+        }
+        Ok(())
+    }
+}
+
+/// Let the [`Fixer`] (if any) remember the state of all files which the
+/// requests are extracted from.
+pub(crate) fn track<S>(requests: S, fixer: Option<Fixer>) -> impl Stream<Item = S::Item>
+where
+    S: Stream<Item = Result<Request, RequestError>>,
+{
+    requests.inspect(move |request| {
+        if let (Some(fixer), Ok(request)) = (&fixer, request) {
+            fixer.track(request);
+        }
+    })
+}
+
+fn hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Apply the fixes to the content of a file.
+/// Returns the new content and the number of replaced links.
+fn fix_content(path: &Path, content: &str, fixes: &[Fix]) -> (String, usize) {
+    let mut lines: Vec<String> = content.split_inclusive('\n').map(String::from).collect();
+    let mut count = 0;
+
+    let mut by_line: HashMap<usize, Vec<&Fix>> = HashMap::new();
+    for fix in fixes {
+        by_line.entry(fix.span.line.get()).or_default().push(fix);
+    }
+
+    for (line_number, fixes) in by_line {
+        let Some(line) = lines.get_mut(line_number - 1) else {
+            continue;
+        };
+
+        // Locate all links before replacing anything, so that positions refer
+        // to the original line. Then replace from the end of the line.
+        let mut replacements: Vec<(usize, usize, &Url)> = Vec::new();
+        for fix in fixes {
+            match locate(line, fix, &replacements) {
+                Some((start, end)) => replacements.push((start, end, &fix.replacement)),
+                None => warn!(
+                    "Cannot fix {} in {}:{}, because the link was not found on that line",
+                    fix.original,
+                    path.display(),
+                    fix.span
+                ),
+            }
+        }
+        replacements.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+
+        for (start, end, replacement) in replacements {
+            line.replace_range(start..end, replacement.as_str());
+            count += 1;
+        }
+    }
+
+    (lines.concat(), count)
+}
+
+/// Find the byte range of the link of the fix in the line.
+///
+/// The search starts at the column of the span (if known) and falls back to
+/// the whole line. Occurrences which were already claimed by other fixes on
+/// the same line are skipped, and so are occurrences which are only the start
+/// of a longer URL.
+fn locate(line: &str, fix: &Fix, claimed: &[(usize, usize, &Url)]) -> Option<(usize, usize)> {
+    let column_offset = fix
+        .span
+        .column
+        .and_then(|column| line.char_indices().nth(column.get() - 1))
+        .map(|(offset, _)| offset);
+
+    // The URL may have been normalized when it was parsed,
+    // e.g. `https://example.com` becomes `https://example.com/`
+    let text = fix.original.as_str();
+    let candidates = [Some(text), text.strip_suffix('/')];
+
+    column_offset.into_iter().chain([0]).find_map(|start| {
+        candidates.into_iter().flatten().find_map(|candidate| {
+            line[start..]
+                .match_indices(candidate)
+                .map(|(offset, _)| (start + offset, start + offset + candidate.len()))
+                .filter(|(_, end)| ends_url(&line[*end..]))
+                .find(|(begin, _)| !claimed.iter().any(|(other, _, _)| other == begin))
+        })
+    })
+}
+
+/// Whether a URL which is followed by `rest` ends there, rather than
+/// continuing, like `https://example.com/a` in `https://example.com/about`.
+///
+/// Punctuation only continues the URL if more of the URL follows it, so
+/// that a link at the end of a sentence is still found.
+fn ends_url(rest: &str) -> bool {
+    let continues = |c: char| c.is_alphanumeric() || "-_~%/".contains(c);
+
+    let mut chars = rest.chars();
+    match chars.next() {
+        Some(c) if continues(c) => false,
+        Some(c) if ".,:;!?#&=+@".contains(c) => !chars.next().is_some_and(continues),
+        _ => true,
+    }
+}
+
+/// A unified diff with one hunk per changed line
+fn diff(path: &Path, old: &str, new: &str) -> String {
+    let path = path.display();
+    let mut diff = format!("--- a/{path}\n+++ b/{path}\n");
+
+    for (index, (old_line, new_line)) in old
+        .split_inclusive('\n')
+        .zip(new.split_inclusive('\n'))
+        .enumerate()
+        .filter(|(_, (old_line, new_line))| old_line != new_line)
+    {
+        let number = index + 1;
+        let _ = writeln!(diff, "@@ -{number} +{number} @@");
+        let _ = writeln!(diff, "-{}", old_line.trim_end_matches(['\r', '\n']));
+        let _ = writeln!(diff, "+{}", new_line.trim_end_matches(['\r', '\n']));
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fix(line: usize, column: Option<usize>, original: &str, replacement: &str) -> Fix {
+        Fix {
+            span: RawUriSpan::new(
+                NonZeroUsize::new(line).unwrap(),
+                column.and_then(NonZeroUsize::new),
+            ),
+            original: Url::parse(original).unwrap(),
+            replacement: Url::parse(replacement).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_fix_content() {
+        let content = "# Links\n\n[a](https://a.com) and [b](https://b.com/x)\n<https://a.com>\n";
+        let fixes = [
+            fix(3, Some(5), "https://a.com", "https://new-a.com/"),
+            fix(3, Some(28), "https://b.com/x", "https://new-b.com/y"),
+            fix(4, None, "https://a.com", "https://new-a.com/"),
+        ];
+
+        let (result, count) = fix_content(Path::new("test.md"), content, &fixes);
+
+        assert_eq!(count, 3);
+        assert_eq!(
+            result,
+            "# Links\n\n[a](https://new-a.com/) and [b](https://new-b.com/y)\n<https://new-a.com/>\n"
+        );
+    }
+
+    #[test]
+    fn test_fix_content_same_link_twice_on_line() {
+        let content = "https://a.com/ https://a.com/\n";
+        let fixes = [
+            fix(1, None, "https://a.com/", "https://b.com/"),
+            fix(1, None, "https://a.com/", "https://b.com/"),
+        ];
+
+        let (result, count) = fix_content(Path::new("test.txt"), content, &fixes);
+
+        assert_eq!(count, 2);
+        assert_eq!(result, "https://b.com/ https://b.com/\n");
+    }
+
+    #[test]
+    fn test_fix_content_link_not_found() {
+        let content = "nothing to see here\n";
+        let fixes = [fix(1, Some(1), "https://a.com/", "https://b.com/")];
+
+        let (result, count) = fix_content(Path::new("test.txt"), content, &fixes);
+
+        assert_eq!(count, 0);
+        assert_eq!(result, content);
+    }
+
+    #[test]
+    fn test_fix_content_skips_longer_urls() {
+        let content = "https://example.com/about and https://example.com/a.\n";
+        let fixes = [fix(
+            1,
+            None,
+            "https://example.com/a",
+            "https://example.org/a",
+        )];
+
+        let (result, count) = fix_content(Path::new("test.txt"), content, &fixes);
+
+        assert_eq!(count, 1);
+        assert_eq!(
+            result,
+            "https://example.com/about and https://example.org/a.\n"
+        );
+    }
+
+    #[test]
+    fn test_fix_content_skips_longer_urls_without_trailing_slash() {
+        let content = "https://example.com/about\nhttps://example.com\n";
+        let fixes = [
+            fix(1, Some(1), "https://example.com/", "https://example.org/"),
+            fix(2, Some(1), "https://example.com/", "https://example.org/"),
+        ];
+
+        let (result, count) = fix_content(Path::new("test.txt"), content, &fixes);
+
+        assert_eq!(count, 1);
+        assert_eq!(result, "https://example.com/about\nhttps://example.org/\n");
+    }
+
+    #[test]
+    fn test_apply_skips_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("README.md");
+        fs::write(&path, "https://a.com/\n").unwrap();
+
+        let fixer = Fixer::default();
+        fixer.files.insert(
+            path.clone(),
+            FileFixes {
+                extracted: Some(hash(b"content at extraction time")),
+                fixes: vec![fix(1, Some(1), "https://a.com/", "https://b.com/")],
+            },
+        );
+        fixer.apply(false, &mut Vec::new()).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "https://a.com/\n");
+    }
+
+    #[test]
+    fn test_diff() {
+        let diff = diff(Path::new("README.md"), "a\nb\nc\n", "a\nB\nc\n");
+        assert_eq!(
+            diff,
+            "--- a/README.md\n+++ b/README.md\n@@ -2 +2 @@\n-b\n+B\n"
+        );
+    }
+}
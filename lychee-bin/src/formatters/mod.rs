@@ -27,6 +27,7 @@ pub(crate) fn get_stats_formatter(
         StatsFormat::Json => Box::new(stats::Json::new()),
         StatsFormat::Junit => Box::new(stats::Junit::new()),
         StatsFormat::Markdown => Box::new(stats::Markdown::new()),
+        StatsFormat::Sarif => Box::new(stats::Sarif::new()),
     }
 }
 
@@ -71,7 +71,8 @@ fn junit_testcases_group(
                     .extra
                     .insert("file".into(), source.to_string().into());
 
-                if let Some(span) = response.span {
+                // Lines of notebook cells aren't lines of the file
+                if let Some(span) = response.span.filter(|span| span.cell.is_none()) {
                     testcase
                         .extra
                         .insert("line".into(), span.line.to_string().into());
@@ -188,10 +188,8 @@ mod tests {
 
     use super::*;
 
-    const SPAN: Option<RawUriSpan> = Some(RawUriSpan {
-        line: NonZeroUsize::MIN,
-        column: Some(NonZeroUsize::MIN),
-    });
+    const SPAN: Option<RawUriSpan> =
+        Some(RawUriSpan::new(NonZeroUsize::MIN, Some(NonZeroUsize::MIN)));
     const DURATION: Option<Duration> = Some(Duration::from_secs(1));
 
     #[test]
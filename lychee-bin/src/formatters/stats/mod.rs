@@ -4,6 +4,7 @@ mod json;
 mod junit;
 mod markdown;
 mod response;
+mod sarif;
 
 pub(crate) use compact::Compact;
 
@@ -12,6 +13,7 @@ pub(crate) use json::Json;
 pub(crate) use junit::Junit;
 pub(crate) use markdown::Markdown;
 pub(crate) use response::ResponseStats;
+pub(crate) use sarif::Sarif;
 use serde::Serialize;
 
 use std::{
@@ -114,10 +116,8 @@ fn get_dummy_stats() -> OutputStats {
 
     use crate::formatters::suggestion::Suggestion;
 
-    const SPAN: Option<RawUriSpan> = Some(RawUriSpan {
-        column: Some(NonZeroUsize::MIN),
-        line: NonZeroUsize::MIN,
-    });
+    const SPAN: Option<RawUriSpan> =
+        Some(RawUriSpan::new(NonZeroUsize::MIN, Some(NonZeroUsize::MIN)));
     const DURATION: Option<Duration> = Some(Duration::from_secs(1));
 
     let source = InputSource::RemoteUrl(Box::new(Url::parse("https://example.com").unwrap()));
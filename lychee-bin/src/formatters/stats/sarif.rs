@@ -0,0 +1,319 @@
+use std::{collections::BTreeSet, num::NonZeroUsize};
+
+use anyhow::{Context, Result};
+use lychee_lib::{CacheStatus, ErrorKind, InputSource, ResponseBody, Status};
+use serde::Serialize;
+use url::Url;
+
+use super::{StatsFormatter, sort_stats_iter};
+use crate::formatters::stats::{OutputStats, ResponseStats};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const VERSION: &str = "2.1.0";
+
+/// The SARIF (Static Analysis Results Interchange Format) report format.
+/// This format is understood by code scanning tools (e.g. GitHub code scanning),
+/// which show broken links as alerts at the line where they occur.
+///
+/// See <https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html>
+pub(crate) struct Sarif;
+
+impl Sarif {
+    pub(crate) const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl StatsFormatter for Sarif {
+    /// Format stats as a SARIF 2.1.0 log with one result per broken link
+    fn format(&self, stats: OutputStats) -> Result<String> {
+        serde_json::to_string_pretty(&sarif_log(&stats.response_stats))
+            .context("Cannot format stats as SARIF")
+    }
+}
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+struct Run {
+    tool: Tool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+struct Rule {
+    id: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<usize>,
+}
+
+fn sarif_log(stats: &ResponseStats) -> Log {
+    // Timeouts fail the run as well, so they are reported alongside errors
+    let broken = sort_stats_iter(stats.error_map.iter().chain(&stats.timeout_map));
+
+    let results: Vec<SarifResult> = broken
+        .into_iter()
+        .flat_map(|(source, responses)| {
+            responses
+                .into_iter()
+                .map(move |response| sarif_result(source, response))
+        })
+        .collect();
+
+    let rules = results
+        .iter()
+        .map(|result| result.rule_id)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|id| Rule { id })
+        .collect();
+
+    Log {
+        schema: SCHEMA,
+        version: VERSION,
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "lychee",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: "https://lychee.cli.rs",
+                    rules,
+                },
+            },
+            results,
+        }],
+    }
+}
+
+fn sarif_result(source: &InputSource, response: &ResponseBody) -> SarifResult {
+    // Lines of notebook cells aren't lines of the file
+    let region = response
+        .span
+        .filter(|span| span.cell.is_none())
+        .map(|span| Region {
+            start_line: span.line.get(),
+            start_column: span.column.map(NonZeroUsize::get),
+        });
+
+    SarifResult {
+        rule_id: rule_id(&response.status),
+        level: "error",
+        message: Message {
+            text: format!("{} | {}", response.uri, response.status.details()),
+        },
+        locations: vec![Location {
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation {
+                    uri: artifact_uri(source),
+                },
+                region,
+            },
+        }],
+    }
+}
+
+/// Errors are identified by the name of their [`ErrorKind`] variant
+/// (e.g. `RejectedStatusCode`), other statuses by their own name
+/// (e.g. `Timeout`).
+///
+/// Cached errors and errors while building requests use the rule of their
+/// underlying error, so that a failure has the same rule on every run.
+/// The cache only keeps the status code of an error, so cached errors
+/// without one keep the generic `Cached` rule.
+fn rule_id(status: &Status) -> &'static str {
+    match status {
+        Status::Error(kind) | Status::Unsupported(kind) => kind.into(),
+        Status::RequestError(error) => error.error().into(),
+        Status::Cached(CacheStatus::Error(Some(code))) => {
+            (&ErrorKind::RejectedStatusCode(*code)).into()
+        }
+        status => status.into(),
+    }
+}
+
+/// SARIF locations are URIs. Relative paths are kept relative, so that code
+/// scanning tools can resolve them against the repository root.
+fn artifact_uri(source: &InputSource) -> String {
+    match source {
+        InputSource::FsPath(path) if path.is_absolute() => {
+            Url::from_file_path(path).map_or_else(|()| source.to_string(), String::from)
+        }
+        InputSource::FsPath(path) => {
+            let path = path.to_string_lossy().replace('\\', "/");
+            path.strip_prefix("./").unwrap_or(&path).to_string()
+        }
+        _ => source.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use http::StatusCode;
+    use lychee_lib::{ErrorKind, InputSource, RequestError, Response, Status, Uri};
+    use pretty_assertions::assert_eq;
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::formatters::stats::get_dummy_stats;
+
+    #[test]
+    fn test_sarif_formatter() {
+        // Report the dummy error with the status a broken link really has
+        let mut stats = get_dummy_stats();
+        for responses in stats.response_stats.error_map.values_mut() {
+            *responses = responses
+                .drain()
+                .map(|response| ResponseBody {
+                    status: Status::Error(ErrorKind::RejectedStatusCode(StatusCode::NOT_FOUND)),
+                    ..response
+                })
+                .collect();
+        }
+
+        let formatter = Sarif::new();
+        let result = formatter.format(stats).unwrap();
+        let log: Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(log["runs"][0]["tool"]["driver"]["name"], "lychee");
+        assert_eq!(
+            log["runs"][0]["tool"]["driver"]["rules"],
+            json!([{ "id": "RejectedStatusCode" }, { "id": "Timeout" }])
+        );
+        assert_eq!(
+            log["runs"][0]["results"],
+            json!([
+                {
+                    "ruleId": "RejectedStatusCode",
+                    "level": "error",
+                    "message": {
+                        "text": "https://github.com/mre/idiomatic-rust-doesnt-exist-man | Rejected status code: 404 Not Found (configurable with \"accept\" option)"
+                    },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": "https://example.com/" },
+                            "region": { "startLine": 1, "startColumn": 1 }
+                        }
+                    }]
+                },
+                {
+                    "ruleId": "Timeout",
+                    "level": "error",
+                    "message": { "text": "https://httpbin.org/delay/2 | Request timed out" },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": "https://example.com/" },
+                            "region": { "startLine": 1, "startColumn": 1 }
+                        }
+                    }]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sarif_rule_id_from_error_kind() {
+        let mut stats = ResponseStats::default();
+        stats.add(Response::new(
+            Uri::try_from("https://example.com/404").unwrap(),
+            Status::Error(ErrorKind::RejectedStatusCode(StatusCode::NOT_FOUND)),
+            None,
+            None,
+            InputSource::FsPath(PathBuf::from("./docs/README.md")),
+            None,
+            None,
+        ));
+
+        let log = serde_json::to_value(sarif_log(&stats)).unwrap();
+        let result = &log["runs"][0]["results"][0];
+
+        assert_eq!(result["ruleId"], "RejectedStatusCode");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"],
+            json!({ "artifactLocation": { "uri": "docs/README.md" } })
+        );
+    }
+
+    #[test]
+    fn test_sarif_rule_id_of_wrapped_errors() {
+        let not_found = ErrorKind::RejectedStatusCode(StatusCode::NOT_FOUND);
+        assert_eq!(
+            rule_id(&Status::Cached(CacheStatus::Error(Some(
+                StatusCode::NOT_FOUND
+            )))),
+            rule_id(&Status::Error(not_found))
+        );
+
+        let source = InputSource::FsPath(PathBuf::from("missing.md"));
+        let request_error = RequestError::GetInputContent(source, Box::new(ErrorKind::EmptyUrl));
+        assert_eq!(rule_id(&Status::RequestError(request_error)), "EmptyUrl");
+    }
+
+    #[test]
+    fn test_sarif_without_errors() {
+        let log = serde_json::to_value(sarif_log(&ResponseStats::default())).unwrap();
+        assert_eq!(log["runs"][0]["results"], json!([]));
+        assert_eq!(log["runs"][0]["tool"]["driver"]["rules"], json!([]));
+    }
+}
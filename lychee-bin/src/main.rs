@@ -58,11 +58,13 @@
 #![deny(anonymous_parameters, macro_use_extern_crate)]
 #![deny(missing_docs)]
 
+use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader, ErrorKind, IsTerminal, stdin};
 use std::num::NonZeroUsize;
+use std::sync::Arc;
 
-use anyhow::{Context, Error, Result};
+use anyhow::{Context, Error, Result, bail};
 use clap::{Parser, crate_version};
 use commands::{CommandParams, generate};
 use formatters::log::init_logging;
@@ -70,29 +72,43 @@ use http::HeaderMap;
 use log::{error, info, warn};
 
 use lychee_lib::filter::PathExcludes;
+use lychee_lib::ratelimit::Recording;
 
 use config::HeaderMapExt;
 use ring as _; // required for apple silicon
 
+use lychee_lib::BaseInfo;
 use lychee_lib::BasicAuthExtractor;
+use lychee_lib::Client;
 use lychee_lib::Collector;
 use lychee_lib::CookieJar;
+use lychee_lib::Crawler;
+use lychee_lib::Input;
+use lychee_lib::extract::KeyPaths;
+use lychee_lib::{Request, RequestError};
 
+mod baseline;
 mod cache;
+mod changed;
 mod client;
 mod commands;
 mod config;
 mod files_from;
+mod fix;
 mod formatters;
 mod parse;
 mod progress;
 mod time;
 mod verbosity;
+mod watch;
 
+use crate::baseline::Baseline;
+use crate::fix::Fixer;
 use crate::formatters::stats::{OutputStats, ResponseStats, output_statistics};
+use crate::watch::Watcher;
 use crate::{
     cache::Cache,
-    config::{Config, LYCHEE_CACHE_FILE, LYCHEE_IGNORE_FILE, LycheeOptions},
+    config::{Command, Config, LYCHEE_CACHE_FILE, LYCHEE_IGNORE_FILE, LycheeOptions},
     formatters::duration::Duration,
     generate::generate,
 };
@@ -217,6 +233,7 @@ fn load_config() -> Result<LycheeOptions> {
     }
 
     handle_fd_limits(&mut opts);
+    opts.config.validate()?;
 
     Ok(opts)
 }
@@ -229,6 +246,25 @@ fn load_cookie_jar(cfg: &Config) -> Result<Option<CookieJar>> {
     }
 }
 
+/// Load the responses to serve with `--replay` or start an empty recording
+/// for `--record`
+fn load_recording(cfg: &Config) -> Result<Option<Arc<Recording>>> {
+    let recording = match (&cfg.replay, &cfg.record) {
+        (Some(path), record) => {
+            let recording = Recording::replay(path)
+                .with_context(|| format!("Cannot load recording from `{}`", path.display()))?;
+            if record.is_some() {
+                recording.record_missing()
+            } else {
+                recording
+            }
+        }
+        (None, Some(_)) => Recording::record(),
+        (None, None) => return Ok(None),
+    };
+    Ok(Some(Arc::new(recording)))
+}
+
 /// Load cache (if exists and is still valid)
 /// This returns an `Option` as starting without a cache is a common scenario
 /// and we silently discard errors on purpose
@@ -351,9 +387,18 @@ fn underlying_io_error_kind(error: &Error) -> Option<io::ErrorKind> {
     None
 }
 
+/// Get the inputs to check, restricted to the changed files with `--changed-since`
+async fn load_inputs(opts: &LycheeOptions) -> Result<HashSet<Input>> {
+    let inputs = opts.inputs()?;
+    match &opts.config.changed_since {
+        Some(git_ref) => changed::changed_since(inputs, git_ref, &opts.config).await,
+        None => Ok(inputs),
+    }
+}
+
 /// Run lychee on the given inputs
 async fn run(opts: &LycheeOptions) -> Result<i32> {
-    let inputs = opts.inputs()?;
+    let inputs = load_inputs(opts).await?;
 
     // Hide the progress bar only when stdin is the sole input and it is
     // interactive (TTY).
@@ -410,66 +455,229 @@ async fn run(opts: &LycheeOptions) -> Result<i32> {
         )
     })?;
 
-    let client = client::create(&opts.config, cookie_jar.as_deref())?;
+    let recording = load_recording(&opts.config)?;
+    let client = client::create(&opts.config, cookie_jar.as_deref(), recording.clone())?;
 
-    let mut collector = Collector::new(opts.config.root_dir.clone(), base.unwrap_or_default())?
-        .skip_missing_inputs(opts.config.skip_missing())
-        .skip_hidden(!opts.config.hidden())
-        // be aware that "no ignore" means do *not* ignore files
-        .skip_ignored(!opts.config.no_ignore())
-        .include_verbatim(opts.config.include_verbatim())
-        .headers(HeaderMap::from_header_pairs(&opts.config.headers())?)
-        .excluded_paths(PathExcludes::new(opts.config.exclude_path.clone())?)
-        // File a bug if you rely on this envvar! It's going to go away eventually.
-        .use_html5ever(std::env::var("LYCHEE_USE_HTML5EVER").is_ok_and(|x| x == "1"))
-        .include_wikilinks(opts.config.include_wikilinks())
-        .preprocessor(opts.config.preprocess.clone())
-        .host_pool(client.host_pool());
+    let collector = create_collector(&opts.config, base, &client)?;
 
-    collector = if let Some(ref basic_auth) = opts.config.basic_auth {
-        collector.basic_auth_extractor(BasicAuthExtractor::new(basic_auth)?)
-    } else {
-        collector
-    };
+    match opts.command {
+        Some(Command::Lsp) => {
+            commands::lsp(client, cache, collector, &opts.config).await?;
+            return Ok(ExitCode::Success as i32);
+        }
+        Some(Command::Serve { listen }) => {
+            commands::serve(listen, client, cache, collector, &opts.config).await?;
+            return Ok(ExitCode::Success as i32);
+        }
+        None => {}
+    }
+
+    let crawler = create_crawler(&collector, &inputs, &opts.config);
+
+    // Start watching before the first check, so that no changes are missed
+    let watcher = opts
+        .config
+        .watch()
+        .then(|| Watcher::new(&inputs, &opts.config))
+        .transpose()?;
 
-    let requests = collector.collect_links_from_file_types(inputs, opts.config.extensions());
+    let fixer = opts.config.fix().then(Fixer::default);
+    let requests = fix::track(
+        collector
+            .clone()
+            .collect_links_from_file_types(inputs, opts.config.extensions()),
+        fixer.clone(),
+    );
+    let baseline = load_baseline(&opts.config)?;
     let params = CommandParams {
-        client,
+        client: client.clone(),
         cache,
         requests,
         cfg: opts.config.clone(),
         is_stdin_input,
+        crawler,
+        fixer,
+        baseline: baseline.clone(),
     };
 
     let exit_code = if opts.config.dump() {
         commands::dump(params).await?
     } else {
-        let (response_stats, cache, exit_code, host_pool) = commands::check(params).await?;
-        github_warning(&response_stats, &opts.config);
-        redirect_warning(&response_stats, &opts.config);
-
-        let stats = OutputStats {
-            response_stats,
-            host_stats: opts
-                .config
-                .host_stats()
-                .then_some(host_pool.all_host_stats()),
-        };
-        output_statistics(stats, &opts.config)?;
-
-        if opts.config.cache() {
-            cache.store(LYCHEE_CACHE_FILE)?;
+        let (cache, exit_code) = check_and_report(params, cookie_jar.as_ref()).await?;
+        match watcher {
+            Some(watcher) => {
+                let context = WatchContext {
+                    config: &opts.config,
+                    client: &client,
+                    collector: &collector,
+                    baseline,
+                    cookie_jar: cookie_jar.as_ref(),
+                };
+                Box::pin(watch(watcher, &context, cache)).await?
+            }
+            None => exit_code,
         }
+    };
 
-        if let Some(cookie_jar) = cookie_jar.as_ref() {
-            info!("Saving cookie jar");
-            cookie_jar.save().context("Cannot save cookie jar")?;
-        }
+    if let (Some(recording), Some(path)) = (recording, &opts.config.record) {
+        recording.save(path).context("Cannot save recording")?;
+    }
+
+    Ok(exit_code as i32)
+}
+
+/// Check the links of all requests and report the results
+async fn check_and_report<S>(
+    params: CommandParams<S>,
+    cookie_jar: Option<&CookieJar>,
+) -> Result<(Cache, ExitCode)>
+where
+    S: futures::Stream<Item = Result<Request, RequestError>>,
+{
+    let config = params.cfg.clone();
+    let fixer = params.fixer.clone();
+    let (response_stats, cache, mut exit_code, host_pool) = commands::check(params).await?;
+    github_warning(&response_stats, &config);
+    redirect_warning(&response_stats, &config);
+
+    if let Some(fixer) = fixer {
+        fixer.apply(config.dry_run(), &mut io::stderr())?;
+    }
+
+    if let Some(path) = &config.baseline
+        && config.update_baseline()
+    {
+        Baseline::from_stats(&response_stats, config.accept_timeouts()).save(path)?;
+        exit_code = ExitCode::Success;
+    }
 
-        exit_code
+    let stats = OutputStats {
+        response_stats,
+        host_stats: config.host_stats().then_some(host_pool.all_host_stats()),
     };
+    output_statistics(stats, &config)?;
 
-    Ok(exit_code as i32)
+    if config.cache() {
+        cache.store(LYCHEE_CACHE_FILE)?;
+    }
+
+    if let Some(cookie_jar) = cookie_jar {
+        info!("Saving cookie jar");
+        cookie_jar.save().context("Cannot save cookie jar")?;
+    }
+
+    Ok((cache, exit_code))
+}
+
+/// Everything which is kept across the checks of `--watch`
+struct WatchContext<'a> {
+    config: &'a Config,
+    client: &'a Client,
+    collector: &'a Collector,
+    baseline: Option<Baseline>,
+    cookie_jar: Option<&'a CookieJar>,
+}
+
+/// Check the links of changed input files again until lychee is stopped
+/// with Ctrl-C (`--watch`).
+///
+/// The client and the cache are kept across checks, so unchanged links are
+/// served from the cache and the rate limits of all hosts stay in effect.
+/// Returns the exit code of the last check.
+async fn watch(
+    mut watcher: Watcher,
+    context: &WatchContext<'_>,
+    mut cache: Cache,
+) -> Result<ExitCode> {
+    let config = context.config;
+    let mut exit_code = ExitCode::Success;
+    // A single listener, so that Ctrl-C during a check isn't missed
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        info!("Watching for changes");
+        let inputs = tokio::select! {
+            inputs = watcher.changed_inputs() => inputs?,
+            _ = &mut ctrl_c => return Ok(exit_code),
+        };
+
+        let fixer = config.fix().then(Fixer::default);
+        let params = CommandParams {
+            client: context.client.clone(),
+            cache,
+            requests: fix::track(
+                context
+                    .collector
+                    .clone()
+                    .collect_links_from_file_types(inputs, config.extensions()),
+                fixer.clone(),
+            ),
+            cfg: config.clone(),
+            is_stdin_input: false,
+            crawler: None,
+            fixer,
+            baseline: context.baseline.clone(),
+        };
+        (cache, exit_code) = tokio::select! {
+            result = check_and_report(params, context.cookie_jar) => result?,
+            _ = &mut ctrl_c => return Ok(exit_code),
+        };
+    }
+}
+
+/// Create the [`Collector`] which extracts the links from all inputs
+fn create_collector(config: &Config, base: Option<BaseInfo>, client: &Client) -> Result<Collector> {
+    let collector = Collector::new(config.root_dir.clone(), base.unwrap_or_default())?
+        .skip_missing_inputs(config.skip_missing())
+        .skip_hidden(!config.hidden())
+        // be aware that "no ignore" means do *not* ignore files
+        .skip_ignored(!config.no_ignore())
+        .include_verbatim(config.include_verbatim())
+        .headers(HeaderMap::from_header_pairs(&config.headers())?)
+        .excluded_paths(PathExcludes::new(config.exclude_path.clone())?)
+        // File a bug if you rely on this envvar! It's going to go away eventually.
+        .use_html5ever(std::env::var("LYCHEE_USE_HTML5EVER").is_ok_and(|x| x == "1"))
+        .include_wikilinks(config.include_wikilinks())
+        .key_paths(KeyPaths::new(&config.key_path))
+        .xml_selectors(config.xml_selector.clone())
+        .preprocessor(config.preprocess.clone())
+        .host_pool(client.host_pool());
+
+    Ok(if let Some(ref basic_auth) = config.basic_auth {
+        collector.basic_auth_extractor(BasicAuthExtractor::new(basic_auth)?)
+    } else {
+        collector
+    })
+}
+
+/// Load the known broken links of `--baseline` (if any).
+///
+/// With `--update-baseline`, the baseline is replaced by the broken links of
+/// the current run, so the previous baseline is not needed.
+fn load_baseline(config: &Config) -> Result<Option<Baseline>> {
+    match (&config.baseline, config.update_baseline()) {
+        (None, true) => bail!("`--update-baseline` requires a `--baseline` file"),
+        (Some(path), false) => Baseline::load(path).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Create a [`Crawler`] for the remote inputs if `--crawl` is enabled
+fn create_crawler(
+    collector: &Collector,
+    inputs: &HashSet<Input>,
+    config: &Config,
+) -> Option<Crawler> {
+    let crawler = config.crawl().then(|| {
+        Crawler::new(collector.clone(), inputs)
+            .max_depth(config.crawl_depth())
+            .scope(config.crawl_scope())
+            .extensions(config.extensions())
+    });
+    if crawler.as_ref().is_some_and(|c| !c.has_roots()) {
+        warn!("`--crawl` has no effect, because none of the inputs is a remote URL");
+    }
+    crawler
 }
 
 /// Display user-friendly message if there were any issues with GitHub URLs
@@ -0,0 +1,143 @@
+//! Checking local files again when they change (`--watch`).
+//!
+//! The [`Watcher`] watches the files and directories of all local inputs.
+//! After a change, it reports the input files which were created or modified,
+//! so that only their links are checked again.
+
+use std::{collections::HashSet, fs, path::PathBuf, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use log::warn;
+use lychee_lib::Input;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use tokio::sync::mpsc;
+
+use crate::changed::{input_files, is_local, local_root};
+use crate::config::Config;
+
+/// Time to wait for further changes before checking the changed files,
+/// because editors often write a file in several steps
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches the local inputs for changes
+pub(crate) struct Watcher {
+    /// The local inputs, i.e. files, directories and globs
+    inputs: Vec<Input>,
+    config: Config,
+    events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+    // Stops watching when dropped
+    _notify: RecommendedWatcher,
+}
+
+impl Watcher {
+    /// Start watching all local inputs
+    pub(crate) fn new(inputs: &HashSet<Input>, config: &Config) -> Result<Self> {
+        let inputs: Vec<Input> = inputs
+            .iter()
+            .filter(|input| is_local(input))
+            .cloned()
+            .collect();
+        if inputs.is_empty() {
+            bail!("`--watch` requires local files, directories or globs as inputs");
+        }
+
+        let (send_event, events) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            // The receiver is only dropped together with the watcher
+            let _ = send_event.send(event);
+        })
+        .context("Cannot watch inputs for changes")?;
+        for root in inputs.iter().filter_map(|input| local_root(&input.source)) {
+            watcher
+                .watch(&root, RecursiveMode::Recursive)
+                .with_context(|| format!("Cannot watch {} for changes", root.display()))?;
+        }
+
+        Ok(Self {
+            inputs,
+            config: config.clone(),
+            events,
+            _notify: watcher,
+        })
+    }
+
+    /// Wait until input files are created or modified and return them
+    pub(crate) async fn changed_inputs(&mut self) -> Result<HashSet<Input>> {
+        loop {
+            let mut changed = HashSet::new();
+            let Some(event) = self.events.recv().await else {
+                bail!("Stopped watching inputs for changes");
+            };
+            add_changed_paths(&mut changed, event);
+            while let Ok(Some(event)) = tokio::time::timeout(DEBOUNCE, self.events.recv()).await {
+                add_changed_paths(&mut changed, event);
+            }
+
+            let inputs = input_files(&self.inputs, &changed, &self.config).await?;
+            if !inputs.is_empty() {
+                return Ok(inputs);
+            }
+        }
+    }
+}
+
+/// Remember the paths of created and modified files
+fn add_changed_paths(changed: &mut HashSet<PathBuf>, event: notify::Result<Event>) {
+    match event {
+        Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+            changed.extend(
+                event
+                    .paths
+                    .iter()
+                    .filter_map(|path| fs::canonicalize(path).ok()),
+            );
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Error while watching inputs for changes: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use lychee_lib::InputSource;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_changed_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("docs");
+        fs::create_dir(&docs).unwrap();
+        fs::write(docs.join("index.md"), "").unwrap();
+        fs::write(docs.join("logo.png"), "").unwrap();
+
+        let inputs = HashSet::from([Input::from_value(docs.to_str().unwrap()).unwrap()]);
+        let mut watcher = Watcher::new(&inputs, &Config::default()).unwrap();
+
+        // Files without a checked extension are ignored
+        fs::write(docs.join("logo.png"), "PNG").unwrap();
+        fs::write(docs.join("index.md"), "https://example.com").unwrap();
+
+        let changed = tokio::time::timeout(Duration::from_secs(10), watcher.changed_inputs())
+            .await
+            .unwrap()
+            .unwrap();
+        let paths: Vec<_> = changed
+            .into_iter()
+            .map(|input| match input.source {
+                InputSource::FsPath(path) => path,
+                source => panic!("unexpected input {source:?}"),
+            })
+            .collect();
+        assert_eq!(paths, [docs.join("index.md")]);
+    }
+
+    #[test]
+    fn test_no_local_inputs() {
+        let inputs = HashSet::from([Input::from_value("https://example.com").unwrap()]);
+        assert!(Watcher::new(&inputs, &Config::default()).is_err());
+    }
+}
@@ -1,7 +1,11 @@
 #[cfg(test)]
 mod cli {
     use anyhow::anyhow;
-    use assert_cmd::{assert::Assert, cargo::cargo_bin_cmd, output::OutputOkExt};
+    use assert_cmd::{
+        assert::Assert,
+        cargo::{cargo_bin, cargo_bin_cmd},
+        output::OutputOkExt,
+    };
     use assert_json_diff::assert_json_include;
     use http::{Method, StatusCode};
     use lychee_lib::{InputSource, ResponseBody};
@@ -17,9 +21,10 @@ mod cli {
         collections::{HashMap, HashSet},
         error::Error,
         fs::{self, File},
-        io::{BufRead, Write},
+        io::{BufRead, BufReader, Write},
         ops::Not,
         path::Path,
+        process::Stdio,
         time::{Duration, Instant},
     };
     use tempfile::{NamedTempFile, tempdir};
@@ -462,6 +467,21 @@ mod cli {
             .stdout(contains("3 OK"));
     }
 
+    #[test]
+    fn test_resolve_paths_with_base_element() {
+        let dir = fixtures_path!().join("base_href");
+
+        cargo_bin_cmd!()
+            .arg("--offline")
+            .arg("--include-fragments")
+            .arg(dir.join("index.html"))
+            .env_clear()
+            .assert()
+            .success()
+            .stdout(contains("4 Total"))
+            .stdout(contains("4 OK"));
+    }
+
     #[test]
     fn test_resolve_paths_from_root_dir() {
         let dir = fixtures_path!().join("resolve_paths_from_root_dir");
@@ -694,6 +714,35 @@ mod cli {
             .code(2);
     }
 
+    #[test]
+    fn test_stdin_file_type_is_sniffed() {
+        // Relative links are only found in HTML, not in plaintext
+        cargo_bin_cmd!()
+            .arg("-")
+            .arg("--dump")
+            .arg("--base-url")
+            .arg("https://example.com/docs/")
+            .write_stdin("<!DOCTYPE html>\n<a href=\"intro.html\">Intro</a>")
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/docs/intro.html"));
+    }
+
+    #[test]
+    fn test_stdin_malformed_xml_is_sniffed() {
+        // Links before the parse error are kept
+        cargo_bin_cmd!()
+            .arg("-")
+            .arg("--dump")
+            .write_stdin(
+                "<?xml version=\"1.0\"?>\n<rss><link>https://example.com/feed</link><a></b></rss>",
+            )
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/feed"))
+            .stderr(contains("Cannot parse XML completely"));
+    }
+
     #[tokio::test]
     async fn test_stdin_input_multiple() {
         let mock_server_a = mock_server!(StatusCode::OK);
@@ -943,6 +992,411 @@ mod cli {
         )
     }
 
+    #[tokio::test]
+    async fn test_sarif_output() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mock_server = mock_server!(StatusCode::NOT_FOUND);
+        let mut file = File::create(dir.path().join("README.md"))?;
+        writeln!(file, "# Title\n\nSee [docs]({}).", mock_server.uri())?;
+
+        let output = cargo_bin_cmd!()
+            .current_dir(dir.path())
+            .arg("--format")
+            .arg("sarif")
+            .arg("README.md")
+            .assert()
+            .failure()
+            .code(2)
+            .get_output()
+            .stdout
+            .clone();
+
+        let sarif: Value = serde_json::from_slice(&output)?;
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(result["ruleId"], "RejectedStatusCode");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "README.md"
+        );
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            3
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fix_permanent_redirects() {
+        redirecting_mock_server!(async |redirect_url: Url, ok_url: Url| {
+            let dir = tempdir().unwrap();
+            let file = dir.path().join("README.md");
+            fs::write(
+                &file,
+                format!("# Docs\n\nSee [the docs]({redirect_url}).\n"),
+            )
+            .unwrap();
+
+            cargo_bin_cmd!()
+                .arg("--fix")
+                .arg("--verbose")
+                .arg(&file)
+                .assert()
+                .success()
+                .stderr(contains("Fixed 1 link(s) in 1 file(s)"));
+
+            assert_eq!(
+                fs::read_to_string(&file).unwrap(),
+                format!("# Docs\n\nSee [the docs]({ok_url}).\n")
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_fix_dry_run() {
+        redirecting_mock_server!(async |redirect_url: Url, ok_url: Url| {
+            let dir = tempdir().unwrap();
+            let file = dir.path().join("README.md");
+            let content = format!("# Docs\n\nSee [the docs]({redirect_url}).\n");
+            fs::write(&file, &content).unwrap();
+
+            cargo_bin_cmd!()
+                .arg("--fix")
+                .arg("--dry-run")
+                .arg(&file)
+                .assert()
+                .success()
+                .stderr(contains(format!(
+                    "@@ -3 +3 @@\n-See [the docs]({redirect_url}).\n+See [the docs]({ok_url}).\n"
+                )));
+
+            assert_eq!(fs::read_to_string(&file).unwrap(), content);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_baseline() {
+        let known = mock_server!(StatusCode::NOT_FOUND);
+        let new = mock_server!(StatusCode::NOT_FOUND);
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("README.md");
+        let baseline = dir.path().join("baseline.json");
+        fs::write(&file, format!("{}\n", known.uri())).unwrap();
+
+        cargo_bin_cmd!()
+            .arg("--baseline")
+            .arg(&baseline)
+            .arg("--update-baseline")
+            .arg(&file)
+            .assert()
+            .success();
+
+        let content = fs::read_to_string(&baseline).unwrap();
+        assert!(content.contains(&format!("{}/", known.uri())));
+
+        // Known broken links don't fail the run
+        cargo_bin_cmd!()
+            .arg("--baseline")
+            .arg(&baseline)
+            .arg(&file)
+            .assert()
+            .success()
+            .stdout(contains("1 Error"));
+
+        // New broken links do
+        fs::write(&file, format!("{}\n{}\n", known.uri(), new.uri())).unwrap();
+        cargo_bin_cmd!()
+            .arg("--baseline")
+            .arg(&baseline)
+            .arg(&file)
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("2 Errors"));
+    }
+
+    #[test]
+    fn test_update_baseline_requires_baseline() {
+        cargo_bin_cmd!()
+            .arg("--update-baseline")
+            .arg("-")
+            .write_stdin("")
+            .assert()
+            .failure()
+            .stderr(contains("`--update-baseline` requires a `--baseline` file"));
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let server = mock_server!(StatusCode::OK);
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("README.md");
+        let recording = dir.path().join("recording.json");
+        fs::write(&file, format!("{}\n", server.uri())).unwrap();
+
+        cargo_bin_cmd!()
+            .arg("--record")
+            .arg(&recording)
+            .arg(&file)
+            .assert()
+            .success()
+            .stdout(contains("1 OK"));
+
+        // The recorded response is served without sending a request
+        drop(server);
+        cargo_bin_cmd!()
+            .arg("--replay")
+            .arg(&recording)
+            .arg(&file)
+            .assert()
+            .success()
+            .stdout(contains("1 OK"));
+
+        // Links without a recorded response fail
+        fs::write(&file, "https://lychee.cli.rs/unrecorded\n").unwrap();
+        cargo_bin_cmd!()
+            .arg("--replay")
+            .arg(&recording)
+            .arg(&file)
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("No recorded response"));
+    }
+
+    /// Read the next message sent by the language server
+    fn read_lsp_message(reader: &mut impl BufRead) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            match header.trim_end() {
+                "" => break,
+                header => {
+                    if let Some(value) = header.strip_prefix("Content-Length: ") {
+                        length = value.parse().unwrap();
+                    }
+                }
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn write_lsp_message(writer: &mut impl Write, message: &Value) {
+        let body = message.to_string();
+        write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        writer.flush().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_lsp_publishes_diagnostics() {
+        let server = mock_server!(StatusCode::NOT_FOUND);
+        let mut child = std::process::Command::new(cargo_bin!())
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+        write_lsp_message(
+            &mut stdin,
+            &json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}),
+        );
+        let response = read_lsp_message(&mut stdout);
+        assert_eq!(
+            response["result"]["capabilities"]["codeActionProvider"],
+            true
+        );
+
+        write_lsp_message(
+            &mut stdin,
+            &json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+        );
+        let text = format!("# Title\n\nSee [the docs]({}) for details.\n", server.uri());
+        write_lsp_message(
+            &mut stdin,
+            &json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+                "textDocument": {"uri": "untitled:Untitled-1", "languageId": "markdown", "version": 1, "text": text}
+            }}),
+        );
+
+        let notification = read_lsp_message(&mut stdout);
+        assert_eq!(notification["method"], "textDocument/publishDiagnostics");
+        let diagnostics = &notification["params"]["diagnostics"];
+        assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+        assert_eq!(diagnostics[0]["severity"], 1);
+        assert_eq!(diagnostics[0]["source"], "lychee");
+        let start = "See [the docs](".len();
+        assert_eq!(
+            diagnostics[0]["range"],
+            json!({
+                "start": {"line": 2, "character": start},
+                "end": {"line": 2, "character": start + server.uri().len()}
+            })
+        );
+
+        write_lsp_message(
+            &mut stdin,
+            &json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}),
+        );
+        assert_eq!(read_lsp_message(&mut stdout)["id"], 2);
+        write_lsp_message(&mut stdin, &json!({"jsonrpc": "2.0", "method": "exit"}));
+        assert!(child.wait().unwrap().success());
+    }
+
+    #[test]
+    fn test_watch_checks_changed_files() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("README.md");
+        fs::write(&file, "").unwrap();
+
+        let mut child = std::process::Command::new(cargo_bin!())
+            .arg("--watch")
+            .arg(dir.path())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+        let mut read_until = |text: &str| {
+            lines
+                .by_ref()
+                .map(std::result::Result::unwrap)
+                .find(|line| line.contains(text))
+                .unwrap()
+        };
+
+        read_until("0 Total");
+        fs::write(&file, "[broken](./missing.md)\n").unwrap();
+        read_until("missing.md");
+        read_until("1 Error");
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_serve() {
+        let ok = mock_server!(StatusCode::OK);
+        let not_found = mock_server!(StatusCode::NOT_FOUND);
+        let mut child = std::process::Command::new(cargo_bin!())
+            .args(["--verbose", "serve", "--listen", "127.0.0.1:0"])
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stderr = BufReader::new(child.stderr.take().unwrap());
+        let mut line = String::new();
+        stderr.read_line(&mut line).unwrap();
+        let (_, address) = line.trim().split_once("Listening on ").unwrap();
+
+        let response: Value = reqwest::Client::new()
+            .post(format!("{address}/check"))
+            .json(&json!({ "urls": [ok.uri(), not_found.uri()] }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_json_include!(
+            actual: response,
+            expected: json!({ "results": [
+                { "url": format!("{}/", ok.uri()), "status": { "code": 200 } },
+                { "url": format!("{}/", not_found.uri()), "status": { "code": 404 } },
+            ]})
+        );
+
+        for url in ["not a url", "file:///etc/hostname"] {
+            let response = reqwest::Client::new()
+                .post(format!("{address}/check"))
+                .json(&json!({ "urls": [url] }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_changed_since() {
+        let dir = tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=lychee",
+                    "-c",
+                    "user.email=lychee@example.com",
+                ])
+                .args(args)
+                .current_dir(dir.path())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        fs::write(dir.path().join("unchanged.md"), "[a](missing-a.md)").unwrap();
+        fs::write(dir.path().join("changed.md"), "").unwrap();
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "Initial commit"]);
+        fs::write(dir.path().join("changed.md"), "[b](missing-b.md)").unwrap();
+        git(&["add", "changed.md"]);
+
+        cargo_bin_cmd!()
+            .current_dir(dir.path())
+            .arg("--changed-since")
+            .arg("HEAD")
+            .arg(".")
+            .assert()
+            .failure()
+            .stdout(contains("missing-b.md"))
+            .stdout(contains("missing-a.md").not())
+            .stdout(contains("1 Total"));
+
+        // The changes are looked up in the repository of the input,
+        // not in the current directory
+        let elsewhere = tempdir().unwrap();
+        cargo_bin_cmd!()
+            .current_dir(elsewhere.path())
+            .arg("--changed-since")
+            .arg("HEAD")
+            .arg(dir.path())
+            .assert()
+            .failure()
+            .stdout(contains("missing-b.md"))
+            .stdout(contains("missing-a.md").not())
+            .stdout(contains("1 Total"));
+
+        cargo_bin_cmd!()
+            .current_dir(dir.path())
+            .arg("--changed-since")
+            .arg("does-not-exist")
+            .arg(".")
+            .assert()
+            .failure()
+            .stderr(contains("Cannot find files changed since `does-not-exist`"));
+
+        // Without changed files, nothing is checked
+        git(&["commit", "--quiet", "-m", "Change"]);
+        cargo_bin_cmd!()
+            .current_dir(dir.path())
+            .arg("--changed-since")
+            .arg("HEAD")
+            .arg(".")
+            .assert()
+            .success()
+            .stdout(contains("0 Total"));
+    }
+
     /// Test writing output of `--dump` command to file
     #[test]
     fn test_dump_to_file() -> Result<()> {
@@ -3783,6 +4237,249 @@ The config file should contain every possible key for documentation purposes."
             .stdout(contains("https://example.com"));
     }
 
+    #[test]
+    fn test_rst_file() {
+        let dir = fixtures_path!().join("rst");
+        cargo_bin_cmd!()
+            .arg("--dump")
+            .arg(dir.join("index.rst"))
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/target"))
+            .stdout(contains("https://example.com/text"))
+            .stdout(contains("rst/images/logo.png"))
+            .stdout(contains("rst/install.rst"))
+            .stdout(contains("https://example.com/comment").not())
+            .stdout(contains("https://example.com/literal").not());
+    }
+
+    #[test]
+    fn test_asciidoc_fragments() {
+        let input = fixtures_path!().join("asciidoc");
+        cargo_bin_cmd!()
+            .arg("--include-fragments")
+            .arg("--offline")
+            .arg("--verbose")
+            .arg(input.join("index.adoc"))
+            .assert()
+            .failure()
+            .stdout(contains("index.adoc#_getting_started").not())
+            .stdout(contains("guide.adoc#_installation").not())
+            .stdout(contains("index.adoc#missing"))
+            .stdout(contains("guide.adoc#no-such-section"))
+            .stdout(contains("2 OK"))
+            .stdout(contains("2 Errors"));
+    }
+
+    #[test]
+    fn test_notebook_file() {
+        let input = fixtures_path!().join("notebook").join("analysis.ipynb");
+        cargo_bin_cmd!()
+            .arg("--offline")
+            .arg(input)
+            .assert()
+            .failure()
+            .stdout(contains("missing.csv (at cell 1, 3:5)"))
+            .stdout(contains("1 OK"))
+            .stdout(contains("1 Error"));
+    }
+
+    #[test]
+    fn test_data_files_key_paths() {
+        let dir = fixtures_path!().join("data");
+        cargo_bin_cmd!()
+            .arg("--dump")
+            .arg(dir.join("package.json"))
+            .arg(dir.join("openapi.yaml"))
+            .arg(dir.join("config.toml"))
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/left-pad.tgz"))
+            .stdout(contains("https://example.com/api"))
+            .stdout(contains("https://example.com/serde"))
+            .stdout(contains("https://example.com/comment").not());
+
+        cargo_bin_cmd!()
+            .arg("--dump")
+            .arg("--key-path")
+            .arg("homepage")
+            .arg("--key-path")
+            .arg("repository")
+            .arg("--key-path")
+            .arg("**.externalDocs.url")
+            .arg(dir.join("package.json"))
+            .arg(dir.join("openapi.yaml"))
+            .arg(dir.join("config.toml"))
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/home"))
+            .stdout(contains("https://example.com/repo.git"))
+            .stdout(contains("https://example.com/pets-docs"))
+            .stdout(contains("https://example.com/left-pad.tgz").not())
+            .stdout(contains("https://example.com/contact").not())
+            .stdout(contains("https://example.com/toml-home").not());
+    }
+
+    #[test]
+    fn test_source_code_comments() {
+        let dir = fixtures_path!().join("source");
+        cargo_bin_cmd!()
+            .arg("--dump")
+            .arg(dir.join("lib.rs"))
+            .arg(dir.join("script.py"))
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/api-docs"))
+            .stdout(contains("https://example.com/docstring"))
+            .stdout(contains("https://example.com/comment"))
+            .stdout(contains("crate::Client").not())
+            .stdout(contains("https://example.com/string-literal").not());
+    }
+
+    #[test]
+    fn test_svg_file() {
+        let input = fixtures_path!().join("svg").join("logo.svg");
+        cargo_bin_cmd!()
+            .arg("--offline")
+            .arg(input)
+            .assert()
+            .failure()
+            .stdout(contains("missing.woff2"))
+            .stdout(contains("2 OK"))
+            .stdout(contains("1 Error"));
+    }
+
+    #[test]
+    fn test_svg_without_links() {
+        cargo_bin_cmd!()
+            .arg(fixtures_path!().join("svg").join("icon.svg"))
+            .arg("-v")
+            .assert()
+            .success()
+            .stdout(contains("0 Total"))
+            .stderr(contains("No URLs found").not());
+    }
+
+    #[test]
+    fn test_xml_selectors() {
+        let input = fixtures_path!().join("xml").join("manual.xml");
+        cargo_bin_cmd!()
+            .arg("--dump")
+            .arg("--xml-selector")
+            .arg("ulink@url")
+            .arg("--xml-selector")
+            .arg("uri")
+            .arg("--xml-selector")
+            .arg("xi:include@href")
+            .arg(input)
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/guide"))
+            .stdout(contains("https://example.com/source"))
+            .stdout(contains("appendix.xml"))
+            .stdout(contains("docbook.org").not());
+    }
+
+    /// Test that files which declare a legacy encoding are decoded instead of
+    /// being skipped as invalid UTF-8
+    #[test]
+    fn test_legacy_encoding_is_decoded() {
+        let input = fixtures_path!().join("encoding").join("legacy.html");
+
+        cargo_bin_cmd!()
+            .arg("--dump")
+            .arg(&input)
+            .assert()
+            .success()
+            .stdout(contains("https://lychee.cli.rs/caf%C3%A9"));
+    }
+
+    /// Test that the files in archives are checked without extracting them,
+    /// with file links resolving against the other files in the archive
+    #[test]
+    fn test_archive_inputs() {
+        for archive in ["docs.tar.gz", "docs.zip"] {
+            let input = fixtures_path!().join("archive").join(archive);
+            cargo_bin_cmd!()
+                .arg("--offline")
+                .arg("--include-fragments")
+                .arg(&input)
+                .assert()
+                .failure()
+                .stdout(contains(format!("[{}/site/index.html]", input.display())))
+                .stdout(contains("site/about.html#nope"))
+                .stdout(contains("site/missing.html"))
+                .stdout(contains("6 OK"))
+                .stdout(contains("2 Errors"));
+        }
+    }
+
+    #[test]
+    fn test_archive_members_are_filtered() {
+        let input = fixtures_path!().join("archive").join("docs.tar.gz");
+        cargo_bin_cmd!()
+            .arg("--dump-inputs")
+            .arg("--exclude-path")
+            .arg("guide")
+            .arg(&input)
+            .assert()
+            .success()
+            .stdout(contains("docs.tar.gz/site/index.html"))
+            .stdout(contains("docs.tar.gz/site/about.html"))
+            .stdout(contains("guide").not())
+            .stdout(contains("logo.png").not());
+    }
+
+    #[test]
+    fn test_compressed_inputs() {
+        let archive = fixtures_path!().join("archive");
+        cargo_bin_cmd!()
+            .arg("--offline")
+            .arg("--include-fragments")
+            .arg(archive.join("page.html.gz"))
+            .assert()
+            .failure()
+            .stdout(contains("docs.zip/site/contact.html"))
+            .stdout(contains("1 OK"))
+            .stdout(contains("1 Error"));
+
+        cargo_bin_cmd!()
+            .arg("--offline")
+            .arg(archive.join("notes.md.zst"))
+            .assert()
+            .success()
+            .stdout(contains("2 OK"));
+    }
+
+    /// Test that the content documents of EPUB books are checked, with links
+    /// and fragments within the book checked against its other files
+    #[test]
+    fn test_epub_input() {
+        let input = fixtures_path!().join("epub").join("book.epub");
+        cargo_bin_cmd!()
+            .arg("--offline")
+            .arg("--include-fragments")
+            .arg(&input)
+            .assert()
+            .failure()
+            .stdout(contains("OEBPS/chapter2.xhtml#section-3"))
+            .stdout(contains("OEBPS/chapter3.xhtml"))
+            .stdout(contains("8 OK"))
+            .stdout(contains("2 Errors"))
+            .stdout(contains("1 Excluded"));
+
+        // Only documents in the manifest are checked
+        cargo_bin_cmd!()
+            .arg("--dump-inputs")
+            .arg(&input)
+            .assert()
+            .success()
+            .stdout(contains("book.epub/OEBPS/nav.xhtml"))
+            .stdout(contains("book.epub/OEBPS/chapter1.xhtml"))
+            .stdout(contains("book.epub/OEBPS/chapter2.xhtml"))
+            .stdout(contains("draft.xhtml").not());
+    }
+
     /// Returns a regex (as a string) which matches URLs with the given base
     /// as a proper prefix. This should respect URL path components.
     fn escape_url_prefix(base_url: &str) -> String {
@@ -4549,6 +5246,99 @@ exclude_path = ["exclude_package.txt"]
         assert!(output.contains("exclude_workspace.txt"));
         Ok(())
     }
+
+    /// Serve a small website for crawling: `/docs/` links to a nested page,
+    /// which links to a missing page and to a page outside of `/docs/`.
+    async fn crawl_server() -> wiremock::MockServer {
+        let mock_server = wiremock::MockServer::start().await;
+        let html =
+            |body: &str| ResponseTemplate::new(200).set_body_raw(body.to_owned(), "text/html");
+
+        Mock::given(method("GET"))
+            .and(path("/docs/"))
+            .respond_with(html(
+                r#"<a href="nested.html">Nested</a><a href="/docs/">Self</a>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/nested.html"))
+            .respond_with(html(
+                r#"<a href="missing.html">Missing</a><a href="/blog/">Blog</a>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/blog/"))
+            .respond_with(html(r#"<a href="/blog/missing.html">Missing</a>"#))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        mock_server
+    }
+
+    #[tokio::test]
+    async fn test_crawl() {
+        let mock_server = crawl_server().await;
+
+        cargo_bin_cmd!()
+            .arg("--crawl")
+            .arg(format!("{}/docs/", mock_server.uri()))
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("4 Total"))
+            .stdout(contains("1 Error"))
+            .stdout(contains("/docs/missing.html"));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_scope_origin() {
+        let mock_server = crawl_server().await;
+
+        cargo_bin_cmd!()
+            .arg("--crawl")
+            .arg("--crawl-scope=origin")
+            .arg(format!("{}/docs/", mock_server.uri()))
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("5 Total"))
+            .stdout(contains("2 Errors"))
+            .stdout(contains("/blog/missing.html"));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_depth() {
+        let mock_server = crawl_server().await;
+
+        // `/blog/` is in scope, but too far away from the input URL
+        cargo_bin_cmd!()
+            .arg("--crawl")
+            .arg("--crawl-depth=1")
+            .arg("--crawl-scope=origin")
+            .arg(format!("{}/docs/", mock_server.uri()))
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("4 Total"))
+            .stdout(contains("1 Error"));
+    }
+
+    #[tokio::test]
+    async fn test_no_crawl_by_default() {
+        let mock_server = crawl_server().await;
+
+        cargo_bin_cmd!()
+            .arg(format!("{}/docs/", mock_server.uri()))
+            .assert()
+            .success()
+            .stdout(contains("2 Total"));
+    }
 }
 
 #[cfg(unix)]
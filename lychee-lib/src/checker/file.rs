@@ -1,12 +1,18 @@
 use http::StatusCode;
 use log::warn;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
 use crate::checker::wikilink::resolver::WikilinkResolver;
 use crate::{
-    BaseInfo, ErrorKind, FragmentCheckerOptions, Result, Status, Uri,
-    utils::fragment_checker::{FragmentChecker, FragmentInput},
+    BaseInfo, ErrorKind, FileType, FragmentCheckerOptions, Result, Status, Uri,
+    utils::{
+        archive::{self, ArchiveIndex, Members},
+        fragment_checker::{FragmentChecker, FragmentInput},
+    },
 };
 
 /// A utility for checking the existence and validity of file-based URIs.
@@ -35,6 +41,8 @@ pub(crate) struct FileChecker {
     fragment_checker: FragmentChecker,
     /// Utility for optionally resolving Wikilinks.
     wikilink_resolver: Option<WikilinkResolver>,
+    /// Cache of the archives which links point into, by their path.
+    archives: Arc<Mutex<HashMap<PathBuf, Arc<ArchiveIndex>>>>,
 }
 
 impl FileChecker {
@@ -70,6 +78,7 @@ impl FileChecker {
             fragment_checker_options,
             fragment_checker: FragmentChecker::new(),
             wikilink_resolver,
+            archives: Arc::default(),
         })
     }
 
@@ -77,6 +86,8 @@ impl FileChecker {
     ///
     /// This method resolves the URI to a file path, checks if the file exists,
     /// and optionally checks for the existence of fragments in HTML files.
+    /// Paths into an archive, like `docs.tar.gz/index.html`, are checked
+    /// against the files in the archive.
     ///
     /// # Arguments
     ///
@@ -90,6 +101,10 @@ impl FileChecker {
             return ErrorKind::InvalidFilePath(uri.clone()).into();
         };
 
+        if let Some((archive, member)) = archive::split_archive_path(&path) {
+            return self.check_archive_member(archive, member, uri).await;
+        }
+
         let path = self.resolve_local_path(&path, uri);
         match path {
             Ok(path) => self.check_file(path.as_ref(), uri).await,
@@ -285,7 +300,14 @@ impl FileChecker {
             return ErrorKind::InvalidFragment(uri.clone()).into();
         }
 
-        match FragmentInput::from_path(path).await {
+        self.check_fragment_input(FragmentInput::from_path(path).await, uri)
+            .await
+    }
+
+    /// Checks for the existence of a fragment in the given input, treating
+    /// errors while reading the input as success.
+    async fn check_fragment_input(&self, input: Result<FragmentInput<'_>>, uri: &Uri) -> Status {
+        match input {
             Ok(input) => match self
                 .fragment_checker
                 .check(input, &uri.url, self.fragment_checker_options)
@@ -304,6 +326,117 @@ impl FileChecker {
             }
         }
     }
+
+    /// Checks a path within an archive against the files in the archive,
+    /// applying fallback extensions, index files and fragment checks like
+    /// for files on disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `archive` - The path of the archive on disk.
+    /// * `member` - The path within the archive. Need not exist.
+    /// * `uri` - The original URI, used for error reporting.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Status` indicating the result of the check.
+    async fn check_archive_member(&self, archive: &Path, member: &Path, uri: &Uri) -> Status {
+        let index = self.archive_index(archive).await;
+        let members = match index.members().await {
+            Ok(members) => members,
+            Err(e) => return ErrorKind::ReadFileInput(e, archive.to_path_buf()).into(),
+        };
+
+        let member = match self.resolve_archive_member(members, member, uri) {
+            Ok(member) => member,
+            Err(err) => return err.into(),
+        };
+
+        if !self.fragment_checker_options.check_anchor_fragments
+            || uri.url.fragment().is_none_or(str::is_empty)
+        {
+            return Status::Ok(StatusCode::OK);
+        }
+
+        if members.is_dir(&member) {
+            return ErrorKind::InvalidFragment(uri.clone()).into();
+        }
+
+        let content = index
+            .content(&member, |member| {
+                FileType::from(member) != FileType::Plaintext
+            })
+            .await
+            .map_err(|e| ErrorKind::ReadFileInput(e, archive.to_path_buf()));
+        match content {
+            Err(err) => err.into(),
+            Ok(Some(content)) => {
+                let input = FragmentInput {
+                    content: Cow::Borrowed(content),
+                    file_type: FileType::from(member.as_path()),
+                };
+                self.check_fragment_input(Ok(input), uri).await
+            }
+            Ok(None) => Status::Ok(StatusCode::OK),
+        }
+    }
+
+    /// Resolves a path within an archive to a file in it, applying fallback
+    /// extensions and index files like [`FileChecker::resolve_local_path`].
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok` with the path of the file or directory in the archive, or
+    /// `Err` with an appropriate error if there is none.
+    fn resolve_archive_member(
+        &self,
+        members: &Members,
+        member: &Path,
+        uri: &Uri,
+    ) -> Result<PathBuf> {
+        if members.is_file(member) {
+            return Ok(member.to_path_buf());
+        }
+
+        if members.is_dir(member) {
+            let Some(names) = &self.index_files else {
+                return Ok(member.to_path_buf());
+            };
+
+            return names
+                .iter()
+                .find_map(|name| {
+                    if name == "." {
+                        return Some(member.to_path_buf());
+                    }
+                    let path = member.join(name);
+                    members.is_file(&path).then_some(path)
+                })
+                .ok_or_else(|| {
+                    let mut names = names.clone();
+                    names.retain(|x| !x.is_empty());
+                    ErrorKind::InvalidIndexFile(names)
+                });
+        }
+
+        self.fallback_extensions
+            .iter()
+            .map(|ext| member.with_extension(ext))
+            .find(|path| members.is_file(path))
+            .ok_or_else(|| ErrorKind::InvalidFilePath(uri.clone()))
+    }
+
+    /// Returns the index of the archive at the given path, which is shared
+    /// by all links into the archive so that it is read only once.
+    async fn archive_index(&self, archive: &Path) -> Arc<ArchiveIndex> {
+        // The archive is read after the lock is released, so that reading
+        // one archive doesn't hold up checking links into others
+        let mut archives = self.archives.lock().await;
+        let index = archives
+            .entry(archive.to_path_buf())
+            .or_insert_with(|| Arc::new(ArchiveIndex::new(archive.to_path_buf())));
+        Arc::clone(index)
+    }
 }
 
 #[cfg(test)]
@@ -425,6 +558,52 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_archive_members() {
+        let checker = FileChecker::new(
+            &BaseInfo::none(),
+            vec!["html".to_owned()],
+            Some(vec!["index.html".to_owned()]),
+            FragmentCheckerOptions {
+                check_anchor_fragments: true,
+                check_text_fragments: false,
+            },
+            false,
+        )
+        .unwrap();
+
+        for archive in ["archive/docs.tar.gz", "archive/docs.zip"] {
+            assert_filecheck!(
+                &checker,
+                &format!("{archive}/site/about.html"),
+                Status::Ok(_)
+            );
+            assert_filecheck!(&checker, &format!("{archive}/site/about"), Status::Ok(_));
+            assert_filecheck!(&checker, &format!("{archive}/site/logo.png"), Status::Ok(_));
+            assert_filecheck!(&checker, &format!("{archive}/site/guide/"), Status::Ok(_));
+            assert_filecheck!(
+                &checker,
+                &format!("{archive}/site/about.html#team"),
+                Status::Ok(_)
+            );
+            assert_filecheck!(
+                &checker,
+                &format!("{archive}/site/about.html#nope"),
+                Status::Error(InvalidFragment(_))
+            );
+            assert_filecheck!(
+                &checker,
+                &format!("{archive}/site/missing.html"),
+                Status::Error(InvalidFilePath(_))
+            );
+            assert_filecheck!(
+                &checker,
+                &format!("{archive}/"),
+                Status::Error(InvalidIndexFile(_))
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_index_files() {
         let checker = FileChecker::new(
@@ -1,8 +1,66 @@
 //! Checker Module
 //!
 //! This module contains all checkers, which are responsible for checking the status of a URL.
+//!
+//! Besides the built-in checkers for websites, files and mail addresses,
+//! library users can register their own [`Checker`]s for other URI schemes
+//! with [`ClientBuilder::checkers`](crate::ClientBuilder::checkers).
 
 pub(crate) mod file;
 pub(crate) mod mail;
 pub(crate) mod website;
 pub(crate) mod wikilink;
+
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::{Status, Uri};
+
+/// Checks URIs of a custom scheme, such as `s3://`, `doi:` or `ipfs://`.
+///
+/// Checkers are registered by scheme on the [`ClientBuilder`](crate::ClientBuilder).
+/// They take precedence over the built-in checkers, but URIs which are
+/// excluded (e.g. with `--exclude`) are never passed to a checker.
+///
+/// # Example
+///
+/// ```rust
+/// use std::{collections::HashMap, sync::Arc};
+///
+/// use async_trait::async_trait;
+/// use http::StatusCode;
+/// use lychee_lib::{Checker, ClientBuilder, Result, Status, Uri};
+///
+/// #[derive(Debug)]
+/// struct DoiChecker;
+///
+/// #[async_trait]
+/// impl Checker for DoiChecker {
+///     async fn check(&self, uri: &Uri) -> Status {
+///         // Resolve the DOI here
+///         Status::Ok(StatusCode::OK)
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let checkers = HashMap::from([("doi".to_string(), Arc::new(DoiChecker) as _)]);
+///     let client = ClientBuilder::builder()
+///         .checkers(checkers)
+///         .build()
+///         .client()?;
+///
+///     let response = client.check("doi:10.1000/182").await?;
+///     assert!(response.status().is_success());
+///     Ok(())
+/// }
+/// ```
+#[async_trait]
+pub trait Checker: Debug + Send + Sync {
+    /// Check the given URI and return its status
+    async fn check(&self, uri: &Uri) -> Status;
+}
+
+/// Custom [`Checker`]s by URI scheme (without the trailing `:`)
+pub type Checkers = HashMap<String, Arc<dyn Checker>>;
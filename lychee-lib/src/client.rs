@@ -31,9 +31,9 @@ use typed_builder::TypedBuilder;
 use crate::{
     BaseInfo, BasicAuthCredentials, ErrorKind, Request, Response, Result, Status, Uri,
     chain::RequestChain,
-    checker::{file::FileChecker, mail::MailChecker, website::WebsiteChecker},
+    checker::{Checkers, file::FileChecker, mail::MailChecker, website::WebsiteChecker},
     filter::Filter,
-    ratelimit::{ClientMap, HostConfigs, HostKey, HostPool, RateLimitConfig},
+    ratelimit::{ClientMap, HostConfigs, HostKey, HostPool, RateLimitConfig, Recording},
     remap::Remaps,
     types::{DEFAULT_ACCEPTED_STATUS_CODES, Redirects, redirect_history::RedirectHistory},
 };
@@ -328,11 +328,26 @@ pub struct ClientBuilder {
     /// skipped and the lychee-internal request chain is not activated.
     plugin_request_chain: RequestChain,
 
+    /// Custom [`Checker`](crate::Checker)s by URI scheme.
+    ///
+    /// URIs with one of these schemes are passed to the registered checker
+    /// instead of the built-in website, file or mail checkers. This makes
+    /// it possible to check schemes like `s3://` or `doi:`, which lychee
+    /// doesn't support otherwise.
+    checkers: Checkers,
+
     /// Global rate limiting configuration that applies as defaults to all hosts
     rate_limit_config: RateLimitConfig,
 
     /// Per-host configuration overrides
     hosts: HostConfigs,
+
+    /// Record all HTTP responses or replay previously recorded ones.
+    ///
+    /// When a [`Recording`] is replayed, no HTTP requests are sent at all.
+    /// Requests without a recorded response fail with
+    /// [`ErrorKind::NotRecorded`].
+    recording: Option<Arc<Recording>>,
 }
 
 impl Default for ClientBuilder {
@@ -366,12 +381,15 @@ impl ClientBuilder {
 
         let client_map = self.build_host_clients(&redirect_history)?;
 
-        let host_pool = HostPool::new(
+        let mut host_pool = HostPool::new(
             self.rate_limit_config,
             self.hosts,
             reqwest_client,
             client_map,
         );
+        if let Some(recording) = self.recording {
+            host_pool = host_pool.with_recording(recording, redirect_history.clone());
+        }
 
         let github_client = match self.github_token.as_ref().map(ExposeSecret::expose_secret) {
             Some(token) if !token.is_empty() => Some(
@@ -413,6 +431,7 @@ impl ClientBuilder {
         Ok(Client {
             remaps: self.remaps,
             filter,
+            checkers: self.checkers,
             email_checker: MailChecker::new(self.timeout),
             website_checker,
             file_checker: FileChecker::new(
@@ -515,6 +534,9 @@ pub struct Client {
     /// Rules to decide whether a given link should be checked or ignored.
     filter: Filter,
 
+    /// Custom checkers by URI scheme.
+    checkers: Checkers,
+
     /// A checker for website URLs.
     website_checker: WebsiteChecker,
 
@@ -560,8 +582,9 @@ impl Client {
         let start = std::time::Instant::now(); // Measure check time
         let remap = self.remap(&mut uri)?.inspect(|r| debug!("Remapping {r}"));
 
-        let (status, redirects) = match uri.scheme() {
+        let (status, redirects) = match self.checkers.get(uri.scheme()) {
             _ if self.is_excluded(&uri) => (Status::Excluded, None),
+            Some(checker) => (checker.check(&uri).await, None),
             _ if uri.is_tel() => (Status::Excluded, None), // We don't check tel: URIs
             _ if uri.is_file() => (self.check_file(&uri).await, None),
             _ if uri.is_mail() => (self.check_mail(&uri).await, None),
@@ -658,6 +681,7 @@ where
 mod tests {
     use std::{
         fs::File,
+        sync::Arc,
         time::{Duration, Instant},
     };
 
@@ -675,8 +699,9 @@ mod tests {
 
     use super::ClientBuilder;
     use crate::{
-        ErrorKind, Redirect, Redirects, Request, Status, Uri,
+        Checker, ErrorKind, Redirect, Redirects, Request, Status, Uri,
         chain::{ChainResult, Handler, RequestChain},
+        ratelimit::Recording,
         remap::{Remap, Remaps},
     };
 
@@ -1001,6 +1026,46 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        redirecting_mock_server!(async |redirect_url: Url, ok_url: Url| {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("recording.json");
+
+            let recording = Arc::new(Recording::record());
+            let recorded = ClientBuilder::builder()
+                .recording(recording.clone())
+                .build()
+                .client()
+                .unwrap()
+                .check(Uri::from(redirect_url.clone()))
+                .await
+                .unwrap();
+            recording.save(&path).unwrap();
+
+            let client = ClientBuilder::builder()
+                .recording(Arc::new(Recording::replay(&path).unwrap()))
+                .build()
+                .client()
+                .unwrap();
+            let replayed = client.check(Uri::from(redirect_url)).await.unwrap();
+
+            assert_eq!(replayed.status(), &Status::Ok(StatusCode::OK));
+            assert_eq!(replayed.status(), recorded.status());
+            assert_eq!(replayed.redirects(), recorded.redirects());
+
+            // Requests which weren't recorded are not sent
+            let mut unrecorded = ok_url;
+            unrecorded.set_path("/unrecorded");
+            let res = client.check(Uri::from(unrecorded)).await.unwrap();
+            assert!(matches!(
+                res.status(),
+                Status::Error(ErrorKind::NotRecorded(_))
+            ));
+        })
+        .await;
+    }
+
     #[tokio::test]
     async fn test_remaps() {
         let mapped = String::from("file:///nope");
@@ -1046,6 +1111,46 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_custom_checker() {
+        use std::{collections::HashMap, sync::Arc};
+
+        #[derive(Debug)]
+        struct S3Checker;
+
+        #[async_trait]
+        impl Checker for S3Checker {
+            async fn check(&self, uri: &Uri) -> Status {
+                if uri.as_str().starts_with("s3://bucket/") {
+                    Status::Ok(StatusCode::OK)
+                } else {
+                    Status::Error(ErrorKind::InvalidUrlHost)
+                }
+            }
+        }
+
+        let client = ClientBuilder::builder()
+            .checkers(HashMap::from([(
+                "s3".to_string(),
+                Arc::new(S3Checker) as Arc<dyn Checker>,
+            )]))
+            .excludes(regex::RegexSet::new([r"s3://excluded/"]).unwrap())
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check("s3://bucket/key").await.unwrap();
+        assert!(res.status().is_success());
+        let res = client.check("s3://other/key").await.unwrap();
+        assert!(res.status().is_error());
+        let res = client.check("s3://excluded/key").await.unwrap();
+        assert!(res.status().is_excluded());
+
+        // Other schemes are still handled by the built-in checkers
+        let res = client.check("slack://example.com").await.unwrap();
+        assert!(res.status().is_unsupported());
+    }
+
     #[tokio::test]
     async fn test_chain() {
         use reqwest::Request;
@@ -3,8 +3,15 @@ use crate::filter::PathExcludes;
 use crate::ratelimit::HostPool;
 use crate::types::resolver::UrlContentResolver;
 use crate::{
-    BaseInfo, Input, LycheeResult, Request, RequestError, basic_auth::BasicAuthExtractor,
-    extract::Extractor, types::FileExtensions, types::uri::raw::RawUri, utils::request,
+    BaseInfo, Input, InputContent, LycheeResult, Request, RequestError, ResolvedInputSource,
+    basic_auth::BasicAuthExtractor,
+    extract::{
+        CustomExtractor, CustomFormat, Extractor, ExtractorOptions, KeyPaths, XmlSelector,
+        rst::RstLinks,
+    },
+    types::FileExtensions,
+    types::uri::raw::RawUri,
+    utils::request,
 };
 use futures::TryStreamExt;
 use futures::{
@@ -14,9 +21,11 @@ use futures::{
 use http::HeaderMap;
 use log::warn;
 use par_stream::ParStreamExt;
-use std::collections::HashSet;
+use reqwest::Url;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use tokio::sync::Mutex;
 
 /// Collector keeps the state of link collection
 /// It drives the link extraction from inputs
@@ -44,6 +53,7 @@ pub struct Collector {
     /// limits, and custom headers.
     host_pool: Arc<HostPool>,
     preprocessor: Option<Preprocessor>,
+    extractor_options: ExtractorOptions,
 }
 
 impl Default for Collector {
@@ -68,6 +78,7 @@ impl Default for Collector {
             host_pool: Arc::new(HostPool::default()),
             excluded_paths: PathExcludes::empty(),
             preprocessor: None,
+            extractor_options: ExtractorOptions::default(),
         }
     }
 }
@@ -121,6 +132,7 @@ impl Collector {
             headers: HeaderMap::new(),
             host_pool: Arc::new(HostPool::default()),
             excluded_paths: PathExcludes::empty(),
+            extractor_options: ExtractorOptions::default(),
             root_dir,
             base,
         })
@@ -219,6 +231,65 @@ impl Collector {
         self
     }
 
+    /// Register a [`CustomExtractor`] for documents of the given format.
+    ///
+    /// Custom extractors take precedence over the built-in extractors.
+    /// Files with a custom extension are also collected when searching
+    /// directories for inputs, in addition to the given extensions.
+    #[must_use]
+    pub fn custom_extractor(
+        mut self,
+        format: CustomFormat,
+        extractor: Arc<dyn CustomExtractor>,
+    ) -> Self {
+        self.extractor_options
+            .custom_extractors
+            .insert(format, extractor);
+        self
+    }
+
+    /// Only check the links of values with the given [`KeyPaths`]
+    /// in JSON, YAML and TOML files
+    #[must_use]
+    pub fn key_paths(mut self, key_paths: KeyPaths) -> Self {
+        self.extractor_options.key_paths = key_paths;
+        self
+    }
+
+    /// Extract links from the XML elements and attributes selected by the
+    /// given [`XmlSelector`]s
+    #[must_use]
+    pub fn xml_selectors(mut self, xml_selectors: Vec<XmlSelector>) -> Self {
+        self.extractor_options.xml_selectors = xml_selectors;
+        self
+    }
+
+    /// Extract the links of a single document with the settings of this
+    /// collector, e.g. of an unsaved document which is open in an editor.
+    ///
+    /// Relative links are resolved against the source of the content.
+    /// Labels of reStructuredText `:ref:` roles aren't checked, because they
+    /// can be defined in other documents.
+    #[must_use]
+    pub fn collect_links_from_content(
+        &self,
+        content: &InputContent,
+    ) -> Vec<Result<Request, RequestError>> {
+        let extractor = Extractor::new(
+            self.use_html5ever,
+            self.include_verbatim,
+            self.include_wikilinks,
+        );
+
+        request::create(
+            extractor.extract_with(content, &self.extractor_options),
+            &content.source,
+            self.root_dir.as_deref(),
+            &self.base,
+            self.basic_auth_extractor.as_ref(),
+        )
+    }
+
     /// Convenience method to fetch all unique links from inputs
     /// with the default extensions.
     pub fn collect_links(
@@ -232,6 +303,11 @@ impl Collector {
     /// All relative URLs get prefixed with `base` (if given).
     /// (This can be a directory or a base URL)
     ///
+    /// Labels of reStructuredText `:ref:` roles are resolved against the
+    /// labels of all inputs, so they are checked after all other links.
+    /// Labels which aren't defined by any input are skipped with a warning,
+    /// as they may be defined by documents which weren't collected.
+    ///
     /// # Errors
     ///
     /// Will return `Err` if links cannot be extracted from an input
@@ -246,6 +322,16 @@ impl Collector {
         let global_base = self.base;
         let excluded_paths = self.excluded_paths;
 
+        let mut extensions = extensions;
+        let custom_extensions: Vec<String> = self
+            .extractor_options
+            .custom_extractors
+            .extensions()
+            .filter(|&extension| !extensions.contains(extension))
+            .map(String::from)
+            .collect();
+        extensions.extend(custom_extensions);
+
         let resolver = UrlContentResolver {
             basic_auth_extractor: self.basic_auth_extractor.clone(),
             headers: self.headers.clone(),
@@ -257,6 +343,13 @@ impl Collector {
             self.include_verbatim,
             self.include_wikilinks,
         );
+        let extractor_options = Arc::new(self.extractor_options);
+
+        let labels = Arc::new(Mutex::new(Labels::default()));
+        let all_labels = labels.clone();
+        let root_dir = self.root_dir.clone();
+        let basic_auth_extractor = self.basic_auth_extractor.clone();
+        let base = global_base.clone();
 
         stream::iter(inputs)
             .par_then_unordered(None, move |input| {
@@ -282,9 +375,12 @@ impl Collector {
                 let global_base = global_base.clone();
                 let root_dir = self.root_dir.clone();
                 let basic_auth_extractor = self.basic_auth_extractor.clone();
+                let extractor_options = extractor_options.clone();
+                let labels = labels.clone();
                 async move {
                     let content = content?;
-                    let uris: Vec<RawUri> = extractor.extract(&content);
+                    let links = extractor.extract_with_labels(&content, &extractor_options);
+                    let uris = labels.lock().await.add(&content.source, links);
                     let requests = request::create(
                         uris,
                         &content.source,
@@ -296,6 +392,95 @@ impl Collector {
                 }
             })
             .try_flatten()
+            .chain(
+                // Only runs once all inputs are collected
+                stream::once(async move {
+                    let labels = std::mem::take(&mut *all_labels.lock().await);
+                    stream::iter(labels.resolve(
+                        root_dir.as_deref(),
+                        &base,
+                        basic_auth_extractor.as_ref(),
+                    ))
+                })
+                .flatten(),
+            )
+    }
+}
+
+/// The labels of all reStructuredText documents and the references to them,
+/// which can only be resolved once all documents are collected
+#[derive(Debug, Default)]
+struct Labels {
+    /// The document which defines each label
+    definitions: HashMap<String, ResolvedInputSource>,
+    /// The references to labels and the documents they appear in
+    references: Vec<(RawUri, ResolvedInputSource)>,
+}
+
+impl Labels {
+    /// Remember the labels of a document and return its other links
+    fn add(&mut self, source: &ResolvedInputSource, links: RstLinks) -> Vec<RawUri> {
+        for label in links.labels {
+            self.definitions
+                .entry(label)
+                .or_insert_with(|| source.clone());
+        }
+        self.references.extend(
+            links
+                .references
+                .into_iter()
+                .map(|reference| (reference, source.clone())),
+        );
+        links.uris
+    }
+
+    /// Create requests for the references to the labels, which link to the
+    /// documents defining them.
+    ///
+    /// References to undefined labels are skipped with a warning, because
+    /// only part of a project may have been collected, e.g. with
+    /// `--changed-since`.
+    fn resolve(
+        self,
+        root_dir: Option<&Path>,
+        base: &BaseInfo,
+        extractor: Option<&BasicAuthExtractor>,
+    ) -> Vec<Result<Request, RequestError>> {
+        let mut requests = Vec::new();
+        for (mut reference, source) in self.references {
+            let Some(definition) = self.definitions.get(&reference.text) else {
+                warn!(
+                    "{source}: Label '{}' is not defined in any input, so its reference isn't checked",
+                    reference.text
+                );
+                continue;
+            };
+            // Labels in documents without a URL, e.g. stdin, can't be linked
+            let Some(mut url) = document_url(definition) else {
+                continue;
+            };
+            url.set_fragment(Some(&reference.text));
+            reference.text = url.into();
+            requests.extend(request::create(
+                vec![reference],
+                &source,
+                root_dir,
+                base,
+                extractor,
+            ));
+        }
+        requests
+    }
+}
+
+/// The URL of a document, if it has one
+fn document_url(source: &ResolvedInputSource) -> Option<Url> {
+    match source {
+        ResolvedInputSource::RemoteUrl(url) => Some(*url.clone()),
+        ResolvedInputSource::FsPath(path) => {
+            Url::from_file_path(std::path::absolute(path).ok()?).ok()
+        }
+        _ => None,
     }
 }
 
@@ -312,7 +497,7 @@ mod tests {
     use crate::{
         LycheeResult, Uri,
         filter::PathExcludes,
-        types::{FileType, Input, InputSource},
+        types::{FileType, Input, InputSource, ResolvedInputSource},
     };
 
     // Helper function to run the collector on the given inputs
@@ -579,6 +764,31 @@ mod tests {
         assert_eq!(links, expected_links);
     }
 
+    #[tokio::test]
+    async fn test_remote_file_type_from_content_type() {
+        let contents = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+    <channel>
+        <link>https://example.com/item</link>
+    </channel>
+</rss>"#;
+        let mock_server = mock_server!(
+            StatusCode::OK,
+            set_body_raw(contents, "application/rss+xml; charset=utf-8")
+        );
+
+        let feed_url = Url::parse(&format!("{}/feed", mock_server.uri())).unwrap();
+        let input = Input::from_input_source(InputSource::RemoteUrl(Box::new(feed_url)));
+
+        let inputs = HashSet::from_iter([input]);
+        let links = collect(inputs, None, BaseInfo::none()).await.ok().unwrap();
+
+        assert_eq!(
+            links,
+            HashSet::from_iter([website!("https://example.com/item")])
+        );
+    }
+
     #[tokio::test]
     async fn test_relative_url_with_base_extracted_from_input() {
         let contents = r#"<html>
@@ -711,6 +921,169 @@ mod tests {
         assert_eq!(links, expected_links);
     }
 
+    /// Extracts the rest of each line after `link: `
+    #[derive(Debug)]
+    struct LineExtractor;
+
+    impl CustomExtractor for LineExtractor {
+        fn extract(&self, content: &str) -> Vec<RawUri> {
+            content
+                .lines()
+                .filter_map(|line| line.strip_prefix("link: "))
+                .map(|text| RawUri {
+                    text: text.to_string(),
+                    element: None,
+                    attribute: None,
+                    span: crate::types::uri::raw::span(1, 7),
+                })
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_extractor_for_extension() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("doc.links"),
+            "link: https://custom.example/",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("doc.unknown"),
+            "https://ignored.example/",
+        )
+        .unwrap();
+
+        let inputs = HashSet::from([Input::from_value(temp_dir.path().to_str().unwrap()).unwrap()]);
+        let links: HashSet<Uri> = Collector::default()
+            .custom_extractor(
+                CustomFormat::Extension("links".to_string()),
+                Arc::new(LineExtractor),
+            )
+            .collect_links(inputs)
+            .map(|r| r.unwrap().uri)
+            .collect()
+            .await;
+
+        assert_eq!(links, HashSet::from([website!("https://custom.example/")]));
+    }
+
+    #[tokio::test]
+    async fn test_custom_extractor_for_mime_type() {
+        let mock_server = mock_server!(
+            StatusCode::OK,
+            set_body_raw("link: https://custom.example/", "text/x-links")
+        );
+
+        let inputs = HashSet::from([Input::from_value(&mock_server.uri()).unwrap()]);
+        let links: HashSet<Uri> = Collector::default()
+            .custom_extractor(
+                CustomFormat::MimeType("text/x-links".to_string()),
+                Arc::new(LineExtractor),
+            )
+            .collect_links(inputs)
+            .map(|r| r.unwrap().uri)
+            .collect()
+            .await;
+
+        assert_eq!(links, HashSet::from([website!("https://custom.example/")]));
+    }
+
+    #[tokio::test]
+    async fn test_rst_label_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::write(
+            dir.join("index.rst"),
+            "See :ref:`install` and :ref:`the guide <Getting Started>`.\n\n:ref:`missing`",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("install.rst"),
+            ".. _install:\n\nInstall\n=======\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("guide.rst"), ".. _getting started:\n").unwrap();
+
+        let inputs = HashSet::from([Input::from_value(dir.to_str().unwrap()).unwrap()]);
+        let results: Vec<_> = Collector::default().collect_links(inputs).collect().await;
+
+        let mut links: Vec<_> = results
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .map(|request| {
+                let path = request.uri.url.to_file_path().unwrap();
+                let name = path.strip_prefix(&dir).unwrap().display().to_string();
+                (name, request.uri.url.fragment().unwrap().to_string())
+            })
+            .collect();
+        links.sort();
+        assert_eq!(
+            links,
+            [
+                ("guide.rst".to_string(), "getting%20started".to_string()),
+                ("install.rst".to_string(), "install".to_string()),
+            ]
+        );
+
+        // Undefined labels may be defined in documents which weren't collected
+        assert!(results.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn test_rst_label_references_across_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = std::fs::canonicalize(dir.path()).unwrap();
+        let definition = dir.join("a.rst");
+        let reference = dir.join("b.rst");
+        std::fs::write(&definition, ".. _intro:\n\nIntro\n=====\n").unwrap();
+        std::fs::write(&reference, "See :ref:`intro`.").unwrap();
+
+        let input = |path: &Path| Input::from_value(path.to_str().unwrap()).unwrap();
+
+        let both = HashSet::from([input(&definition), input(&reference)]);
+        let results: Vec<_> = Collector::default().collect_links(both).collect().await;
+        let urls: Vec<_> = results
+            .into_iter()
+            .map(|result| result.unwrap().uri.url)
+            .collect();
+        let mut expected = Url::from_file_path(&definition).unwrap();
+        expected.set_fragment(Some("intro"));
+        assert_eq!(urls, [expected]);
+
+        // Checking only the reference, e.g. with `--changed-since`
+        let results: Vec<_> = Collector::default()
+            .collect_links(HashSet::from([input(&reference)]))
+            .collect()
+            .await;
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_collect_links_from_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = InputContent {
+            source: ResolvedInputSource::FsPath(dir.path().join("README.md")),
+            file_type: FileType::Markdown,
+            content_type: None,
+            content: "[a](https://example.com/a)\n\n[b](b.md)".to_string(),
+        };
+
+        let requests: Vec<Request> = Collector::default()
+            .collect_links_from_content(&content)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].uri, website!("https://example.com/a"));
+        assert_eq!(
+            requests[1].uri.url.to_file_path().unwrap(),
+            dir.path().join("b.md")
+        );
+        assert_eq!(requests[1].span.unwrap().line.get(), 3);
+    }
+
     #[tokio::test]
     async fn test_file_path_with_base() {
         let base = BaseInfo::try_from("/path/to/root").unwrap();
@@ -0,0 +1,373 @@
+//! Recursive crawling of websites.
+//!
+//! By default, lychee only checks the links found in the inputs it is given.
+//! A [`Crawler`] extends this to whole websites: whenever a link to an HTML
+//! page within the crawl scope was checked successfully, the page is fed back
+//! into the [`Collector`] as a new input, so that its links get checked too.
+//!
+//! Crawling starts at the [`InputSource::RemoteUrl`] inputs. Each page is
+//! visited at most once and pages further away from the start URLs than the
+//! configured maximum depth are not followed.
+
+use std::{collections::HashSet, ffi::OsStr, path::Path};
+
+use dashmap::{DashMap, mapref::entry::Entry};
+use futures::stream::Stream;
+use serde::Deserialize;
+use strum::{Display, EnumString, VariantNames};
+use url::Url;
+
+use crate::{
+    Collector, FileExtensions, FileType, Input, InputSource, Request, RequestError, Response,
+};
+
+/// Default maximum depth of a crawl, counted from the start URLs
+pub const DEFAULT_CRAWL_DEPTH: usize = 5;
+
+/// Determines which pages are followed when crawling a website.
+#[derive(
+    Debug, Deserialize, Default, Clone, Copy, Display, EnumString, VariantNames, PartialEq, Eq,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+pub enum CrawlScope {
+    /// Follow links to any page with the same origin (scheme, host and port)
+    /// as one of the start URLs
+    Origin,
+    /// Only follow links to pages below the directory of one of the start
+    /// URLs. For example, a start URL of `https://example.com/docs/` (or
+    /// `https://example.com/docs/index.html`) limits the crawl to pages below
+    /// `https://example.com/docs/`.
+    #[default]
+    Prefix,
+}
+
+/// Feeds pages that were checked successfully back into a [`Collector`].
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct Crawler {
+    collector: Collector,
+    extensions: FileExtensions,
+    max_depth: usize,
+    scope: CrawlScope,
+    /// The start URLs of the crawl
+    roots: Vec<Url>,
+    /// All pages which were queued for crawling so far,
+    /// together with their distance from the start URLs
+    visited: DashMap<Url, usize>,
+}
+
+impl Crawler {
+    /// Create a new crawler which starts at the remote URLs of the given
+    /// inputs. Other inputs (e.g. local files) don't start a crawl.
+    ///
+    /// Links to crawled pages are extracted with the given `collector`.
+    #[must_use]
+    pub fn new(collector: Collector, inputs: &HashSet<Input>) -> Self {
+        let roots: Vec<Url> = inputs
+            .iter()
+            .filter_map(|input| match &input.source {
+                InputSource::RemoteUrl(url) => Some(without_fragment(url)),
+                _ => None,
+            })
+            .collect();
+        let visited = roots.iter().map(|url| (url.clone(), 0)).collect();
+
+        Self {
+            collector,
+            extensions: FileType::default_extensions(),
+            max_depth: DEFAULT_CRAWL_DEPTH,
+            scope: CrawlScope::default(),
+            roots,
+            visited,
+        }
+    }
+
+    /// Set the maximum depth of the crawl.
+    ///
+    /// Links on the start URLs have a depth of one. With a maximum depth of
+    /// zero, no pages besides the start URLs are visited.
+    #[must_use]
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set the [`CrawlScope`] which decides which pages are followed
+    #[must_use]
+    pub const fn scope(mut self, scope: CrawlScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Set the file extensions passed on to the [`Collector`]
+    #[must_use]
+    pub fn extensions(mut self, extensions: FileExtensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Returns `true` if at least one input is a remote URL,
+    /// i.e. if crawling can find any pages at all.
+    #[must_use]
+    pub const fn has_roots(&self) -> bool {
+        !self.roots.is_empty()
+    }
+
+    /// Crawl the page behind the given response.
+    ///
+    /// If the response belongs to a successfully checked HTML page which is in
+    /// scope, within the maximum depth and wasn't visited before, the links of
+    /// that page are returned as new requests. Otherwise, `None` is returned.
+    #[must_use = "crawled pages are marked as visited, so their links must be used"]
+    pub fn crawl(
+        &self,
+        response: &Response,
+    ) -> Option<impl Stream<Item = Result<Request, RequestError>> + use<>> {
+        let url = self.next_page(response)?;
+
+        let input = Input {
+            source: InputSource::RemoteUrl(Box::new(url)),
+            file_type_hint: Some(FileType::Html),
+        };
+
+        Some(
+            self.collector
+                .clone()
+                .collect_links_from_file_types(HashSet::from([input]), self.extensions.clone()),
+        )
+    }
+
+    /// Decide whether the page behind the response should be crawled and
+    /// mark it as visited if so.
+    fn next_page(&self, response: &Response) -> Option<Url> {
+        if !response.status().is_success() {
+            return None;
+        }
+
+        let InputSource::RemoteUrl(source) = response.source() else {
+            return None;
+        };
+        let depth = *self.visited.get(&without_fragment(source))?;
+        if depth >= self.max_depth {
+            return None;
+        }
+
+        let url = without_fragment(&response.body().uri.url);
+        if !matches!(url.scheme(), "http" | "https") || !is_html_page(&url) || !self.in_scope(&url)
+        {
+            return None;
+        }
+
+        match self.visited.entry(url) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => {
+                let url = entry.key().clone();
+                entry.insert(depth + 1);
+                Some(url)
+            }
+        }
+    }
+
+    fn in_scope(&self, url: &Url) -> bool {
+        self.roots.iter().any(|root| match self.scope {
+            CrawlScope::Origin => root.origin() == url.origin(),
+            CrawlScope::Prefix => root
+                .join("./")
+                .is_ok_and(|prefix| url.as_str().starts_with(prefix.as_str())),
+        })
+    }
+}
+
+/// Pages without a file extension (e.g. `/docs/intro`) are assumed to be HTML.
+fn is_html_page(url: &Url) -> bool {
+    match Path::new(url.path()).extension().and_then(OsStr::to_str) {
+        Some(extension) => FileType::from_extension(extension) == Some(FileType::Html),
+        None => true,
+    }
+}
+
+fn without_fragment(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseInfo, ErrorKind, Status, Uri};
+    use futures::StreamExt;
+    use http::StatusCode;
+    use test_utils::mock_server;
+
+    fn crawler(roots: &[&str]) -> Crawler {
+        let inputs = roots
+            .iter()
+            .map(|root| Input::from_value(root).unwrap())
+            .collect();
+        Crawler::new(Collector::new(None, BaseInfo::none()).unwrap(), &inputs)
+    }
+
+    fn response(uri: &str, source: &str, status: Status) -> Response {
+        Response::new(
+            Uri::try_from(uri).unwrap(),
+            status,
+            None,
+            None,
+            InputSource::RemoteUrl(Box::new(Url::parse(source).unwrap())),
+            None,
+            None,
+        )
+    }
+
+    fn ok(uri: &str, source: &str) -> Response {
+        response(uri, source, Status::Ok(StatusCode::OK))
+    }
+
+    #[test]
+    fn test_prefix_scope() {
+        let crawler = crawler(&["https://example.com/docs/index.html"]);
+        let root = "https://example.com/docs/index.html";
+
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/docs/a", root))
+                .is_some()
+        );
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/docs/b/c.html", root))
+                .is_some()
+        );
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/blog/", root))
+                .is_none()
+        );
+        assert!(
+            crawler
+                .next_page(&ok("https://other.com/docs/d", root))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_origin_scope() {
+        let crawler = crawler(&["https://example.com/docs/"]).scope(CrawlScope::Origin);
+        let root = "https://example.com/docs/";
+
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/blog/", root))
+                .is_some()
+        );
+        assert!(
+            crawler
+                .next_page(&ok("http://example.com/blog/", root))
+                .is_none()
+        );
+        assert!(
+            crawler
+                .next_page(&ok("https://sub.example.com/", root))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_only_html_pages_are_crawled() {
+        let crawler = crawler(&["https://example.com/"]);
+        let root = "https://example.com/";
+
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/style.css", root))
+                .is_none()
+        );
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/image.png", root))
+                .is_none()
+        );
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/page.htm", root))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_failed_pages_are_not_crawled() {
+        let crawler = crawler(&["https://example.com/"]);
+        let response = response(
+            "https://example.com/404",
+            "https://example.com/",
+            Status::Error(ErrorKind::RejectedStatusCode(StatusCode::NOT_FOUND)),
+        );
+
+        assert!(crawler.next_page(&response).is_none());
+    }
+
+    #[test]
+    fn test_pages_are_visited_once() {
+        let crawler = crawler(&["https://example.com/"]);
+        let root = "https://example.com/";
+
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/a", root))
+                .is_some()
+        );
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/a#section", root))
+                .is_none()
+        );
+        assert!(
+            crawler
+                .next_page(&ok("https://example.com/", root))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let crawler = crawler(&["https://example.com/"]).max_depth(2);
+
+        let depth_1 = crawler.next_page(&ok("https://example.com/1", "https://example.com/"));
+        assert!(depth_1.is_some());
+        let depth_2 = crawler.next_page(&ok("https://example.com/2", "https://example.com/1"));
+        assert!(depth_2.is_some());
+        let depth_3 = crawler.next_page(&ok("https://example.com/3", "https://example.com/2"));
+        assert!(depth_3.is_none());
+    }
+
+    #[test]
+    fn test_links_from_unknown_sources_are_not_crawled() {
+        let crawler = crawler(&["https://example.com/"]);
+        let response = ok("https://example.com/a", "https://example.com/unvisited");
+
+        assert!(crawler.next_page(&response).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_collects_links_of_page() {
+        let mock_server = mock_server!(
+            StatusCode::OK,
+            set_body_string(r#"<a href="https://example.com/">Link</a>"#)
+        );
+        let root = format!("{}/", mock_server.uri());
+        let crawler = crawler(&[&root]);
+
+        let requests: Vec<_> = crawler
+            .crawl(&ok(&format!("{root}page.html"), &root))
+            .unwrap()
+            .collect()
+            .await;
+
+        assert_eq!(requests.len(), 1);
+        let request = requests[0].as_ref().unwrap();
+        assert_eq!(request.uri, Uri::try_from("https://example.com/").unwrap());
+    }
+}
@@ -0,0 +1,546 @@
+//! Extract links and fragments from AsciiDoc documents.
+//!
+//! The following links are extracted:
+//! - `link:` macros and URLs, including URL macros like `https://example.com[text]`
+//! - cross references, i.e. `xref:` macros and `<<anchor>>` shorthands.
+//!   References to other documents are resolved to their `.adoc` file.
+//! - the targets of `image:` and `image::` macros and `include::` directives
+//! - references to document attributes whose value is a URL, e.g. `{url-repo}[repository]`
+//!
+//! Links in comments are ignored, and so are links in literal, listing and
+//! passthrough blocks unless verbatim content is included.
+//! Antora resource IDs with a module, component or family,
+//! e.g. `xref:module:page.adoc[]`, can't be resolved and aren't checked.
+
+use std::{collections::HashMap, collections::HashSet, ops::Range, path::Path, sync::LazyLock};
+
+use regex::{Captures, Regex};
+
+use crate::{
+    types::uri::raw::{OffsetSpanProvider, RawUri, RawUriSpan, SourceSpanProvider, SpanProvider},
+    utils::url,
+};
+
+/// Attribute entry, e.g. `:url-repo: https://github.com/lycheeverse/lychee`
+static ATTRIBUTE_ENTRY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^:(?P<name>[\w-]+):(?:\s+(?P<value>.*?))?\s*$").unwrap());
+
+/// Block macro with a target, e.g. `image::logo.png[Logo]`
+static BLOCK_MACRO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:image|include)::(?P<target>[^\s\[]+)\[").unwrap());
+
+/// Inline markup with links
+static INLINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?x)
+        \+\+\+.*?\+\+\+ | `\+.*?\+` | pass:\[.*?\]            # passthrough
+        | \b(?P<macro>link|xref|image):(?P<target>[^\s\[\]:][^\s\[\]]*)\[
+        | <<(?P<anchor>[^\s,>]+)(?:,[^>]*)?>>                 # cross reference
+        | \{(?P<attribute>[\w-]+)\}(?P<rest>[^\s\[\]]*)       # attribute reference
+        ",
+    )
+    .unwrap()
+});
+
+/// Attribute reference within a target, e.g. `{docs-url}/guide.html`
+static ATTRIBUTE_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{(?P<name>[\w-]+)\}").unwrap());
+
+/// Section title, e.g. `== Getting started`
+static SECTION_TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:={2,6}|#{2,6})\s+(?P<title>\S.*?)\s*$").unwrap());
+
+/// Explicit ids, i.e. anchors like `[[id]]` or `anchor:id[]` and block ids like `[#id]`
+static EXPLICIT_ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[\[(?P<anchor>[\w:.-]+)(?:,[^\]]*)?\]\]|\banchor:(?P<macro>[\w:.-]+)\[|^\[#(?P<block>[\w:-]+)[^\]]*\]$")
+        .unwrap()
+});
+
+/// Characters which are removed from section titles to generate their ids
+static INVALID_ID_CHARS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<[^>]+>|&(?:[a-z][a-z]+\d{0,2}|#\d{2,5}|#x[\da-f]{2,5});|[^ \w.-]+").unwrap()
+});
+
+/// A list item, which is not a literal paragraph when indented
+static LIST_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:[*.-]+|\d+\.|[a-zA-Z]\.)\s").unwrap());
+
+/// Extract unparsed URL strings from an AsciiDoc document
+pub(crate) fn extract_asciidoc(input: &str, include_verbatim: bool) -> Vec<RawUri> {
+    let span_provider = SourceSpanProvider::from_input(input);
+    let mut attributes = HashMap::new();
+    let mut uris = Vec::new();
+
+    for (offset, line) in content_lines(input, include_verbatim) {
+        if let Some(captures) = ATTRIBUTE_ENTRY.captures(line) {
+            let value = captures.name("value").map_or("", |value| value.as_str());
+            attributes.insert(captures["name"].to_string(), value.to_string());
+            continue;
+        }
+
+        let span_provider = OffsetSpanProvider {
+            offset,
+            inner: &span_provider,
+        };
+        if let Some(target) = BLOCK_MACRO.captures(line).and_then(|c| c.name("target")) {
+            if let Some(text) = local_or_url(target.as_str(), &attributes) {
+                uris.push(raw_uri(text, span_provider.span(target.start())));
+            }
+            continue;
+        }
+        extract_inline(line, &attributes, &span_provider, &mut uris);
+    }
+    uris
+}
+
+/// Extract the links of inline macros and standalone URLs from a line
+fn extract_inline(
+    line: &str,
+    attributes: &HashMap<String, String>,
+    span_provider: &impl SpanProvider,
+    uris: &mut Vec<RawUri>,
+) {
+    let mut markup: Vec<Range<usize>> = Vec::new();
+    for captures in INLINE.captures_iter(line) {
+        let whole = captures.get(0).expect("match has a whole capture");
+        markup.push(whole.range());
+        if let Some((text, start)) = inline_link(&captures, attributes) {
+            uris.push(raw_uri(text, span_provider.span(start)));
+        }
+    }
+
+    uris.extend(
+        url::find_links(line)
+            .filter(|link| !markup.iter().any(|range| range.contains(&link.start())))
+            .map(|link| {
+                // The attributes of URL macros aren't part of the URL
+                let text = link.as_str().split('[').next().unwrap_or_default();
+                raw_uri(text.to_string(), span_provider.span(link.start()))
+            }),
+    );
+}
+
+/// The link of inline markup and where it starts
+fn inline_link(
+    captures: &Captures<'_>,
+    attributes: &HashMap<String, String>,
+) -> Option<(String, usize)> {
+    if let Some(target) = captures.name("target") {
+        let text = match &captures["macro"] {
+            "xref" => xref(&substitute(target.as_str(), attributes)?)?,
+            _ => local_or_url(target.as_str(), attributes)?,
+        };
+        Some((text, target.start()))
+    } else if let Some(anchor) = captures.name("anchor") {
+        Some((xref(anchor.as_str())?, anchor.start()))
+    } else if let Some(attribute) = captures.name("attribute") {
+        let value = attributes.get(attribute.as_str())?;
+        // Like for bare URLs, trailing punctuation isn't part of the link
+        let rest = captures["rest"].trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+        value
+            .contains("://")
+            .then(|| (format!("{value}{rest}"), attribute.start() - 1))
+    } else {
+        None
+    }
+}
+
+/// Resolve the target of a cross reference, which is either an id within the
+/// document or a path to another document with an optional id
+fn xref(target: &str) -> Option<String> {
+    if is_resource_id(target) {
+        return None;
+    }
+    let (path, id) = match target.split_once('#') {
+        Some((path, id)) => (path, id),
+        // Targets without an extension are ids
+        None if Path::new(target).extension().is_none() => ("", target),
+        None => (target, ""),
+    };
+    let mut resolved = path.to_string();
+    if !path.is_empty() && Path::new(path).extension().is_none() {
+        resolved.push_str(".adoc");
+    }
+    if !id.is_empty() {
+        resolved.push('#');
+        resolved.push_str(id);
+    }
+    (!resolved.is_empty()).then_some(resolved)
+}
+
+/// Substitute attribute references in the target of a macro,
+/// unless it is an Antora resource ID
+fn local_or_url(target: &str, attributes: &HashMap<String, String>) -> Option<String> {
+    let target = substitute(target, attributes)?;
+    (!is_resource_id(&target)).then_some(target)
+}
+
+/// Antora resource IDs specify a component, version, module or family
+/// (e.g. `partial$intro.adoc`), so they can't be resolved to a path
+fn is_resource_id(target: &str) -> bool {
+    !target.contains("://") && !target.starts_with("mailto:") && target.contains([':', '$', '@'])
+}
+
+/// Substitute all attribute references in a target.
+/// Returns `None` if an attribute isn't defined.
+fn substitute(target: &str, attributes: &HashMap<String, String>) -> Option<String> {
+    let mut substituted = String::with_capacity(target.len());
+    let mut last = 0;
+    for captures in ATTRIBUTE_REFERENCE.captures_iter(target) {
+        let reference = captures.get(0).expect("match has a whole capture");
+        substituted.push_str(&target[last..reference.start()]);
+        substituted.push_str(attributes.get(&captures["name"])?);
+        last = reference.end();
+    }
+    substituted.push_str(&target[last..]);
+    Some(substituted)
+}
+
+const fn raw_uri(text: String, span: RawUriSpan) -> RawUri {
+    RawUri {
+        text,
+        element: None,
+        attribute: None,
+        span,
+    }
+}
+
+/// Extract the ids of sections and anchors, which are used as fragments.
+///
+/// Section ids are generated like Asciidoctor does, honoring the `idprefix`
+/// and `idseparator` attributes: `== Getting Started` has the id
+/// `_getting_started` by default. Sections with an explicit id don't have a
+/// generated one.
+pub(crate) fn extract_asciidoc_fragments(input: &str) -> HashSet<String> {
+    let mut fragments = HashSet::new();
+    let mut prefix = "_".to_string();
+    let mut separator = "_".to_string();
+    let mut explicit_id = false;
+
+    for (_, line) in content_lines(input, false) {
+        let line = line.trim_end();
+        if let Some(captures) = ATTRIBUTE_ENTRY.captures(line) {
+            let value = captures.name("value").map_or("", |value| value.as_str());
+            match &captures["name"] {
+                "idprefix" => value.clone_into(&mut prefix),
+                "idseparator" => value.clone_into(&mut separator),
+                _ => {}
+            }
+            continue;
+        }
+
+        let mut has_id = false;
+        for captures in EXPLICIT_ID.captures_iter(line) {
+            if let Some(id) = ["anchor", "macro", "block"]
+                .into_iter()
+                .find_map(|name| captures.name(name))
+            {
+                fragments.insert(id.as_str().to_string());
+                has_id = true;
+            }
+        }
+
+        if let Some(captures) = SECTION_TITLE.captures(line) {
+            if !explicit_id && !has_id {
+                let id = section_id(&captures["title"], &prefix, &separator);
+                let mut unique = id.clone();
+                let mut count = 2;
+                while fragments.contains(&unique) {
+                    unique = format!("{id}{separator}{count}");
+                    count += 1;
+                }
+                fragments.insert(unique);
+            }
+            explicit_id = false;
+        } else {
+            // Block ids and attribute lists apply to the next block
+            explicit_id = (explicit_id || has_id) && line.starts_with('[');
+        }
+    }
+    fragments
+}
+
+/// Generate the id of a section like Asciidoctor
+fn section_id(title: &str, prefix: &str, separator: &str) -> String {
+    let title = title.to_lowercase();
+    let title = INVALID_ID_CHARS.replace_all(&title, "");
+    let mut id = prefix.to_string();
+    let mut in_separator = false;
+    for c in title.chars() {
+        if matches!(c, ' ' | '.' | '-') && !separator.is_empty() {
+            if !in_separator {
+                id.push_str(separator);
+            }
+            in_separator = true;
+        } else {
+            id.push(c);
+            in_separator = false;
+        }
+    }
+    if !separator.is_empty() && id.ends_with(separator) && id.len() > prefix.len() {
+        id.truncate(id.len() - separator.len());
+    }
+    id
+}
+
+/// The lines of a document which aren't comments, with their byte offsets.
+/// Lines of literal, listing and passthrough blocks and of literal paragraphs
+/// are only included with `include_verbatim`.
+fn content_lines(input: &str, include_verbatim: bool) -> Vec<(usize, &str)> {
+    let mut lines = Vec::new();
+    // The delimiter of the current verbatim or comment block
+    let mut delimiter: Option<&str> = None;
+    let mut paragraph_start = true;
+    let mut literal_paragraph = false;
+
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let line_offset = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim_end();
+
+        if let Some(open) = delimiter {
+            if trimmed == open {
+                delimiter = None;
+            } else if include_verbatim && !open.starts_with('/') {
+                lines.push((line_offset, line));
+            }
+            continue;
+        }
+        if trimmed.is_empty() {
+            paragraph_start = true;
+            continue;
+        }
+        if is_verbatim_delimiter(trimmed) {
+            delimiter = Some(if trimmed.starts_with("```") {
+                "```"
+            } else {
+                trimmed
+            });
+            paragraph_start = true;
+            continue;
+        }
+        if trimmed.starts_with("//") {
+            continue;
+        }
+
+        if paragraph_start {
+            let unindented = line.trim_start();
+            literal_paragraph = unindented.len() < line.len() && !LIST_ITEM.is_match(unindented);
+            paragraph_start = false;
+        }
+        if !literal_paragraph || include_verbatim {
+            lines.push((line_offset, line));
+        }
+    }
+    lines
+}
+
+/// Delimiters of comment, listing, literal and passthrough blocks,
+/// e.g. `----`
+fn is_verbatim_delimiter(line: &str) -> bool {
+    if line.starts_with("```") {
+        return true;
+    }
+    let mut chars = line.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    matches!(first, '/' | '-' | '.' | '+') && line.len() >= 4 && chars.all(|c| c == first)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn extract(input: &str) -> Vec<(String, usize, usize)> {
+        extract_asciidoc(input, false)
+            .into_iter()
+            .map(|uri| {
+                (
+                    uri.text,
+                    uri.span.line.get(),
+                    uri.span.column.unwrap().get(),
+                )
+            })
+            .collect()
+    }
+
+    fn texts(input: &str, include_verbatim: bool) -> Vec<String> {
+        extract_asciidoc(input, include_verbatim)
+            .into_iter()
+            .map(|uri| uri.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_links() {
+        let input = "\
+= Document
+
+See link:guide.html[the guide], https://example.com/macro[with text^]
+and https://example.com/bare.
+Also link:https://example.com/link[].
+";
+        assert_eq!(
+            extract(input),
+            [
+                ("guide.html".into(), 3, 10),
+                ("https://example.com/macro".into(), 3, 33),
+                ("https://example.com/bare".into(), 4, 5),
+                ("https://example.com/link".into(), 5, 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cross_references() {
+        let input = "\
+Read <<install>>, <<usage,the usage>> and <<other.adoc#setup,setup>>.
+See xref:guide.adoc[], xref:reference#options[options] and xref:#faq[FAQ].
+Antora xref:module:page.adoc[] and xref:2.0@component::index.adoc[] aren't checked.
+";
+        assert_eq!(
+            texts(input, false),
+            [
+                "#install",
+                "#usage",
+                "other.adoc#setup",
+                "guide.adoc",
+                "reference.adoc#options",
+                "#faq",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_images_and_includes() {
+        let input = "\
+image::images/logo.png[Logo]
+Inline image:icon.svg[Icon,16] here.
+include::partials/intro.adoc[]
+include::partial$intro.adoc[]
+";
+        assert_eq!(
+            extract(input),
+            [
+                ("images/logo.png".into(), 1, 8),
+                ("icon.svg".into(), 2, 14),
+                ("partials/intro.adoc".into(), 3, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_attributes() {
+        let input = "\
+:url-repo: https://github.com/lycheeverse/lychee
+:docs-dir: docs
+
+See the {url-repo}[repository] and {url-repo}/issues.
+include::{docs-dir}/usage.adoc[]
+image::{undefined}/logo.png[]
+";
+        assert_eq!(
+            extract(input),
+            [
+                ("https://github.com/lycheeverse/lychee".into(), 4, 9),
+                ("https://github.com/lycheeverse/lychee/issues".into(), 4, 36),
+                ("docs/usage.adoc".into(), 5, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verbatim() {
+        let input = "\
+// A comment with https://example.com/comment
+////
+https://example.com/comment-block
+////
+
+----
+curl https://example.com/listing
+----
+
+ https://example.com/literal-paragraph
+
+* https://example.com/list-item
+
+[source,bash]
+```
+curl https://example.com/fenced
+```
+Use pass:[https://example.com/passthrough] and https://example.com/text.
+";
+        assert_eq!(
+            texts(input, false),
+            ["https://example.com/list-item", "https://example.com/text"]
+        );
+        assert_eq!(
+            texts(input, true),
+            [
+                "https://example.com/listing",
+                "https://example.com/literal-paragraph",
+                "https://example.com/list-item",
+                "https://example.com/fenced",
+                "https://example.com/text",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fragments() {
+        let input = "\
+= Document Title
+
+== Getting Started
+
+=== Install lychee (v1.0)
+
+[[custom-id]]
+== Explicit id
+
+[#block-id.role]
+== Another explicit id
+
+== Getting Started
+
+Some text with an anchor:inline-anchor[] and [[another-anchor,Text]].
+
+----
+== Not a section
+----
+";
+        let expected: HashSet<String> = [
+            "_getting_started",
+            "_install_lychee_v1_0",
+            "custom-id",
+            "block-id",
+            "_getting_started_2",
+            "inline-anchor",
+            "another-anchor",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        assert_eq!(extract_asciidoc_fragments(input), expected);
+    }
+
+    #[test]
+    fn test_fragments_id_attributes() {
+        let input = "\
+:idprefix:
+:idseparator: -
+
+== Getting Started
+";
+        assert_eq!(
+            extract_asciidoc_fragments(input),
+            HashSet::from(["getting-started".to_string()])
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Extraction of links from custom document formats.
+//!
+//! lychee natively extracts links from the formats listed in
+//! [`FileType`](crate::FileType).
+//! Library users can register a [`CustomExtractor`] on the
+//! [`Collector`](crate::Collector) to check links in other formats as well.
+use std::{collections::HashMap, fmt::Debug, path::Path, sync::Arc};
+
+use crate::types::{InputContent, ResolvedInputSource, uri::raw::RawUri};
+
+/// Extracts links from documents in a custom format.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::{collections::HashSet, num::NonZeroUsize, sync::Arc};
+///
+/// use futures::StreamExt;
+/// use lychee_lib::{
+///     BaseInfo, Collector, Input, RawUri, RawUriSpan, Result,
+///     extract::{CustomExtractor, CustomFormat},
+/// };
+///
+/// /// Links are written as `link: <url>` on their own line
+/// #[derive(Debug)]
+/// struct MyFormatExtractor;
+///
+/// impl CustomExtractor for MyFormatExtractor {
+///     fn extract(&self, content: &str) -> Vec<RawUri> {
+///         content
+///             .lines()
+///             .zip(1..)
+///             .filter_map(|(line, number)| {
+///                 Some(RawUri {
+///                     text: line.strip_prefix("link: ")?.to_string(),
+///                     element: None,
+///                     attribute: None,
+///                     span: RawUriSpan::new(NonZeroUsize::new(number)?, NonZeroUsize::new(7)),
+///                 })
+///             })
+///             .collect()
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let collector = Collector::new(None, BaseInfo::none())?.custom_extractor(
+///         CustomFormat::Extension("myformat".to_string()),
+///         Arc::new(MyFormatExtractor),
+///     );
+///
+///     // Directories are searched for `.myformat` files, too
+///     let inputs = HashSet::from([Input::from_value("docs/")?]);
+///     let requests: Vec<_> = collector.collect_links(inputs).collect().await;
+///     Ok(())
+/// }
+/// ```
+pub trait CustomExtractor: Debug + Send + Sync {
+    /// Extract all links from the given document.
+    ///
+    /// The spans of the returned links should refer to their position in
+    /// `content`, so that they can be reported to the user.
+    fn extract(&self, content: &str) -> Vec<RawUri>;
+}
+
+/// The documents handled by a [`CustomExtractor`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CustomFormat {
+    /// Files with the given extension (without the leading dot), e.g. `adoc`.
+    ///
+    /// Extensions are matched case-insensitively. Files with this extension
+    /// are also picked up when searching directories for inputs.
+    Extension(String),
+    /// Remote documents served with the given MIME type, e.g. `text/x-rst`.
+    ///
+    /// The MIME type takes precedence over the extension of the URL.
+    MimeType(String),
+}
+
+impl CustomFormat {
+    fn normalized(self) -> Self {
+        match self {
+            Self::Extension(extension) => {
+                Self::Extension(extension.trim_start_matches('.').to_lowercase())
+            }
+            Self::MimeType(mime_type) => Self::MimeType(essence(&mime_type)),
+        }
+    }
+}
+
+/// [`CustomExtractor`]s by the [`CustomFormat`] they handle
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CustomExtractors(HashMap<CustomFormat, Arc<dyn CustomExtractor>>);
+
+impl CustomExtractors {
+    /// Register an extractor for the given format, replacing any extractor
+    /// previously registered for it.
+    ///
+    /// Custom extractors take precedence over the built-in extractors,
+    /// e.g. an extractor for the `md` extension replaces the Markdown
+    /// extractor.
+    pub(crate) fn insert(&mut self, format: CustomFormat, extractor: Arc<dyn CustomExtractor>) {
+        self.0.insert(format.normalized(), extractor);
+    }
+
+    /// Returns `true` if no extractors are registered
+    #[must_use]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// All file extensions which have a registered extractor
+    pub(crate) fn extensions(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().filter_map(|format| match format {
+            CustomFormat::Extension(extension) => Some(extension.as_str()),
+            CustomFormat::MimeType(_) => None,
+        })
+    }
+
+    /// Find the extractor for the given content, if any
+    pub(crate) fn get(&self, content: &InputContent) -> Option<&dyn CustomExtractor> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let by_mime_type = || {
+            let mime_type = essence(content.content_type.as_deref()?);
+            self.0.get(&CustomFormat::MimeType(mime_type))
+        };
+        let by_extension = || {
+            let extension = extension(&content.source)?.to_lowercase();
+            self.0.get(&CustomFormat::Extension(extension))
+        };
+
+        by_mime_type().or_else(by_extension).map(AsRef::as_ref)
+    }
+}
+
+/// The MIME type without parameters, e.g. `text/html` for
+/// `text/html; charset=utf-8`
+fn essence(mime_type: &str) -> String {
+    mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase()
+}
+
+fn extension(source: &ResolvedInputSource) -> Option<&str> {
+    let path = match source {
+        ResolvedInputSource::FsPath(path) => path.as_path(),
+        ResolvedInputSource::RemoteUrl(url) => Path::new(url.path()),
+        ResolvedInputSource::Stdin | ResolvedInputSource::String(_) => return None,
+    };
+    path.extension()?.to_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use url::Url;
+
+    use super::*;
+    use crate::types::{FileType, uri::raw::span};
+
+    #[derive(Debug)]
+    struct Dummy(&'static str);
+
+    impl CustomExtractor for Dummy {
+        fn extract(&self, _content: &str) -> Vec<RawUri> {
+            vec![RawUri::from((self.0, span(1, 1)))]
+        }
+    }
+
+    fn content(source: ResolvedInputSource, content_type: Option<&str>) -> InputContent {
+        InputContent {
+            source,
+            file_type: FileType::Plaintext,
+            content_type: content_type.map(String::from),
+            content: String::new(),
+        }
+    }
+
+    fn extracted(extractors: &CustomExtractors, content: &InputContent) -> Option<String> {
+        extractors
+            .get(content)
+            .map(|extractor| extractor.extract("").remove(0).text)
+    }
+
+    #[test]
+    fn test_custom_extractor_lookup() {
+        let mut extractors = CustomExtractors::default();
+        extractors.insert(
+            CustomFormat::Extension(".Foo".to_string()),
+            Arc::new(Dummy("by-extension")),
+        );
+        extractors.insert(
+            CustomFormat::MimeType("Text/X-Foo".to_string()),
+            Arc::new(Dummy("by-mime-type")),
+        );
+
+        let file = content(ResolvedInputSource::FsPath(PathBuf::from("doc.FOO")), None);
+        assert_eq!(
+            extracted(&extractors, &file).as_deref(),
+            Some("by-extension")
+        );
+
+        let url = Url::parse("https://example.com/doc.foo").unwrap();
+        let remote = content(ResolvedInputSource::RemoteUrl(Box::new(url.clone())), None);
+        assert_eq!(
+            extracted(&extractors, &remote).as_deref(),
+            Some("by-extension")
+        );
+
+        let remote = content(
+            ResolvedInputSource::RemoteUrl(Box::new(url)),
+            Some("text/x-foo; charset=utf-8"),
+        );
+        assert_eq!(
+            extracted(&extractors, &remote).as_deref(),
+            Some("by-mime-type")
+        );
+
+        let other = content(ResolvedInputSource::FsPath(PathBuf::from("doc.md")), None);
+        assert_eq!(extracted(&extractors, &other), None);
+
+        assert_eq!(extractors.extensions().collect::<Vec<_>>(), ["foo"]);
+    }
+}
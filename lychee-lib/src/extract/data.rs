@@ -0,0 +1,937 @@
+//! Extract links from structured data in JSON, YAML and TOML files.
+//!
+//! The extractors walk all string values and extract the URLs in them.
+//! The key path of a value, i.e. its keys joined with dots like
+//! `info.contact.url`, is stored as the attribute of its links.
+//! Array indices aren't part of key paths, so the URLs of an `OpenAPI` spec
+//! like `servers[0].url` have the key path `servers.url`.
+//!
+//! With [`KeyPaths`], only the links of certain values are extracted.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use log::warn;
+use toml::de::{DeTable, DeValue};
+
+use super::plaintext::extract_raw_uri_from_plaintext;
+use crate::{
+    types::uri::raw::{RawUri, SourceSpanProvider, SpanProvider},
+    utils::url,
+};
+
+/// Patterns for the key paths of values in JSON, YAML and TOML files whose
+/// links are checked.
+///
+/// A pattern is a list of keys separated by dots, e.g. `info.contact.url`,
+/// where `*` matches any key and `**` any number of keys. A pattern matches
+/// a value if it matches its key path or the key path of a parent value.
+/// So `repository` matches `repository.url`, too.
+///
+/// Without any patterns, the links of all values are checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyPaths(Vec<Vec<String>>);
+
+impl KeyPaths {
+    /// Create the key paths from the given patterns
+    #[must_use]
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self(
+            patterns
+                .into_iter()
+                .map(|pattern| pattern.as_ref().split('.').map(String::from).collect())
+                .collect(),
+        )
+    }
+
+    /// Returns `true` if the links of the value at the given key path are checked
+    #[must_use]
+    pub fn matches<S: AsRef<str>>(&self, path: &[S]) -> bool {
+        self.0.is_empty() || self.0.iter().any(|pattern| matches_prefix(pattern, path))
+    }
+}
+
+/// Returns `true` if the pattern matches the path or one of its prefixes
+fn matches_prefix<S: AsRef<str>>(pattern: &[String], path: &[S]) -> bool {
+    match pattern.split_first() {
+        None => true,
+        Some((key, rest)) if key == "**" => {
+            (0..=path.len()).any(|i| matches_prefix(rest, &path[i..]))
+        }
+        Some((key, rest)) => path.split_first().is_some_and(|(first, path)| {
+            (key == "*" || key == first.as_ref()) && matches_prefix(rest, path)
+        }),
+    }
+}
+
+/// Collects the links of the string values of a document
+struct Links<'a> {
+    key_paths: &'a KeyPaths,
+    span_provider: SourceSpanProvider<'a>,
+    uris: Vec<RawUri>,
+    /// The offsets and texts of the links, as YAML aliases add the links of
+    /// their anchored value again
+    seen: HashSet<(usize, String)>,
+}
+
+impl<'a> Links<'a> {
+    fn new(input: &'a str, key_paths: &'a KeyPaths) -> Self {
+        Self {
+            key_paths,
+            span_provider: SourceSpanProvider::from_input(input),
+            uris: Vec::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Add the links in a string value which starts at the given offset.
+    ///
+    /// If the value contains escape sequences, offsets within the value
+    /// don't match the document, so all links get the span of the value.
+    fn add<S: AsRef<str>>(&mut self, path: &[S], value: &str, start: usize, escaped: bool) {
+        if !self.key_paths.matches(path) {
+            return;
+        }
+        let key_path = path.iter().map(AsRef::as_ref).collect::<Vec<_>>().join(".");
+        for link in url::find_links(value) {
+            let offset = if escaped { start } else { start + link.start() };
+            if !self.seen.insert((offset, link.as_str().to_owned())) {
+                continue;
+            }
+            self.uris.push(RawUri {
+                text: link.as_str().to_owned(),
+                element: None,
+                attribute: Some(key_path.clone()),
+                span: self.span_provider.span(offset),
+            });
+        }
+    }
+
+    fn into_uris(mut self) -> Vec<RawUri> {
+        self.uris
+            .sort_by_key(|uri| (uri.span.line, uri.span.column));
+        self.uris
+    }
+}
+
+/// Extract unparsed URL strings from the string values of a JSON document.
+///
+/// Invalid JSON is parsed up to the first error.
+pub(crate) fn extract_json(input: &str, key_paths: &KeyPaths) -> Vec<RawUri> {
+    let mut parser = JsonParser {
+        input,
+        pos: 0,
+        links: Links::new(input, key_paths),
+    };
+    let mut path = Vec::new();
+    if parser.value(&mut path, 0).is_none() {
+        warn!("Cannot parse JSON completely, some links may be missing");
+    }
+    parser.links.into_uris()
+}
+
+/// Maximum nesting depth of JSON documents
+const MAX_DEPTH: usize = 128;
+
+/// A minimal JSON parser which keeps track of the positions of strings
+struct JsonParser<'a> {
+    input: &'a str,
+    pos: usize,
+    links: Links<'a>,
+}
+
+impl JsonParser<'_> {
+    /// Parse a value and add the links of all strings in it.
+    /// Returns `None` on invalid JSON.
+    fn value(&mut self, path: &mut Vec<String>, depth: usize) -> Option<()> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.whitespace();
+        match self.peek()? {
+            b'{' => {
+                self.pos += 1;
+                self.whitespace();
+                if self.eat(b'}') {
+                    return Some(());
+                }
+                loop {
+                    self.whitespace();
+                    let (key, _, _) = self.string()?;
+                    self.whitespace();
+                    self.eat(b':').then_some(())?;
+                    path.push(key);
+                    self.value(path, depth + 1)?;
+                    path.pop();
+                    self.whitespace();
+                    if self.eat(b'}') {
+                        return Some(());
+                    }
+                    self.eat(b',').then_some(())?;
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+                self.whitespace();
+                if self.eat(b']') {
+                    return Some(());
+                }
+                loop {
+                    self.value(path, depth + 1)?;
+                    self.whitespace();
+                    if self.eat(b']') {
+                        return Some(());
+                    }
+                    self.eat(b',').then_some(())?;
+                }
+            }
+            b'"' => {
+                let (value, start, escaped) = self.string()?;
+                self.links.add(path, &value, start, escaped);
+                Some(())
+            }
+            _ => {
+                // numbers, booleans and null
+                let rest = &self.input.as_bytes()[self.pos..];
+                let len = rest
+                    .iter()
+                    .position(|c| matches!(c, b',' | b'}' | b']') || c.is_ascii_whitespace())
+                    .unwrap_or(rest.len());
+                (len > 0).then_some(())?;
+                self.pos += len;
+                Some(())
+            }
+        }
+    }
+
+    /// Parse a string and return its value, the offset where its content
+    /// starts and whether it contains escape sequences
+    fn string(&mut self) -> Option<(String, usize, bool)> {
+        self.eat(b'"').then_some(())?;
+        let start = self.pos;
+        let mut value = String::new();
+        let mut escaped = false;
+        let mut chars = self.input[start..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos = start + i + 1;
+                    return Some((value, start, escaped));
+                }
+                '\\' => {
+                    escaped = true;
+                    match chars.next()?.1 {
+                        'n' => value.push('\n'),
+                        't' => value.push('\t'),
+                        'r' => value.push('\r'),
+                        'b' => value.push('\u{8}'),
+                        'f' => value.push('\u{c}'),
+                        'u' => {
+                            let hex: String = (0..4)
+                                .filter_map(|_| chars.next())
+                                .map(|(_, c)| c)
+                                .collect();
+                            let code = u32::from_str_radix(&hex, 16).ok()?;
+                            value.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                        }
+                        c => value.push(c),
+                    }
+                }
+                c => value.push(c),
+            }
+        }
+        None
+    }
+
+    fn whitespace(&mut self) {
+        let rest = &self.input.as_bytes()[self.pos..];
+        self.pos += rest
+            .iter()
+            .position(|c| !c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+}
+
+/// Extract unparsed URL strings from the string values of a TOML document.
+///
+/// If the document is invalid, the links are extracted as plain text instead.
+pub(crate) fn extract_toml(input: &str, key_paths: &KeyPaths) -> Vec<RawUri> {
+    let table = match DeTable::parse(input) {
+        Ok(table) => table,
+        Err(e) => {
+            warn!("Cannot parse TOML, extracting links as plain text: {e}");
+            return extract_raw_uri_from_plaintext(input, &SourceSpanProvider::from_input(input));
+        }
+    };
+
+    let mut links = Links::new(input, key_paths);
+    let mut path = Vec::new();
+    add_toml_table(&mut links, input, &mut path, table.get_ref());
+    links.into_uris()
+}
+
+fn add_toml_table<'i>(
+    links: &mut Links<'_>,
+    input: &str,
+    path: &mut Vec<&'i str>,
+    table: &'i DeTable<'i>,
+) {
+    for (key, value) in table {
+        path.push(key.get_ref().as_ref());
+        add_toml_value(links, input, path, value.get_ref(), value.span());
+        path.pop();
+    }
+}
+
+fn add_toml_value<'i>(
+    links: &mut Links<'_>,
+    input: &str,
+    path: &mut Vec<&'i str>,
+    value: &'i DeValue<'i>,
+    span: Range<usize>,
+) {
+    match value {
+        DeValue::String(value) => {
+            let raw = input.get(span.clone()).unwrap_or_default();
+            let quotes = if raw.starts_with("\"\"\"") || raw.starts_with("'''") {
+                3
+            } else {
+                1
+            };
+            let content = raw
+                .get(quotes..raw.len().saturating_sub(quotes))
+                .unwrap_or_default();
+            // Multi-line strings may start with a newline which isn't part of the value
+            let start = span.start
+                + quotes
+                + (content.len() - content.trim_start_matches(['\r', '\n']).len());
+            let escaped = !content.ends_with(value.as_ref());
+            links.add(path, value, start, escaped);
+        }
+        DeValue::Array(array) => {
+            for item in array {
+                add_toml_value(links, input, path, item.get_ref(), item.span());
+            }
+        }
+        DeValue::Table(table) => add_toml_table(links, input, path, table),
+        _ => {}
+    }
+}
+
+/// Extract unparsed URL strings from the scalar values of a YAML document.
+///
+/// This is a line-based extractor which supports block mappings and
+/// sequences, block scalars and flow collections like `{url: https://example.com}`.
+/// The links of anchored values like `&docs` are also extracted with the key
+/// paths of their aliases like `*docs`, which may match other [`KeyPaths`].
+/// Files may contain multiple documents separated by `---`.
+pub(crate) fn extract_yaml(input: &str, key_paths: &KeyPaths) -> Vec<RawUri> {
+    let mut parser = YamlParser {
+        input,
+        pos: 0,
+        links: Links::new(input, key_paths),
+        anchors: HashMap::new(),
+        recording: Vec::new(),
+    };
+    // The keys of the current value with their indentation
+    let mut keys: Vec<YamlKey> = Vec::new();
+    // The indentation of the key of the current block scalar
+    let mut block_scalar: Option<usize> = None;
+    // The end of the last flow collection, which may span multiple lines
+    let mut flow_end = 0;
+
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let line_offset = offset;
+        offset += line.len();
+        if line_offset < flow_end {
+            continue;
+        }
+        let line = line.trim_end_matches(['\n', '\r']);
+        let content = line.trim_start();
+        let mut indent = line.len() - content.len();
+
+        if let Some(block_indent) = block_scalar {
+            if content.is_empty() || indent > block_indent {
+                parser.scalar(&key_path(&keys), content, line_offset + indent, false);
+                continue;
+            }
+            block_scalar = None;
+        }
+        // Skip comments and directives like `%YAML 1.2`
+        if content.is_empty() || content.starts_with('#') || line.starts_with('%') {
+            continue;
+        }
+        if line == "---" || line == "..." || line.starts_with("--- ") {
+            // Anchors are local to their document
+            keys.clear();
+            parser.anchors.clear();
+            parser.recording.clear();
+            continue;
+        }
+
+        // Sequence entries are indented like mappings
+        let mut rest = content;
+        while let Some(item) = rest
+            .strip_prefix('-')
+            .filter(|item| item.is_empty() || item.starts_with([' ', '\t']))
+        {
+            let item = item.trim_start();
+            indent += rest.len() - item.len();
+            rest = item;
+        }
+        while let Some(key) = keys.pop_if(|key| key.indent >= indent) {
+            if key.anchored {
+                parser.recording.pop();
+            }
+        }
+
+        let mut value_start = indent;
+        let mut has_key = false;
+        if let Some((key, value)) = split_key(rest) {
+            keys.push(YamlKey {
+                indent,
+                name: key.to_string(),
+                anchored: false,
+            });
+            has_key = true;
+            value_start += value;
+            rest = &rest[value..];
+        }
+        let (anchor, properties_len) = node_properties(rest);
+        value_start += properties_len;
+        let value = strip_comment(&rest[properties_len..]);
+
+        let mut path = key_path(&keys);
+        if let Some(anchor) = anchor {
+            parser.start_anchor(anchor, path.len());
+        }
+        match value.as_bytes().first() {
+            Some(b'|' | b'>') => block_scalar = Some(keys.last().map_or(0, |key| key.indent)),
+            Some(b'{' | b'[') => flow_end = parser.flow(&mut path, line_offset + value_start),
+            Some(b'*') => {
+                // Merge keys like `<<: *defaults` add the keys of the alias
+                if path.last().is_some_and(|key| key == "<<") {
+                    path.pop();
+                }
+                parser.alias(&path, &value[1..]);
+            }
+            Some(_) => parser.scalar(&path, value, line_offset + value_start, false),
+            None => {}
+        }
+        if anchor.is_some() {
+            // The anchored value of a key may continue on the following lines
+            match keys.last_mut() {
+                Some(key) if has_key && (value.is_empty() || block_scalar.is_some()) => {
+                    key.anchored = true;
+                }
+                _ => {
+                    parser.recording.pop();
+                }
+            }
+        }
+    }
+    parser.links.into_uris()
+}
+
+/// A key of a block mapping in a YAML document
+struct YamlKey {
+    indent: usize,
+    name: String,
+    /// Whether the value of the key has an anchor
+    anchored: bool,
+}
+
+fn key_path(keys: &[YamlKey]) -> Vec<String> {
+    keys.iter().map(|key| key.name.clone()).collect()
+}
+
+/// Split the anchor and tag of a YAML node like `&docs !!str` from its value.
+/// Returns the anchor and the offset of the value.
+fn node_properties(node: &str) -> (Option<&str>, usize) {
+    let mut anchor = None;
+    let mut rest = node;
+    while rest.starts_with(['&', '!']) {
+        let end = rest.find([' ', '\t']).unwrap_or(rest.len());
+        if let Some(name) = rest[..end].strip_prefix('&') {
+            anchor = Some(name);
+        }
+        rest = rest[end..].trim_start();
+    }
+    (anchor, node.len() - rest.len())
+}
+
+/// A scalar of an anchored YAML value
+#[derive(Clone)]
+struct YamlScalar {
+    /// The key path of the scalar relative to the anchored value
+    path: Vec<String>,
+    value: String,
+    start: usize,
+    escaped: bool,
+}
+
+/// Adds the links of the scalars of a YAML document, resolves aliases and
+/// parses flow collections
+struct YamlParser<'a> {
+    input: &'a str,
+    /// The position in the current flow collection
+    pos: usize,
+    links: Links<'a>,
+    /// The scalars of the anchored values by anchor
+    anchors: HashMap<String, Vec<YamlScalar>>,
+    /// The anchors of the values which are being parsed, with the length of
+    /// the key path of the anchored value
+    recording: Vec<(String, usize)>,
+}
+
+impl YamlParser<'_> {
+    /// Add the links of a scalar and remember it for the anchored values
+    /// which contain it
+    fn scalar(&mut self, path: &[String], value: &str, start: usize, escaped: bool) {
+        self.links.add(path, value, start, escaped);
+        for (anchor, depth) in &self.recording {
+            if let Some(scalars) = self.anchors.get_mut(anchor) {
+                scalars.push(YamlScalar {
+                    path: path.get(*depth..).unwrap_or_default().to_vec(),
+                    value: value.to_string(),
+                    start,
+                    escaped,
+                });
+            }
+        }
+    }
+
+    fn start_anchor(&mut self, anchor: &str, depth: usize) {
+        self.anchors.insert(anchor.to_string(), Vec::new());
+        self.recording.push((anchor.to_string(), depth));
+    }
+
+    /// Add the links of an anchored value again with the key path of its alias
+    fn alias(&mut self, path: &[String], anchor: &str) {
+        let Some(scalars) = self.anchors.get(anchor.trim_end()).cloned() else {
+            return;
+        };
+        for scalar in scalars {
+            let path: Vec<_> = path.iter().chain(&scalar.path).cloned().collect();
+            self.scalar(&path, &scalar.value, scalar.start, scalar.escaped);
+        }
+    }
+
+    /// Parse the flow collection at the given offset, e.g. `[a, b]` or
+    /// `{url: https://example.com}`, and return the offset after it
+    fn flow(&mut self, path: &mut Vec<String>, start: usize) -> usize {
+        self.pos = start;
+        if self.flow_node(path, 0).is_none() {
+            warn!("Cannot parse YAML flow collection completely, some links may be missing");
+        }
+        self.pos
+    }
+
+    /// Parse a node of a flow collection and add the links of its scalars.
+    /// Returns `None` on invalid flow collections.
+    fn flow_node(&mut self, path: &mut Vec<String>, depth: usize) -> Option<()> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let mut anchor = None;
+        loop {
+            self.flow_whitespace();
+            match self.peek() {
+                Some(b'&') => {
+                    self.pos += 1;
+                    anchor = Some(self.flow_token().to_string());
+                }
+                Some(b'!') => {
+                    self.flow_token();
+                }
+                _ => break,
+            }
+        }
+        if let Some(anchor) = &anchor {
+            self.start_anchor(anchor, path.len());
+        }
+
+        let result = match self.peek()? {
+            b'{' => self.flow_collection(path, depth, b'}'),
+            b'[' => self.flow_collection(path, depth, b']'),
+            b'*' => {
+                self.pos += 1;
+                let anchor = self.flow_token().to_string();
+                self.alias(path, &anchor);
+                Some(())
+            }
+            _ => {
+                let (value, start, escaped) = self.flow_scalar()?;
+                self.scalar(path, &value, start, escaped);
+                Some(())
+            }
+        };
+        if anchor.is_some() {
+            self.recording.pop();
+        }
+        result
+    }
+
+    /// Parse a flow mapping or sequence, which ends with the given character
+    fn flow_collection(&mut self, path: &mut Vec<String>, depth: usize, end: u8) -> Option<()> {
+        self.pos += 1;
+        loop {
+            self.flow_whitespace();
+            if self.eat(end) {
+                return Some(());
+            }
+            if end == b'}' {
+                let (key, _, _) = self.flow_scalar()?;
+                self.flow_whitespace();
+                if self.eat(b':') {
+                    path.push(key);
+                    let value = self.flow_node(path, depth + 1);
+                    path.pop();
+                    value?;
+                }
+            } else {
+                self.flow_node(path, depth + 1)?;
+            }
+            self.flow_whitespace();
+            if self.eat(end) {
+                return Some(());
+            }
+            self.eat(b',').then_some(())?;
+        }
+    }
+
+    /// Parse a quoted or plain scalar and return its value, the offset where
+    /// its content starts and whether it contains escape sequences
+    fn flow_scalar(&mut self) -> Option<(String, usize, bool)> {
+        let quote = self.peek()?;
+        if !matches!(quote, b'"' | b'\'') {
+            let start = self.pos;
+            let bytes = self.input.as_bytes();
+            let mut end = start;
+            while let Some(&c) = bytes.get(end) {
+                let next = bytes.get(end + 1).copied();
+                let ends_value = match c {
+                    b',' | b'[' | b']' | b'{' | b'}' => true,
+                    b':' => next.is_none_or(|next| {
+                        next.is_ascii_whitespace() || matches!(next, b',' | b']' | b'}')
+                    }),
+                    b'#' => end > start && bytes[end - 1].is_ascii_whitespace(),
+                    _ => false,
+                };
+                if ends_value {
+                    break;
+                }
+                end += 1;
+            }
+            self.pos = end;
+            let value = self.input[start..end].trim_end();
+            return Some((value.to_string(), start, false));
+        }
+
+        self.pos += 1;
+        let start = self.pos;
+        let mut value = String::new();
+        let mut escaped = false;
+        let mut chars = self.input[start..].char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                // Quotes are escaped by doubling them in single-quoted scalars
+                '\'' if quote == b'\'' && chars.peek().is_some_and(|(_, c)| *c == '\'') => {
+                    chars.next();
+                    escaped = true;
+                    value.push('\'');
+                }
+                c if c == char::from(quote) => {
+                    self.pos = start + i + 1;
+                    return Some((value, start, escaped));
+                }
+                '\\' if quote == b'"' => {
+                    escaped = true;
+                    value.push(chars.next()?.1);
+                }
+                c => value.push(c),
+            }
+        }
+        None
+    }
+
+    /// Parse an anchor, alias or tag name
+    fn flow_token(&mut self) -> &str {
+        let start = self.pos;
+        let rest = &self.input.as_bytes()[start..];
+        self.pos += rest
+            .iter()
+            .position(|c| c.is_ascii_whitespace() || matches!(c, b',' | b'[' | b']' | b'{' | b'}'))
+            .unwrap_or(rest.len());
+        &self.input[start..self.pos]
+    }
+
+    /// Skip whitespace, line breaks and comments
+    fn flow_whitespace(&mut self) {
+        loop {
+            let rest = &self.input.as_bytes()[self.pos..];
+            self.pos += rest
+                .iter()
+                .position(|c| !c.is_ascii_whitespace())
+                .unwrap_or(rest.len());
+            if self.peek() != Some(b'#') {
+                return;
+            }
+            let rest = &self.input[self.pos..];
+            self.pos += rest.find('\n').unwrap_or(rest.len());
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+}
+
+/// Split a mapping entry into its key and the offset of its value
+fn split_key(entry: &str) -> Option<(&str, usize)> {
+    if entry.starts_with(['{', '[']) {
+        return None;
+    }
+    let (key, separator) =
+        if let Some(quote) = entry.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+            let end = entry[1..].find(quote)? + 1;
+            (&entry[1..end], end + 1)
+        } else {
+            let separator = entry
+                .find(": ")
+                .or_else(|| entry.ends_with(':').then(|| entry.len() - 1))?;
+            (entry[..separator].trim_end(), separator)
+        };
+    let value = entry[separator..].strip_prefix(':')?;
+    let value_start = entry.len() - value.trim_start().len();
+    Some((key, value_start))
+}
+
+/// Remove a trailing comment from a value
+fn strip_comment(value: &str) -> &str {
+    let end = value
+        .match_indices('#')
+        .find(|(i, _)| value[..*i].ends_with([' ', '\t']))
+        .map_or(value.len(), |(i, _)| i);
+    value[..end].trim_end()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn links(uris: Vec<RawUri>) -> Vec<(String, String, String)> {
+        uris.into_iter()
+            .map(|uri| (uri.text, uri.attribute.unwrap(), uri.span.to_string()))
+            .collect()
+    }
+
+    fn link(text: &str, key_path: &str, span: &str) -> (String, String, String) {
+        (text.into(), key_path.into(), span.into())
+    }
+
+    #[test]
+    fn test_key_paths() {
+        let key_paths = KeyPaths::new(["homepage", "*.url", "paths.**.externalDocs"]);
+        assert!(key_paths.matches(&["homepage"]));
+        assert!(key_paths.matches(&["info", "url"]));
+        assert!(key_paths.matches(&["paths", "/pets", "get", "externalDocs", "url"]));
+        assert!(key_paths.matches(&["paths", "externalDocs"]));
+        assert!(!key_paths.matches(&["url"]));
+        assert!(!key_paths.matches(&["info", "contact", "url"]));
+        assert!(!key_paths.matches(&["description"]));
+
+        assert!(KeyPaths::default().matches(&["anything"]));
+    }
+
+    #[test]
+    fn test_extract_json() {
+        let input = r#"{
+  "name": "lychee",
+  "homepage": "https://lychee.cli.rs",
+  "repository": {"type": "git", "url": "git+https://github.com/lycheeverse/lychee.git"},
+  "keywords": ["links", "See https://example.com/keywords"],
+  "escaped": "https:\/\/example.com\/escaped",
+  "count": 3, "private": true, "other": null
+}"#;
+        assert_eq!(
+            links(extract_json(input, &KeyPaths::default())),
+            [
+                link("https://lychee.cli.rs", "homepage", "3:16"),
+                link(
+                    "git+https://github.com/lycheeverse/lychee.git",
+                    "repository.url",
+                    "4:41"
+                ),
+                link("https://example.com/keywords", "keywords", "5:30"),
+                link("https://example.com/escaped", "escaped", "6:15"),
+            ]
+        );
+
+        let key_paths = KeyPaths::new(["homepage", "repository"]);
+        assert_eq!(extract_json(input, &key_paths).len(), 2);
+    }
+
+    #[test]
+    fn test_extract_invalid_json() {
+        let input = r#"{"homepage": "https://lychee.cli.rs", "broken": "#;
+        assert_eq!(
+            links(extract_json(input, &KeyPaths::default())),
+            [link("https://lychee.cli.rs", "homepage", "1:15")]
+        );
+    }
+
+    #[test]
+    fn test_extract_yaml() {
+        let input = "\
+openapi: 3.0.0
+info:
+  title: API # see https://example.com/comment
+  contact:
+    url: https://example.com/contact
+servers:
+  - url: \"https://api.example.com/v1\"
+    description: Production
+  - url: https://staging.example.com
+description: |
+  Multi-line text with
+  https://example.com/block
+externalDocs: {url: https://example.com/flow}
+";
+        assert_eq!(
+            links(extract_yaml(input, &KeyPaths::default())),
+            [
+                link("https://example.com/contact", "info.contact.url", "5:10"),
+                link("https://api.example.com/v1", "servers.url", "7:11"),
+                link("https://staging.example.com", "servers.url", "9:10"),
+                link("https://example.com/block", "description", "12:3"),
+                link("https://example.com/flow", "externalDocs.url", "13:21"),
+            ]
+        );
+
+        let key_paths = KeyPaths::new(["servers.url"]);
+        assert_eq!(extract_yaml(input, &key_paths).len(), 2);
+        let key_paths = KeyPaths::new(["*.url"]);
+        assert_eq!(
+            links(extract_yaml(input, &key_paths)),
+            [
+                link("https://api.example.com/v1", "servers.url", "7:11"),
+                link("https://staging.example.com", "servers.url", "9:10"),
+                link("https://example.com/flow", "externalDocs.url", "13:21"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_yaml_flow_collections() {
+        let input = "\
+tags: [{name: docs, externalDocs: {url: 'https://example.com/docs'}}, \"https://example.com/tag\"]
+links: {
+  home: https://example.com/, # comment https://example.com/comment
+  mirrors: [https://example.com/a, https://example.com/b]
+}
+after: https://example.com/after
+";
+        assert_eq!(
+            links(extract_yaml(input, &KeyPaths::default())),
+            [
+                link("https://example.com/docs", "tags.externalDocs.url", "1:42"),
+                link("https://example.com/tag", "tags", "1:72"),
+                link("https://example.com/", "links.home", "3:9"),
+                link("https://example.com/a", "links.mirrors", "4:13"),
+                link("https://example.com/b", "links.mirrors", "4:36"),
+                link("https://example.com/after", "after", "6:8"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_yaml_anchors_and_documents() {
+        let input = "\
+%YAML 1.2
+---
+x-docs: &docs
+  url: https://example.com/docs
+x-link: &link https://example.com/link
+info:
+  externalDocs: *docs
+  license: {url: *link}
+defaults: &defaults {homepage: https://example.com/home}
+project:
+  <<: *defaults
+---
+externalDocs: *docs
+other: https://example.com/other
+";
+        let key_paths = KeyPaths::new(["info.externalDocs.url", "*.license.url", "project"]);
+        assert_eq!(
+            links(extract_yaml(input, &key_paths)),
+            [
+                link("https://example.com/docs", "info.externalDocs.url", "4:8"),
+                link("https://example.com/link", "info.license.url", "5:15"),
+                link("https://example.com/home", "project.homepage", "9:32"),
+            ]
+        );
+        // Anchors of other documents aren't resolved
+        assert_eq!(extract_yaml(input, &KeyPaths::default()).len(), 4);
+    }
+
+    #[test]
+    fn test_extract_toml() {
+        let input = r#"[package]
+name = "lychee"
+homepage = "https://lychee.cli.rs"
+documentation = """
+https://docs.rs/lychee"""
+
+[dependencies]
+lychee-lib = { git = "https://github.com/lycheeverse/lychee" }
+
+[[bin]]
+urls = ['https://example.com/a', "https://example.com/b"]
+"#;
+        assert_eq!(
+            links(extract_toml(input, &KeyPaths::default())),
+            [
+                link("https://lychee.cli.rs", "package.homepage", "3:13"),
+                link("https://docs.rs/lychee", "package.documentation", "5:1"),
+                link(
+                    "https://github.com/lycheeverse/lychee",
+                    "dependencies.lychee-lib.git",
+                    "8:23"
+                ),
+                link("https://example.com/a", "bin.urls", "11:10"),
+                link("https://example.com/b", "bin.urls", "11:35"),
+            ]
+        );
+    }
+}
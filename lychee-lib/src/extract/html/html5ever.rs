@@ -7,7 +7,11 @@ use html5ever::{
 };
 
 use super::{
-    super::{css::extract_css_with_default_span, plaintext::extract_raw_uri_from_plaintext},
+    super::{
+        css::extract_css_with_default_span,
+        plaintext::extract_raw_uri_from_plaintext,
+        suppression::{Directive, Suppressions},
+    },
     is_email_link, is_verbatim_elem, srcset,
 };
 use crate::types::uri::raw::{RawUri, RawUriSpan, SourceSpanProvider, SpanProvider};
@@ -45,6 +49,8 @@ struct LinkExtractor {
     in_style_tag: RefCell<bool>,
     /// Accumulated CSS content from within a `<style>` tag.
     style_content: RefCell<String>,
+    /// Lines on which links are suppressed by `lychee-*` comments.
+    suppressions: RefCell<Suppressions>,
 }
 
 impl TokenSink for LinkExtractor {
@@ -83,7 +89,12 @@ impl TokenSink for LinkExtractor {
             Token::ParseError(_err) => {
                 // Silently ignore parse errors
             }
-            Token::CommentToken(_raw) => (),
+            Token::CommentToken(raw) => {
+                if let Some(directive) = Directive::parse(&raw) {
+                    let line = respect_multiline_tendril(line_number, &raw);
+                    self.suppressions.borrow_mut().add(directive, line);
+                }
+            }
             Token::NullCharacterToken => (),
             Token::DoctypeToken(_doctype) => (),
             Token::EOFToken => (),
@@ -100,13 +111,14 @@ fn respect_multiline_tendril(line_number: usize, raw: &Tendril<UTF8>) -> usize {
 }
 
 impl LinkExtractor {
-    pub(crate) const fn new(include_verbatim: bool) -> Self {
+    pub(crate) fn new(include_verbatim: bool) -> Self {
         Self {
             links: RefCell::new(Vec::new()),
             include_verbatim,
             current_verbatim_element_name: RefCell::new(None),
             in_style_tag: RefCell::new(false),
             style_content: RefCell::new(String::new()),
+            suppressions: RefCell::new(Suppressions::default()),
         }
     }
 
@@ -242,12 +254,12 @@ impl LinkExtractor {
                         text: url.to_string(),
                         element: Some(name.to_string()),
                         attribute: Some(attr.name.local.to_string()),
-                        span: RawUriSpan {
-                            line: line_number
+                        span: RawUriSpan::new(
+                            line_number
                                 .try_into()
                                 .expect("checked above that `line_number != 0`"),
-                            column: None,
-                        },
+                            None,
+                        ),
                     })
                     .collect::<Vec<_>>(),
             };
@@ -309,7 +321,8 @@ pub(crate) fn extract_html(buf: &str, include_verbatim: bool) -> Vec<RawUri> {
     let _handle = tokenizer.feed(&input);
     tokenizer.end();
 
-    tokenizer.sink.links.into_inner()
+    let links = tokenizer.sink.links.into_inner();
+    tokenizer.sink.suppressions.borrow().filter(links)
 }
 
 #[cfg(test)]
@@ -481,6 +494,56 @@ mod tests {
         assert_eq!(uris, expected);
     }
 
+    #[test]
+    fn test_suppression_comments() {
+        let input = r#"<html>
+<body>
+  <a href="https://same-line.com">x</a> <!-- lychee-ignore -->
+  <!-- lychee-ignore-next-line -->
+  <a href="https://next-line.com">x</a>
+  <!-- lychee-disable -->
+  <a href="https://disabled-1.com">x</a>
+  <img src="https://disabled-2.com/a.png">
+  <!--
+    lychee-enable
+  -->
+  <a href="https://checked.com">x</a>
+</body>
+</html>"#;
+
+        let uris: Vec<String> = extract_html(input, false)
+            .into_iter()
+            .map(|uri| uri.text)
+            .collect();
+        assert_eq!(uris, ["https://checked.com"]);
+    }
+
+    #[test]
+    fn test_extract_base_href() {
+        let input = r#"<!DOCTYPE html>
+        <html lang="en-US">
+          <head>
+            <base href="/docs/" target="_blank">
+          </head>
+          <body>
+            <a href="intro.html">
+          </body>
+        </html>"#;
+
+        let uris = extract_html(input, false);
+        assert_eq!(
+            uris.first(),
+            Some(&RawUri {
+                text: "/docs/".to_string(),
+                element: Some("base".to_string()),
+                attribute: Some("href".to_string()),
+                span: span_line(4),
+            })
+        );
+        assert!(uris[0].is_base_href());
+        assert!(!uris[1].is_base_href());
+    }
+
     #[test]
     fn test_valid_tel() {
         let input = r#"<!DOCTYPE html>
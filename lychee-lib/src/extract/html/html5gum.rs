@@ -6,7 +6,11 @@ use std::collections::{HashMap, HashSet};
 
 use super::{is_email_link, is_verbatim_elem, srcset};
 use crate::{
-    extract::{css::extract_css, plaintext::extract_raw_uri_from_plaintext},
+    extract::{
+        css::extract_css,
+        plaintext::extract_raw_uri_from_plaintext,
+        suppression::{Directive, Suppressions},
+    },
     types::uri::raw::{OffsetSpanProvider, RawUri, SourceSpanProvider, SpanProvider},
 };
 
@@ -53,6 +57,8 @@ struct LinkExtractor<S: SpanProvider> {
     style_content: String,
     /// Start offset of the style tag content (for span calculation).
     style_content_offset: usize,
+    /// Lines on which links are suppressed by `lychee-*` comments.
+    suppressions: Suppressions,
 }
 
 impl<S: SpanProvider> LinkExtractor<S> {
@@ -73,6 +79,7 @@ impl<S: SpanProvider> LinkExtractor<S> {
             in_style_tag: false,
             style_content: String::default(),
             style_content_offset: 0,
+            suppressions: Suppressions::default(),
         }
     }
 
@@ -344,9 +351,13 @@ impl<S: SpanProvider> Callback<(), usize> for &mut LinkExtractor<S> {
                     ));
                 }
             }
-            CallbackEvent::Comment { .. }
-            | CallbackEvent::Doctype { .. }
-            | CallbackEvent::Error(_) => {}
+            CallbackEvent::Comment { value } => {
+                if let Some(directive) = Directive::parse(&String::from_utf8_lossy(value)) {
+                    let line = self.span_provider.span(span.start).line.get();
+                    self.suppressions.add(directive, line);
+                }
+            }
+            CallbackEvent::Doctype { .. } | CallbackEvent::Error(_) => {}
         }
         None
     }
@@ -365,11 +376,12 @@ pub(crate) fn extract_html_with_span<S: SpanProvider>(
     let mut extractor = LinkExtractor::new(span_provider, include_verbatim);
     let mut tokenizer = Tokenizer::new_with_emitter(buf, CallbackEmitter::new(&mut extractor));
     assert!(tokenizer.next().is_none());
-    extractor
+    let links = extractor
         .links
         .into_iter()
         .filter(|link| link.attribute.is_some() || include_verbatim)
-        .collect()
+        .collect();
+    extractor.suppressions.filter(links)
 }
 
 /// Extract fragments from id attributes within a HTML string.
@@ -561,6 +573,56 @@ mod tests {
         assert_eq!(uris, expected);
     }
 
+    #[test]
+    fn test_suppression_comments() {
+        let input = r#"<html>
+<body>
+  <a href="https://same-line.com">x</a> <!-- lychee-ignore -->
+  <!-- lychee-ignore-next-line -->
+  <a href="https://next-line.com">x</a>
+  <!-- lychee-disable -->
+  <a href="https://disabled-1.com">x</a>
+  <img src="https://disabled-2.com/a.png">
+  <!--
+    lychee-enable
+  -->
+  <a href="https://checked.com">x</a>
+</body>
+</html>"#;
+
+        let uris: Vec<String> = extract_html(input, false)
+            .into_iter()
+            .map(|uri| uri.text)
+            .collect();
+        assert_eq!(uris, ["https://checked.com"]);
+    }
+
+    #[test]
+    fn test_extract_base_href() {
+        let input = r#"<!DOCTYPE html>
+        <html lang="en-US">
+          <head>
+            <base href="/docs/" target="_blank">
+          </head>
+          <body>
+            <a href="intro.html">
+          </body>
+        </html>"#;
+
+        let uris = extract_html(input, false);
+        assert_eq!(
+            uris.first(),
+            Some(&RawUri {
+                text: "/docs/".to_string(),
+                element: Some("base".to_string()),
+                attribute: Some("href".to_string()),
+                span: span(4, 25),
+            })
+        );
+        assert!(uris[0].is_base_href());
+        assert!(!uris[1].is_base_href());
+    }
+
     #[test]
     fn test_valid_tel() {
         let input = r#"<!DOCTYPE html>
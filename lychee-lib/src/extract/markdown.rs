@@ -6,7 +6,10 @@ use pulldown_cmark::{CowStr, Event, LinkType, Options, Parser, Tag, TagEnd, Text
 
 use crate::{
     checker::wikilink::wikilink,
-    extract::{html::html5gum::extract_html_with_span, plaintext::extract_raw_uri_from_plaintext},
+    extract::{
+        html::html5gum::extract_html_with_span, plaintext::extract_raw_uri_from_plaintext,
+        suppression::Suppressions,
+    },
     types::uri::raw::{
         OffsetSpanProvider, RawUri, RawUriSpan, SourceSpanProvider, SpanProvider as _,
     },
@@ -40,10 +43,13 @@ pub(crate) fn extract_markdown(
     let mut html_block_buffer = String::new();
     let mut html_block_start_offset = 0;
 
+    // Lines on which links are suppressed by `lychee-*` comments
+    let mut suppressions = Suppressions::default();
+
     let span_provider = SourceSpanProvider::from_input(input);
     let parser =
         TextMergeWithOffset::new(Parser::new_ext(input, md_extensions()).into_offset_iter());
-    parser
+    let links = parser
         .filter_map(|(event, span)| match event {
             // A link.
             Event::Start(Tag::Link {
@@ -151,13 +157,15 @@ pub(crate) fn extract_markdown(
                 if html_block_buffer.is_empty() {
                     None
                 } else {
+                    let span_provider = OffsetSpanProvider {
+                        offset: html_block_start_offset,
+                        inner: &span_provider
+                    };
+                    suppressions.add_from_html(&html_block_buffer, &span_provider);
                     Some(extract_html_with_span(
                         &html_block_buffer,
                         include_verbatim,
-                        OffsetSpanProvider {
-                            offset: html_block_start_offset,
-                            inner: &span_provider
-                        }
+                        span_provider
                     ))
                 }
             }
@@ -170,21 +178,17 @@ pub(crate) fn extract_markdown(
                     None
                 } else {
                     // Standalone HTML (not part of a block) - process immediately
-                    Some(extract_html_with_span(
-                        &html,
-                        include_verbatim,
-                        OffsetSpanProvider { offset: span.start, inner: &span_provider }
-                    ))
+                    let span_provider = OffsetSpanProvider { offset: span.start, inner: &span_provider };
+                    suppressions.add_from_html(&html, &span_provider);
+                    Some(extract_html_with_span(&html, include_verbatim, span_provider))
                 }
             }
 
             // Inline HTML (not part of a block)
             Event::InlineHtml(html) => {
-                Some(extract_html_with_span(
-                    &html,
-                    include_verbatim,
-                    OffsetSpanProvider { offset: span.start, inner: &span_provider }
-                ))
+                let span_provider = OffsetSpanProvider { offset: span.start, inner: &span_provider };
+                suppressions.add_from_html(&html, &span_provider);
+                Some(extract_html_with_span(&html, include_verbatim, span_provider))
             }
 
             // An inline code node.
@@ -213,7 +217,9 @@ pub(crate) fn extract_markdown(
             _ => None,
         })
         .flatten()
-        .collect()
+        .collect();
+
+    suppressions.filter(links)
 }
 
 fn get_email_span_provider<'a>(
@@ -446,6 +452,36 @@ or inline like `https://bar.org` for instance.
         assert_eq!(uris, expected);
     }
 
+    #[test]
+    fn test_suppression_comments() {
+        let input = r#"# Suppressions
+
+[same line](https://same-line.com) <!-- lychee-ignore -->
+
+<!-- lychee-ignore-next-line -->
+See https://next-line.com
+
+<!-- lychee-disable -->
+[disabled](https://disabled.com)
+<a href="https://disabled-html.com">disabled</a>
+<!-- lychee-enable -->
+
+[checked](https://checked.com)
+
+```html
+<!-- lychee-disable -->
+```
+
+https://also-checked.com
+"#;
+
+        let uris: Vec<String> = extract_markdown(input, false, false)
+            .into_iter()
+            .map(|uri| uri.text)
+            .collect();
+        assert_eq!(uris, ["https://checked.com", "https://also-checked.com"]);
+    }
+
     #[test]
     fn test_skip_verbatim_html() {
         let input = "
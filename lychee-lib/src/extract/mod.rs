@@ -3,15 +3,32 @@ use crate::types::{
     uri::raw::{RawUri, SourceSpanProvider},
 };
 
+pub(crate) mod asciidoc;
 pub mod css;
+mod custom;
+mod data;
 pub mod html;
 pub mod markdown;
+mod notebook;
 mod plaintext;
+pub(crate) mod rst;
+mod source_code;
+mod suppression;
 pub mod xml;
 
+use custom::CustomExtractors;
+pub use custom::{CustomExtractor, CustomFormat};
+pub use data::KeyPaths;
+pub use xml::{XmlSelector, XmlSelectorParseError};
+
+use asciidoc::extract_asciidoc;
 use css::extract_css;
+use data::{extract_json, extract_toml, extract_yaml};
 use markdown::extract_markdown;
+use notebook::extract_notebook;
 use plaintext::extract_raw_uri_from_plaintext;
+use rst::{RstLinks, extract_rst};
+use source_code::extract_source_code;
 use xml::extract_xml;
 
 /// A handler for extracting links from various input formats like Markdown and
@@ -24,6 +41,16 @@ pub struct Extractor {
     include_wikilinks: bool,
 }
 
+/// Settings of the [`Extractor`] which are configured on the
+/// [`Collector`](crate::Collector), e.g. the [`CustomExtractor`]s.
+#[derive(Default, Debug, Clone)]
+pub(crate) struct ExtractorOptions {
+    /// Extractors for formats which aren't supported natively
+    pub(crate) custom_extractors: CustomExtractors,
+    pub(crate) key_paths: KeyPaths,
+    pub(crate) xml_selectors: Vec<XmlSelector>,
+}
+
 impl Extractor {
     /// Creates a new extractor
     ///
@@ -47,10 +74,23 @@ impl Extractor {
     }
 
     /// Main entrypoint for extracting links from various sources
-    /// (Markdown, HTML, CSS, and plaintext)
+    /// (Markdown, HTML, CSS, reStructuredText, AsciiDoc, Jupyter notebooks,
+    /// JSON, YAML, TOML, comments of source code, and plaintext)
     #[must_use]
     pub fn extract(&self, input_content: &InputContent) -> Vec<RawUri> {
+        self.extract_with(input_content, &ExtractorOptions::default())
+    }
+
+    /// Extract links like [`Extractor::extract`], with the given options
+    pub(crate) fn extract_with(
+        self,
+        input_content: &InputContent,
+        options: &ExtractorOptions,
+    ) -> Vec<RawUri> {
         let content = &input_content.content;
+        if let Some(extractor) = options.custom_extractors.get(input_content) {
+            return extractor.extract(content);
+        }
         match input_content.file_type {
             FileType::Markdown => {
                 extract_markdown(content, self.include_verbatim, self.include_wikilinks)
@@ -66,7 +106,44 @@ impl Extractor {
             FileType::Plaintext => {
                 extract_raw_uri_from_plaintext(content, &SourceSpanProvider::from_input(content))
             }
-            FileType::Xml => extract_xml(content, &SourceSpanProvider::from_input(content)),
+            FileType::Xml => extract_xml(
+                content,
+                &SourceSpanProvider::from_input(content),
+                &options.xml_selectors,
+            ),
+            FileType::Rst => extract_rst(content, self.include_verbatim).uris,
+            FileType::AsciiDoc => extract_asciidoc(content, self.include_verbatim),
+            FileType::Notebook => {
+                extract_notebook(content, self.include_verbatim, self.include_wikilinks)
+            }
+            FileType::Json => extract_json(content, &options.key_paths),
+            FileType::Yaml => extract_yaml(content, &options.key_paths),
+            FileType::Toml => extract_toml(content, &options.key_paths),
+            FileType::SourceCode(language) => extract_source_code(
+                content,
+                language,
+                self.include_verbatim,
+                self.include_wikilinks,
+            ),
+        }
+    }
+
+    /// Extract links like [`Extractor::extract_with`], together with the
+    /// labels which reStructuredText documents define and reference.
+    /// Other documents have no labels.
+    pub(crate) fn extract_with_labels(
+        self,
+        input_content: &InputContent,
+        options: &ExtractorOptions,
+    ) -> RstLinks {
+        if input_content.file_type == FileType::Rst
+            && options.custom_extractors.get(input_content).is_none()
+        {
+            return extract_rst(&input_content.content, self.include_verbatim);
+        }
+        RstLinks {
+            uris: self.extract_with(input_content, options),
+            ..RstLinks::default()
         }
     }
 }
@@ -231,6 +308,7 @@ mod tests {
         let input_content = &InputContent {
             source,
             file_type: FileType::Html,
+            content_type: None,
             content: contents.to_string(),
         };
 
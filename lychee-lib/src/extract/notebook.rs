@@ -0,0 +1,211 @@
+//! Extract links from Jupyter notebooks.
+//!
+//! Notebooks are JSON documents with a list of cells. Links are extracted
+//! from the Markdown of markdown cells, and from the source code of code
+//! cells and their text outputs like plain text. The spans of the links
+//! have the index of their cell, and the index of their output for links in
+//! outputs. Their line is relative to the source or output of the cell.
+
+use std::{collections::HashMap, num::NonZeroUsize};
+
+use log::warn;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{markdown::extract_markdown, plaintext::extract_raw_uri_from_plaintext};
+use crate::types::uri::raw::{RawUri, SourceSpanProvider};
+
+#[derive(Deserialize)]
+struct Notebook {
+    cells: Vec<Cell>,
+}
+
+#[derive(Deserialize)]
+struct Cell {
+    #[serde(rename = "cell_type")]
+    kind: String,
+    #[serde(default)]
+    source: Text,
+    #[serde(default)]
+    outputs: Vec<Output>,
+}
+
+#[derive(Deserialize)]
+struct Output {
+    /// Text of `stream` outputs
+    #[serde(default)]
+    text: Option<Text>,
+    /// Data of `execute_result` and `display_data` outputs by MIME type
+    #[serde(default)]
+    data: HashMap<String, Value>,
+}
+
+/// Multiline strings are either a single string or a list of lines
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Text {
+    Single(String),
+    Lines(Vec<String>),
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Self::Single(String::new())
+    }
+}
+
+impl Text {
+    fn into_string(self) -> String {
+        match self {
+            Self::Single(text) => text,
+            Self::Lines(lines) => lines.concat(),
+        }
+    }
+}
+
+/// Extract unparsed URL strings from a Jupyter notebook.
+///
+/// If the notebook isn't valid JSON, the links are extracted from the
+/// content as plain text instead.
+pub(crate) fn extract_notebook(
+    input: &str,
+    include_verbatim: bool,
+    include_wikilinks: bool,
+) -> Vec<RawUri> {
+    let notebook: Notebook = match serde_json::from_str(input) {
+        Ok(notebook) => notebook,
+        Err(e) => {
+            warn!("Cannot parse Jupyter notebook, extracting links as plain text: {e}");
+            return extract_plaintext(input);
+        }
+    };
+
+    let mut uris = Vec::new();
+    for (index, cell) in notebook.cells.into_iter().enumerate() {
+        let source = cell.source.into_string();
+        let mut cell_uris = match cell.kind.as_str() {
+            "markdown" => extract_markdown(&source, include_verbatim, include_wikilinks),
+            "code" => extract_plaintext(&source),
+            _ => continue,
+        };
+        for (output_index, output) in cell.outputs.into_iter().enumerate() {
+            if let Some(text) = output_text(output) {
+                let mut output_uris = extract_plaintext(&text);
+                for uri in &mut output_uris {
+                    // outputs are 1-based like cells
+                    uri.span.output = NonZeroUsize::new(output_index + 1);
+                }
+                cell_uris.extend(output_uris);
+            }
+        }
+
+        for uri in &mut cell_uris {
+            // cells are 1-based like lines
+            uri.span.cell = NonZeroUsize::new(index + 1);
+        }
+        uris.extend(cell_uris);
+    }
+    uris
+}
+
+/// The text of a stream output or the plain text of a result
+fn output_text(output: Output) -> Option<String> {
+    if let Some(text) = output.text {
+        return Some(text.into_string());
+    }
+    let text = output.data.get("text/plain")?.clone();
+    serde_json::from_value::<Text>(text)
+        .ok()
+        .map(Text::into_string)
+}
+
+fn extract_plaintext(input: &str) -> Vec<RawUri> {
+    extract_raw_uri_from_plaintext(input, &SourceSpanProvider::from_input(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn extract(input: &str) -> Vec<(String, String)> {
+        extract_notebook(input, false, false)
+            .into_iter()
+            .map(|uri| (uri.text, uri.span.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_notebook() {
+        let input = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Title\n",
+    "\n",
+    "See [the docs](https://example.com/docs).\n",
+    "```\n",
+    "https://example.com/verbatim\n",
+    "```"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": ["Downloading\n", "from https://example.com/stream\n"]
+    },
+    {
+     "output_type": "execute_result",
+     "execution_count": 1,
+     "metadata": {},
+     "data": {
+      "text/plain": "'https://example.com/result'",
+      "text/html": "<a href=\"https://example.com/html\">link</a>"
+     }
+    }
+   ],
+   "source": "import requests\nrequests.get(\"https://example.com/data.csv\")"
+  },
+  {
+   "cell_type": "raw",
+   "metadata": {},
+   "source": "https://example.com/raw"
+  }
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+        assert_eq!(
+            extract(input),
+            [
+                ("https://example.com/docs".into(), "cell 1, 3:5".into()),
+                ("https://example.com/data.csv".into(), "cell 2, 2:15".into()),
+                (
+                    "https://example.com/stream".into(),
+                    "cell 2, output 1, 2:6".into()
+                ),
+                (
+                    "https://example.com/result".into(),
+                    "cell 2, output 2, 1:2".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_notebook() {
+        assert_eq!(
+            extract("not a notebook https://example.com"),
+            [("https://example.com".into(), "1:16".into())]
+        );
+    }
+}
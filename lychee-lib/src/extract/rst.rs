@@ -0,0 +1,477 @@
+//! Extract links from reStructuredText documents.
+//!
+//! The following links are extracted:
+//! - hyperlink targets, e.g. `.. _lychee: https://lychee.cli.rs` or
+//!   `__ https://lychee.cli.rs`
+//! - inline links with embedded URIs, e.g. `` `lychee <https://lychee.cli.rs>`_ ``
+//! - the paths and `:target:` options of `image` and `figure` directives
+//! - `:doc:` roles, resolved to the `.rst` file of the document,
+//!   and `:download:` roles
+//! - standalone URLs in text
+//!
+//! Links in comments are ignored, and so are links in literal blocks and
+//! inline literals unless verbatim content is included.
+//!
+//! Labels of `:ref:` roles can be defined in any document of a Sphinx
+//! project, e.g. with `.. _install:`. So the labels and references of each
+//! document are collected as well, and the [`Collector`](crate::Collector)
+//! resolves the references once all documents are read.
+// NOTE: this is a line-based extractor rather than a full reStructuredText
+// parser. It covers the constructs used for links in practice, but may be
+// confused by unusual indentation, e.g. in nested lists.
+
+use std::{ops::Range, path::Path, sync::LazyLock};
+
+use regex::{Captures, Regex};
+
+use crate::{
+    types::uri::raw::{OffsetSpanProvider, RawUri, SourceSpanProvider, SpanProvider},
+    utils::url,
+};
+
+/// Hyperlink target, e.g. `.. _name: https://example.com` or the
+/// anonymous `.. __: https://example.com`.
+/// Targets without a link, e.g. `.. _install:`, define labels.
+static TARGET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\.\.\s+_(?:`(?P<quoted>[^`]*)`|(?P<name>[^:`]*)):(?:\s+(?P<target>\S+))?\s*$")
+        .unwrap()
+});
+
+/// Short form of an anonymous hyperlink target, e.g. `__ https://example.com`
+static ANONYMOUS_TARGET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^__\s+(?P<target>\S+)\s*$").unwrap());
+
+/// Directive, optionally as part of a substitution definition,
+/// e.g. `.. image:: logo.png` or `.. |logo| image:: logo.png`
+static DIRECTIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\.\.\s+(?:\|[^|]+\|\s+)?(?P<name>[\w:-]+)::(?:\s+(?P<argument>\S.*?))?\s*$")
+        .unwrap()
+});
+
+/// Footnote or citation, e.g. `.. [1] See https://example.com`
+static FOOTNOTE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\.\.\s+\[[^\]]+\]\s").unwrap());
+
+/// Option of an `image` or `figure` directive with a link
+static TARGET_OPTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^:target:\s+(?P<target>\S+)\s*$").unwrap());
+
+/// Inline markup with links: embedded URIs, e.g. `` `text <url>`_ ``,
+/// roles with files or labels, e.g. `` :doc:`install` ``, and inline literals
+static INLINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?x)
+        ``[^`]+``                                                       # inline literal
+        | :(?P<role>doc|download|ref):`(?:[^`<]*<)?(?P<file>[^`<>]+)>?` # role
+        | `[^`]*?<(?P<uri>[^`<>]+)>`__?                                 # embedded URI
+        ",
+    )
+    .unwrap()
+});
+
+/// Directives whose content is a literal block
+const LITERAL_DIRECTIVES: &[&str] = &["code", "code-block", "sourcecode", "parsed-literal"];
+
+/// An indented block in which lines aren't regular text
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Block {
+    /// A comment or a literal block, with the indentation of its marker
+    Skipped(usize),
+    /// An `image` or `figure` directive, whose options may contain links
+    Image(usize),
+}
+
+impl Block {
+    const fn indent(self) -> usize {
+        match self {
+            Self::Skipped(indent) | Self::Image(indent) => indent,
+        }
+    }
+}
+
+/// The links of a reStructuredText document, together with the labels it
+/// defines and references
+#[derive(Debug, Default)]
+pub(crate) struct RstLinks {
+    /// Links to check
+    pub(crate) uris: Vec<RawUri>,
+    /// Labels defined by hyperlink targets without a link, e.g. `.. _install:`
+    pub(crate) labels: Vec<String>,
+    /// Labels referenced by `:ref:` roles, which must be defined by any
+    /// document
+    pub(crate) references: Vec<RawUri>,
+}
+
+/// Extract unparsed URL strings, labels and label references from a
+/// reStructuredText document
+pub(crate) fn extract_rst(input: &str, include_verbatim: bool) -> RstLinks {
+    let span_provider = SourceSpanProvider::from_input(input);
+    let mut links = RstLinks::default();
+    let mut uris = Vec::new();
+    // Byte ranges of consecutive lines of regular text, i.e. of paragraphs
+    let mut paragraphs: Vec<Range<usize>> = Vec::new();
+    let mut block: Option<Block> = None;
+    // Indentation of a paragraph which ends with `::`,
+    // i.e. which is followed by a literal block
+    let mut literal_marker: Option<usize> = None;
+
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let line_offset = offset;
+        offset += line.len();
+
+        let content = line.trim();
+        if content.is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let content_offset = line_offset + indent;
+
+        if let Some(marker_indent) = literal_marker.take()
+            && indent > marker_indent
+        {
+            block = Some(Block::Skipped(marker_indent));
+        }
+        if block.is_some_and(|block| indent <= block.indent()) {
+            block = None;
+        }
+
+        let mut push = |text: &str, start: usize| {
+            uris.push(RawUri {
+                text: text.to_string(),
+                element: None,
+                attribute: None,
+                span: span_provider.span(start),
+            });
+        };
+
+        match block {
+            Some(Block::Skipped(_)) if !include_verbatim => continue,
+            Some(Block::Image(_)) if content.starts_with(':') => {
+                if let Some(target) = TARGET_OPTION
+                    .captures(content)
+                    .and_then(|c| c.name("target"))
+                {
+                    push(target.as_str(), content_offset + target.start());
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        if content.starts_with("..") {
+            if let Some(captures) = TARGET.captures(content) {
+                match captures.name("target") {
+                    // Targets ending with `_` refer to other targets
+                    Some(target) if !target.as_str().ends_with('_') => {
+                        push(target.as_str(), content_offset + target.start());
+                    }
+                    Some(_) => {}
+                    None => links.labels.extend(label(&captures)),
+                }
+            } else if let Some(captures) = DIRECTIVE.captures(content) {
+                let name = captures["name"].to_lowercase();
+                if name == "image" || name == "figure" {
+                    if let Some(path) = captures.name("argument") {
+                        push(path.as_str(), content_offset + path.start());
+                    }
+                    block = Some(Block::Image(indent));
+                } else if LITERAL_DIRECTIVES.contains(&name.as_str()) {
+                    block = Some(Block::Skipped(indent));
+                }
+            } else if FOOTNOTE.is_match(content) {
+                add_paragraph_line(&mut paragraphs, content_offset..line_offset + line.len());
+            } else {
+                // Everything else is a comment
+                block = Some(Block::Skipped(indent));
+            }
+            continue;
+        }
+
+        if let Some(target) = ANONYMOUS_TARGET
+            .captures(content)
+            .and_then(|c| c.name("target"))
+        {
+            push(target.as_str(), content_offset + target.start());
+            continue;
+        }
+
+        if content.ends_with("::") {
+            literal_marker = Some(indent);
+        }
+        add_paragraph_line(&mut paragraphs, line_offset..line_offset + line.len());
+    }
+
+    links.uris = uris;
+    for paragraph in paragraphs {
+        let span_provider = OffsetSpanProvider {
+            offset: paragraph.start,
+            inner: &span_provider,
+        };
+        extract_inline(
+            &input[paragraph],
+            include_verbatim,
+            &span_provider,
+            &mut links,
+        );
+    }
+    links
+        .uris
+        .sort_by_key(|uri| (uri.span.line, uri.span.column));
+    links
+}
+
+/// The label defined by a hyperlink target without a link
+fn label(target: &Captures) -> Option<String> {
+    let name = target.name("quoted").or_else(|| target.name("name"))?;
+    // `.. __:` is an anonymous target rather than a label
+    (name.as_str() != "_").then(|| normalize_label(name.as_str()))
+}
+
+/// Normalize a label like Sphinx does: labels are case-insensitive and
+/// whitespace is collapsed
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Add a line of text to the current paragraph, or start a new paragraph
+fn add_paragraph_line(paragraphs: &mut Vec<Range<usize>>, line: Range<usize>) {
+    match paragraphs.last_mut() {
+        Some(paragraph) if paragraph.end == line.start => paragraph.end = line.end,
+        _ => paragraphs.push(line),
+    }
+}
+
+/// Extract the links and label references of inline markup and
+/// standalone URLs from text
+fn extract_inline(
+    text: &str,
+    include_verbatim: bool,
+    span_provider: &impl SpanProvider,
+    links: &mut RstLinks,
+) {
+    let mut markup = Vec::new();
+
+    for captures in INLINE.captures_iter(text) {
+        let whole = captures.get(0).expect("match has a whole capture");
+        markup.push(whole.range());
+
+        let link = if let Some(uri) = captures.name("uri") {
+            // Embedded URIs ending with `_` refer to targets
+            (!uri.as_str().ends_with('_')).then(|| (uri.as_str().to_string(), uri.start()))
+        } else if let Some(file) = captures.name("file") {
+            let path = file.as_str().trim();
+            match &captures["role"] {
+                "ref" => {
+                    links.references.push(RawUri {
+                        text: normalize_label(path),
+                        element: None,
+                        attribute: None,
+                        span: span_provider.span(file.start()),
+                    });
+                    None
+                }
+                "doc" if Path::new(path).extension().is_none() => {
+                    Some((format!("{path}.rst"), file.start()))
+                }
+                _ => Some((path.to_string(), file.start())),
+            }
+        } else if include_verbatim {
+            // URLs in inline literals are found below
+            markup.pop();
+            None
+        } else {
+            None
+        };
+
+        if let Some((text, start)) = link {
+            links.uris.push(RawUri {
+                text,
+                element: None,
+                attribute: None,
+                span: span_provider.span(start),
+            });
+        }
+    }
+
+    links.uris.extend(
+        url::find_links(text)
+            .filter(|link| !markup.iter().any(|range| range.contains(&link.start())))
+            .map(|link| RawUri {
+                text: link.as_str().to_owned(),
+                element: None,
+                attribute: None,
+                span: span_provider.span(link.start()),
+            }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::types::uri::raw::span;
+
+    fn extract(input: &str) -> Vec<(String, usize, usize)> {
+        extract_rst(input, false)
+            .uris
+            .into_iter()
+            .map(|uri| {
+                (
+                    uri.text,
+                    uri.span.line.get(),
+                    uri.span.column.unwrap().get(),
+                )
+            })
+            .collect()
+    }
+
+    fn texts(input: &str, include_verbatim: bool) -> Vec<String> {
+        extract_rst(input, include_verbatim)
+            .uris
+            .into_iter()
+            .map(|uri| uri.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_hyperlink_targets() {
+        let input = "\
+.. _lychee: https://lychee.cli.rs
+.. _`Link checker`: https://github.com/lycheeverse
+.. __: https://anonymous.example.com
+__ https://short.example.com
+.. _internal-label:
+.. _alias: lychee_
+";
+        assert_eq!(
+            extract(input),
+            [
+                ("https://lychee.cli.rs".into(), 1, 13),
+                ("https://github.com/lycheeverse".into(), 2, 21),
+                ("https://anonymous.example.com".into(), 3, 8),
+                ("https://short.example.com".into(), 4, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_links() {
+        let input = "\
+See `the docs <https://example.com/docs>`_ and
+`another page
+<https://example.com/other>`__, or `<https://example.com/bare>`_.
+A `reference <lychee_>`_ to a target isn't a link.
+";
+        assert_eq!(
+            extract(input),
+            [
+                ("https://example.com/docs".into(), 1, 16),
+                ("https://example.com/other".into(), 3, 2),
+                ("https://example.com/bare".into(), 3, 38),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_images() {
+        let input = "\
+.. image:: images/logo.png
+   :alt: Logo
+   :target: https://example.com/logo
+
+.. figure:: /images/diagram.svg
+
+   A caption with https://example.com/caption
+
+.. |badge| image:: https://example.com/badge.svg
+";
+        assert_eq!(
+            extract(input),
+            [
+                ("images/logo.png".into(), 1, 12),
+                ("https://example.com/logo".into(), 3, 13),
+                ("/images/diagram.svg".into(), 5, 13),
+                ("https://example.com/caption".into(), 7, 19),
+                ("https://example.com/badge.svg".into(), 9, 20),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_roles() {
+        let input = "Read :doc:`install`, :doc:`the guide <../guide/index>`, \
+:download:`script <scripts/setup.py>` and :ref:`some-label`.";
+        assert_eq!(
+            texts(input, false),
+            ["install.rst", "../guide/index.rst", "scripts/setup.py"]
+        );
+
+        let references = extract_rst(input, false).references;
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].text, "some-label");
+        assert_eq!(references[0].span, span(1, 105));
+    }
+
+    #[test]
+    fn test_labels() {
+        let input = "\
+.. _install:
+.. _`Getting  Started`:
+.. __: https://anonymous.example.com
+.. _lychee: https://lychee.cli.rs
+
+See :ref:`the guide <Getting Started>` and :ref:`install`.
+";
+        let links = extract_rst(input, false);
+        assert_eq!(links.labels, ["install", "getting started"]);
+        let references: Vec<_> = links.references.into_iter().map(|uri| uri.text).collect();
+        assert_eq!(references, ["getting started", "install"]);
+    }
+
+    #[test]
+    fn test_verbatim() {
+        let input = "\
+Example::
+
+    curl https://example.com/literal
+
+.. code-block:: bash
+
+   curl https://example.com/code
+
+Use ``https://example.com/inline`` and visit https://example.com/text.
+";
+        assert_eq!(texts(input, false), ["https://example.com/text"]);
+        assert_eq!(
+            texts(input, true),
+            [
+                "https://example.com/literal",
+                "https://example.com/code",
+                "https://example.com/inline",
+                "https://example.com/text",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comments_and_footnotes() {
+        let input = "\
+.. This is a comment with https://example.com/comment
+   continued https://example.com/continued
+
+.. [1] A footnote with https://example.com/footnote
+";
+        assert_eq!(texts(input, false), ["https://example.com/footnote"]);
+    }
+
+    #[test]
+    fn test_span() {
+        let input = "Title\n=====\n\nVisit https://example.com";
+        assert_eq!(extract_rst(input, false).uris[0].span, span(4, 7));
+    }
+}
@@ -0,0 +1,489 @@
+//! Extract links from the comments of source code.
+//!
+//! Links in string literals and the rest of the code are ignored, because
+//! these are often URL-shaped test data or templates rather than links.
+//! Rust doc comments are Markdown, so their links are extracted like the
+//! links of a Markdown file, except for intra-doc links like
+//! `[Client](crate::Client)`. Python docstrings are checked like comments.
+
+use std::{ops::Range, sync::LazyLock};
+
+use regex::Regex;
+
+use super::{markdown::extract_markdown, plaintext::extract_raw_uri_from_plaintext};
+use crate::types::{
+    Language,
+    uri::raw::{OffsetSpanProvider, RawUri, SourceSpanProvider, SpanProvider},
+};
+
+/// Rustdoc intra-doc link destinations like `Self::new`, `crate::Client`,
+/// `vec!` or `struct@Client`
+static INTRA_DOC_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:[a-z]+@)?[A-Za-z_]\w*(?:::[A-Za-z_]\w*)*(?:!|\(\))?$").unwrap()
+});
+
+/// A comment found in the source code
+#[derive(Debug, PartialEq)]
+enum Comment {
+    /// A comment with plain text
+    Text(Range<usize>),
+    /// A Rust doc comment with Markdown, with the range of each of its lines
+    Docs(Vec<Range<usize>>),
+}
+
+/// Extract unparsed URL strings from the comments of source code.
+pub(crate) fn extract_source_code(
+    input: &str,
+    language: Language,
+    include_verbatim: bool,
+    include_wikilinks: bool,
+) -> Vec<RawUri> {
+    let span_provider = SourceSpanProvider::from_input(input);
+    let mut uris = Vec::new();
+    for comment in Scanner::new(input, language).comments() {
+        match comment {
+            Comment::Text(range) => uris.extend(extract_raw_uri_from_plaintext(
+                &input[range.clone()],
+                &OffsetSpanProvider {
+                    offset: range.start,
+                    inner: &span_provider,
+                },
+            )),
+            Comment::Docs(lines) => uris.extend(extract_docs(
+                input,
+                &lines,
+                &span_provider,
+                include_verbatim,
+                include_wikilinks,
+            )),
+        }
+    }
+    uris
+}
+
+/// Extract the links of a Rust doc comment as Markdown.
+///
+/// The lines of the comment are joined without their comment markers,
+/// and the spans of the links are mapped back to the source code.
+fn extract_docs(
+    input: &str,
+    lines: &[Range<usize>],
+    span_provider: &SourceSpanProvider,
+    include_verbatim: bool,
+    include_wikilinks: bool,
+) -> Vec<RawUri> {
+    let markdown = lines
+        .iter()
+        .map(|line| &input[line.clone()])
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    extract_markdown(&markdown, include_verbatim, include_wikilinks)
+        .into_iter()
+        .filter(|uri| !INTRA_DOC_LINK.is_match(&uri.text))
+        .filter_map(|mut uri| {
+            let line = lines.get(uri.span.line.get() - 1)?;
+            let text = &input[line.clone()];
+            let offset = uri.span.column.map_or(0, |column| {
+                text.char_indices()
+                    .nth(column.get() - 1)
+                    .map_or(text.len(), |(offset, _)| offset)
+            });
+            let column = uri.span.column;
+            uri.span = span_provider.span(line.start + offset);
+            if column.is_none() {
+                uri.span.column = None;
+            }
+            Some(uri)
+        })
+        .collect()
+}
+
+/// A minimal lexer, which only knows enough about a language to tell
+/// comments apart from string literals and code.
+///
+/// All delimiters are ASCII, so the byte offsets are always at character
+/// boundaries.
+struct Scanner<'a> {
+    input: &'a str,
+    bytes: &'a [u8],
+    language: Language,
+    position: usize,
+    comments: Vec<Comment>,
+    /// Lines of the current Rust doc comment and whether it is an inner
+    /// doc comment (`//!`)
+    docs: Option<(Vec<Range<usize>>, bool)>,
+}
+
+impl<'a> Scanner<'a> {
+    const fn new(input: &'a str, language: Language) -> Self {
+        Self {
+            input,
+            bytes: input.as_bytes(),
+            language,
+            position: 0,
+            comments: Vec::new(),
+            docs: None,
+        }
+    }
+
+    fn comments(mut self) -> Vec<Comment> {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            if byte.is_ascii_whitespace() {
+                self.position += 1;
+                continue;
+            }
+            if self.language == Language::Rust && self.doc_line() {
+                continue;
+            }
+            self.flush_docs();
+            self.token(byte);
+        }
+        self.flush_docs();
+        self.comments
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.bytes[self.position..].starts_with(prefix.as_bytes())
+    }
+
+    fn line_end(&self, from: usize) -> usize {
+        self.input[from..]
+            .find('\n')
+            .map_or(self.input.len(), |end| from + end)
+    }
+
+    /// Handle a Rust `///` or `//!` doc comment line, and add it to the
+    /// current doc comment
+    fn doc_line(&mut self) -> bool {
+        let inner = if self.starts_with("///") && !self.starts_with("////") {
+            false
+        } else if self.starts_with("//!") {
+            true
+        } else {
+            return false;
+        };
+        if self.docs.as_ref().is_some_and(|(_, kind)| *kind != inner) {
+            self.flush_docs();
+        }
+
+        let end = self.line_end(self.position);
+        let mut start = self.position + 3;
+        if self.bytes.get(start) == Some(&b' ') {
+            start += 1;
+        }
+        let start = start.min(end);
+        self.docs
+            .get_or_insert_with(|| (Vec::new(), inner))
+            .0
+            .push(start..end);
+        self.position = end;
+        true
+    }
+
+    fn flush_docs(&mut self) {
+        if let Some((lines, _)) = self.docs.take() {
+            self.comments.push(Comment::Docs(lines));
+        }
+    }
+
+    fn token(&mut self, byte: u8) {
+        match (self.language, byte) {
+            (Language::Python | Language::Shell, b'#') => self.hash_comment(),
+            (Language::Python | Language::Shell, _) => self.script_token(byte),
+            (_, b'/') if self.starts_with("//") => {
+                let end = self.line_end(self.position);
+                self.comments.push(Comment::Text(self.position + 2..end));
+                self.position = end;
+            }
+            (_, b'/') if self.starts_with("/*") => self.block_comment(),
+            (Language::Rust, b'\'') => self.rust_char(),
+            (Language::Rust, b'r' | b'b') if self.rust_raw_string() => {}
+            (_, b'"') => self.string(b'"', self.language == Language::Rust),
+            (_, b'\'') => self.string(b'\'', false),
+            (Language::Go, b'`') => self.raw_string(b'`'),
+            (Language::JavaScript | Language::TypeScript, b'`') => self.string(b'`', true),
+            _ => self.position += 1,
+        }
+    }
+
+    fn hash_comment(&mut self) {
+        // `$#` and `${#array[@]}` aren't comments in shell scripts
+        let word_start = self.position == 0
+            || self.language == Language::Python
+            || matches!(
+                self.bytes[self.position - 1],
+                b' ' | b'\t' | b'\n' | b';' | b'|' | b'&' | b'(' | b')'
+            );
+        if !word_start {
+            self.position += 1;
+            return;
+        }
+        let end = self.line_end(self.position);
+        self.comments.push(Comment::Text(self.position + 1..end));
+        self.position = end;
+    }
+
+    fn script_token(&mut self, byte: u8) {
+        match byte {
+            b'"' | b'\'' if self.language == Language::Python => {
+                let quotes = if byte == b'"' { "\"\"\"" } else { "'''" };
+                if self.starts_with(quotes) {
+                    self.docstring(quotes);
+                } else {
+                    self.string(byte, false);
+                }
+            }
+            b'"' => self.string(b'"', true),
+            b'\'' => self.raw_string(b'\''),
+            _ => self.position += 1,
+        }
+    }
+
+    /// Handle a Python triple-quoted string, which is a docstring if it
+    /// starts a statement
+    fn docstring(&mut self, quotes: &str) {
+        let is_docstring = self.input[..self.position]
+            .trim_end_matches(['r', 'R', 'u', 'U'])
+            .rsplit('\n')
+            .next()
+            .is_some_and(|line| line.trim().is_empty());
+        let start = self.position + 3;
+        let end = self.input[start..]
+            .find(quotes)
+            .map_or(self.input.len(), |end| start + end);
+        if is_docstring {
+            self.comments.push(Comment::Text(start..end));
+        }
+        self.position = (end + 3).min(self.input.len());
+    }
+
+    fn block_comment(&mut self) {
+        let start = self.position + 2;
+        let nested = self.language == Language::Rust;
+        let mut depth = 1;
+        let mut position = start;
+        let mut end = self.input.len();
+        while position < self.bytes.len() {
+            if self.bytes[position..].starts_with(b"*/") {
+                depth -= 1;
+                if depth == 0 {
+                    end = position;
+                    break;
+                }
+                position += 2;
+            } else if nested && self.bytes[position..].starts_with(b"/*") {
+                depth += 1;
+                position += 2;
+            } else {
+                position += 1;
+            }
+        }
+        self.position = (end + 2).min(self.input.len());
+
+        let body = &self.input[start..end];
+        // `/**/` and `/***` aren't doc comments
+        let is_docs = self.language == Language::Rust
+            && (body.starts_with('!') || (body.starts_with('*') && !body.starts_with("**")));
+        if is_docs {
+            let mut offset = start + 1;
+            let lines = self.input[offset..end]
+                .split('\n')
+                .map(|line| {
+                    let range = offset..offset + line.len();
+                    offset += line.len() + 1;
+                    range
+                })
+                .collect();
+            self.comments.push(Comment::Docs(lines));
+        } else {
+            self.comments.push(Comment::Text(start..end));
+        }
+    }
+
+    /// Skip a string literal with backslash escapes.
+    ///
+    /// Strings which can't span multiple lines end at the end of the line,
+    /// so that unknown syntax doesn't hide the rest of the file.
+    fn string(&mut self, quote: u8, multiline: bool) {
+        let mut position = self.position + 1;
+        while let Some(&byte) = self.bytes.get(position) {
+            match byte {
+                b'\\' => position += 2,
+                b'\n' if !multiline => break,
+                _ if byte == quote => {
+                    position += 1;
+                    break;
+                }
+                _ => position += 1,
+            }
+        }
+        self.position = position.min(self.input.len());
+    }
+
+    /// Skip a string literal without escapes
+    fn raw_string(&mut self, quote: u8) {
+        let start = self.position + 1;
+        self.position = self.bytes[start..]
+            .iter()
+            .position(|&byte| byte == quote)
+            .map_or(self.input.len(), |end| start + end + 1);
+    }
+
+    /// Skip a Rust character literal, or a lifetime like `'a`
+    fn rust_char(&mut self) {
+        let rest = &self.input[self.position + 1..];
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some('\\'), _) => self.string(b'\'', false),
+            (Some(c), Some('\'')) => self.position += 1 + c.len_utf8() + 1,
+            _ => self.position += 1,
+        }
+    }
+
+    /// Skip a Rust raw string like `r#"..."#` or `br"..."`
+    fn rust_raw_string(&mut self) -> bool {
+        if self.position > 0 {
+            let previous = self.bytes[self.position - 1];
+            if previous.is_ascii_alphanumeric() || previous == b'_' {
+                return false;
+            }
+        }
+        let rest = &self.input[self.position..];
+        let Some(rest) = rest.strip_prefix('b').unwrap_or(rest).strip_prefix('r') else {
+            return false;
+        };
+        let hashes = rest.len() - rest.trim_start_matches('#').len();
+        let Some(body) = rest[hashes..].strip_prefix('"') else {
+            return false;
+        };
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        let start = self.input.len() - body.len();
+        self.position = body
+            .find(&terminator)
+            .map_or(self.input.len(), |end| start + end + terminator.len());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn extract(input: &str, language: Language) -> Vec<(String, String)> {
+        extract_source_code(input, language, false, false)
+            .into_iter()
+            .map(|uri| (uri.text, uri.span.to_string()))
+            .collect()
+    }
+
+    fn link(text: &str, span: &str) -> (String, String) {
+        (text.into(), span.into())
+    }
+
+    #[test]
+    fn test_rust() {
+        let input = r##"//! Crate docs, see [the book](https://example.com/book).
+//!
+//! ```
+//! let url = "https://example.com/doctest";
+//! ```
+
+/// Creates a [`Client`](crate::Client) like [`vec!`] and [new](Self::new).
+/// More at <https://example.com/client>.
+fn client() {
+    // https://example.com/comment
+    let url = "https://example.com/string // not a comment";
+    let raw = r#"https://example.com/raw "quoted" // raw"#;
+    let c = '"'; // https://example.com/after-char
+    fn f<'a>(x: &'a str) {} /* https://example.com/block */
+}
+
+/** Block docs with a [link](https://example.com/block-docs). */
+struct S;
+"##;
+        assert_eq!(
+            extract(input, Language::Rust),
+            [
+                link("https://example.com/book", "1:21"),
+                link("https://example.com/client", "8:14"),
+                link("https://example.com/comment", "10:8"),
+                link("https://example.com/after-char", "13:21"),
+                link("https://example.com/block", "14:32"),
+                link("https://example.com/block-docs", "17:23"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python() {
+        let input = r##""""Module docs: https://example.com/module"""
+
+URL = "https://example.com/string"  # https://example.com/comment
+TEMPLATE = """
+https://example.com/template
+"""
+
+
+def f():
+    '''See https://example.com/function'''
+    return "#not-a-comment https://example.com/hash"
+"##;
+        assert_eq!(
+            extract(input, Language::Python),
+            [
+                link("https://example.com/module", "1:17"),
+                link("https://example.com/comment", "3:39"),
+                link("https://example.com/function", "10:12"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_c_family() {
+        let input = r#"/**
+ * Javadoc with <a href="https://example.com/javadoc">a link</a>.
+ */
+const url = "https://example.com/string"; // https://example.com/line
+const tpl = `https://example.com/${path} // not a comment`;
+const c = '//'; /* https://example.com/block */
+"#;
+        for language in [Language::JavaScript, Language::Java, Language::C] {
+            assert_eq!(
+                extract(input, language),
+                [
+                    link("https://example.com/javadoc", "2:26"),
+                    link("https://example.com/line", "4:46"),
+                    link("https://example.com/block", "6:20"),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_go_raw_string() {
+        let input = "var s = `https://example.com/raw // raw`\n// https://example.com/comment\n";
+        assert_eq!(
+            extract(input, Language::Go),
+            [link("https://example.com/comment", "2:4")]
+        );
+    }
+
+    #[test]
+    fn test_shell() {
+        let input = r#"#!/bin/sh
+# Install from https://example.com/install
+curl "https://example.com/script.sh#fragment" | sh
+echo ${#array[@]} $# 'https://example.com/quoted' # https://example.com/comment
+"#;
+        assert_eq!(
+            extract(input, Language::Shell),
+            [
+                link("https://example.com/install", "2:16"),
+                link("https://example.com/comment", "4:53"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Inline comments which exclude links from being checked.
+//!
+//! Authors can suppress links in HTML and Markdown documents with the
+//! following comments:
+//!
+//! - `<!-- lychee-ignore -->` ignores all links on the same line
+//! - `<!-- lychee-ignore-next-line -->` ignores all links on the next line
+//! - `<!-- lychee-disable -->` ignores all links until the next
+//!   `<!-- lychee-enable -->` (or the end of the document)
+//!
+//! Suppressions work on lines, because that's the granularity of the spans
+//! available for all extracted links.
+use std::{collections::HashSet, ops::RangeInclusive};
+
+use crate::types::uri::raw::{RawUri, SpanProvider};
+
+/// A comment which excludes links from being checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Directive {
+    /// Ignore links on the line of the comment
+    Ignore,
+    /// Ignore links on the line following the comment
+    IgnoreNextLine,
+    /// Ignore links until the next [`Directive::Enable`]
+    Disable,
+    /// Stop ignoring links after a [`Directive::Disable`]
+    Enable,
+}
+
+impl Directive {
+    /// Parse the text inside of an HTML comment, i.e. without `<!--` and `-->`
+    pub(crate) fn parse(comment: &str) -> Option<Self> {
+        match comment.trim() {
+            "lychee-ignore" => Some(Self::Ignore),
+            "lychee-ignore-next-line" => Some(Self::IgnoreNextLine),
+            "lychee-disable" => Some(Self::Disable),
+            "lychee-enable" => Some(Self::Enable),
+            _ => None,
+        }
+    }
+}
+
+/// The lines of a document on which links are suppressed by [`Directive`]s
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Suppressions {
+    /// Lines suppressed by `lychee-ignore` and `lychee-ignore-next-line`
+    lines: HashSet<usize>,
+    /// Lines between `lychee-disable` and `lychee-enable`
+    ranges: Vec<RangeInclusive<usize>>,
+    /// Line of a `lychee-disable` which wasn't followed by `lychee-enable` yet
+    disabled_since: Option<usize>,
+}
+
+impl Suppressions {
+    /// Record a directive found on the given line.
+    /// Directives must be added in the order of their appearance.
+    pub(crate) fn add(&mut self, directive: Directive, line: usize) {
+        match directive {
+            Directive::Ignore => {
+                self.lines.insert(line);
+            }
+            Directive::IgnoreNextLine => {
+                self.lines.insert(line + 1);
+            }
+            Directive::Disable => {
+                self.disabled_since.get_or_insert(line);
+            }
+            Directive::Enable => {
+                if let Some(start) = self.disabled_since.take() {
+                    self.ranges.push(start..=line);
+                }
+            }
+        }
+    }
+
+    /// Record all directives in the HTML comments of a snippet of HTML.
+    ///
+    /// This is meant for HTML that isn't tokenized as a whole, such as the
+    /// HTML fragments of a Markdown document.
+    pub(crate) fn add_from_html(&mut self, html: &str, span_provider: &impl SpanProvider) {
+        let mut offset = 0;
+        while let Some(start) = html[offset..].find("<!--").map(|start| offset + start) {
+            let text_start = start + "<!--".len();
+            let Some(len) = html[text_start..].find("-->") else {
+                break;
+            };
+            if let Some(directive) = Directive::parse(&html[text_start..text_start + len]) {
+                self.add(directive, span_provider.span(start).line.get());
+            }
+            offset = text_start + len;
+        }
+    }
+
+    fn is_suppressed(&self, line: usize) -> bool {
+        self.lines.contains(&line)
+            || self.ranges.iter().any(|range| range.contains(&line))
+            || self.disabled_since.is_some_and(|start| line >= start)
+    }
+
+    /// Remove all links on suppressed lines
+    pub(crate) fn filter(&self, links: Vec<RawUri>) -> Vec<RawUri> {
+        if self.lines.is_empty() && self.ranges.is_empty() && self.disabled_since.is_none() {
+            return links;
+        }
+        links
+            .into_iter()
+            .filter(|link| !self.is_suppressed(link.span.line.get()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::uri::raw::SourceSpanProvider;
+
+    #[test]
+    fn test_parse_directive() {
+        assert_eq!(Directive::parse(" lychee-ignore "), Some(Directive::Ignore));
+        assert_eq!(
+            Directive::parse("lychee-ignore-next-line"),
+            Some(Directive::IgnoreNextLine)
+        );
+        assert_eq!(
+            Directive::parse("\nlychee-disable\n"),
+            Some(Directive::Disable)
+        );
+        assert_eq!(Directive::parse("lychee-enable"), Some(Directive::Enable));
+        assert_eq!(Directive::parse("lychee-ignore this"), None);
+        assert_eq!(Directive::parse("a regular comment"), None);
+    }
+
+    #[test]
+    fn test_suppressed_lines() {
+        let mut suppressions = Suppressions::default();
+        suppressions.add(Directive::Ignore, 2);
+        suppressions.add(Directive::IgnoreNextLine, 4);
+        suppressions.add(Directive::Disable, 7);
+        suppressions.add(Directive::Enable, 9);
+        suppressions.add(Directive::Enable, 11);
+        suppressions.add(Directive::Disable, 13);
+
+        let suppressed: Vec<usize> = (1..=15)
+            .filter(|&l| suppressions.is_suppressed(l))
+            .collect();
+        assert_eq!(suppressed, [2, 5, 7, 8, 9, 13, 14, 15]);
+    }
+
+    #[test]
+    fn test_add_from_html() {
+        let html = "<!-- lychee-ignore-next-line -->\n<p>\n<!-- comment --><!--lychee-ignore-->";
+        let mut suppressions = Suppressions::default();
+        suppressions.add_from_html(html, &SourceSpanProvider::from_input(html));
+
+        assert!(suppressions.is_suppressed(2));
+        assert!(suppressions.is_suppressed(3));
+        assert!(!suppressions.is_suppressed(1));
+    }
+}
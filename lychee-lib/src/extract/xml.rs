@@ -1,18 +1,186 @@
-//! Extract links from XML documents. Currently supports sitemaps, RSS and Atom feeds.
+//! Extract links from XML documents. Currently supports sitemaps, RSS and Atom feeds, and SVG.
+//! Links of other XML dialects can be extracted with [`XmlSelector`]s.
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
 use log::warn;
 use quick_xml::Reader;
-use quick_xml::events::Event;
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::css::extract_css;
+use crate::types::uri::raw::{OffsetSpanProvider, RawUri, RawUriSpan, SpanProvider};
+
+/// SVG elements which link to other resources with `href` or `xlink:href`
+const SVG_LINK_ELEMENTS: &[&[u8]] = &[b"a", b"image", b"use", b"feImage"];
+
+/// Errors of parsing an [`XmlSelector`]
+#[derive(Copy, Clone, Debug, Error, PartialEq, Eq)]
+pub enum XmlSelectorParseError {
+    /// The element name is empty
+    #[error("Empty XML selector element. Valid form is '<element>' or '<element>@<attribute>'")]
+    EmptyElement,
+
+    /// The attribute name after `@` is empty
+    #[error("Empty XML selector attribute. Valid form is '<element>' or '<element>@<attribute>'")]
+    EmptyAttribute,
+}
+
+/// [`XmlSelector`] selects the links of XML elements, either from the text
+/// of an element or from one of its attributes.
+///
+/// Elements match by their qualified name, like `xi:include`, or by their
+/// local name without namespace prefix, like `ulink`.
+/// Selected elements aren't checked for the links of built-in formats
+/// like sitemaps or SVG.
+///
+/// Selectors can be parsed from strings like `ulink@url` for the `url`
+/// attribute of `<ulink>` elements, or `uri` for the text of `<uri>`
+/// elements.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "RawXmlSelector")]
+pub struct XmlSelector {
+    /// Name of the selected elements
+    pub element: String,
+    /// Attribute with the link, or `None` if the text of the element is the link
+    pub attribute: Option<String>,
+}
+
+/// Selectors can be configured as string or as table
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawXmlSelector {
+    Short(String),
+    Full(FullXmlSelector),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FullXmlSelector {
+    element: String,
+    attribute: Option<String>,
+}
 
-use crate::types::uri::raw::{RawUri, SpanProvider};
+impl TryFrom<RawXmlSelector> for XmlSelector {
+    type Error = XmlSelectorParseError;
 
-/// Extract unparsed URL strings from common XML formats, like sitemap.xml, RSS feeds, or Atom feeds.
-pub(crate) fn extract_xml<S: SpanProvider>(input: &str, span_provider: &S) -> Vec<RawUri> {
+    fn try_from(raw: RawXmlSelector) -> Result<Self, Self::Error> {
+        match raw {
+            RawXmlSelector::Short(selector) => selector.parse(),
+            RawXmlSelector::Full(FullXmlSelector { element, attribute }) => {
+                if element.trim().is_empty() {
+                    return Err(XmlSelectorParseError::EmptyElement);
+                }
+                if attribute.as_ref().is_some_and(|a| a.trim().is_empty()) {
+                    return Err(XmlSelectorParseError::EmptyAttribute);
+                }
+                Ok(Self { element, attribute })
+            }
+        }
+    }
+}
+
+impl FromStr for XmlSelector {
+    type Err = XmlSelectorParseError;
+
+    fn from_str(selector: &str) -> Result<Self, Self::Err> {
+        let (element, attribute) = match selector.trim().split_once('@') {
+            Some((element, attribute)) => (element, Some(attribute)),
+            None => (selector.trim(), None),
+        };
+        if element.is_empty() {
+            return Err(XmlSelectorParseError::EmptyElement);
+        }
+        if attribute.is_some_and(str::is_empty) {
+            return Err(XmlSelectorParseError::EmptyAttribute);
+        }
+        Ok(Self {
+            element: element.to_string(),
+            attribute: attribute.map(ToString::to_string),
+        })
+    }
+}
+
+impl Display for XmlSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.attribute {
+            Some(attribute) => write!(f, "{}@{attribute}", self.element),
+            None => write!(f, "{}", self.element),
+        }
+    }
+}
+
+impl XmlSelector {
+    fn matches(&self, element: &BytesStart) -> bool {
+        let name = self.element.as_bytes();
+        element.name().as_ref() == name || element.local_name().as_ref() == name
+    }
+}
+
+/// Extract unparsed URL strings from common XML formats, like sitemap.xml, RSS feeds, Atom feeds,
+/// or SVG images, and from the elements selected by the given [`XmlSelector`]s.
+pub(crate) fn extract_xml<S: SpanProvider>(
+    input: &str,
+    span_provider: &S,
+    selectors: &[XmlSelector],
+) -> Vec<RawUri> {
     let mut reader = Reader::from_str(input);
 
     let mut uris: Vec<RawUri> = Vec::new();
+    // Whether the root element is `<svg>`. Many images have no links at all.
+    let mut is_svg = None;
 
     loop {
-        match reader.read_event().unwrap() {
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("Cannot parse XML completely, some links may be missing: {e}");
+                return uris;
+            }
+        };
+        if is_svg.is_none()
+            && let Event::Start(e) | Event::Empty(e) = &event
+        {
+            is_svg = Some(e.local_name().as_ref() == b"svg");
+        }
+        match event {
+            Event::Start(e) if selectors.iter().any(|selector| selector.matches(&e)) => {
+                let end_of_tag: usize = reader.buffer_position().try_into().unwrap_or_default();
+                extract_selected_attributes(
+                    &e,
+                    selectors,
+                    span_provider.span(end_of_tag),
+                    &mut uris,
+                );
+
+                let selects_text = selectors
+                    .iter()
+                    .any(|selector| selector.attribute.is_none() && selector.matches(&e));
+                if selects_text {
+                    let text = reader.read_text(e.name()).unwrap_or_default();
+                    let leading_whitespace = text.len() - text.trim_start().len();
+                    let text = unescape_or_raw(text.trim());
+                    if !text.is_empty() {
+                        uris.push(RawUri {
+                            text,
+                            element: Some(String::from_utf8_lossy(e.name().as_ref()).into_owned()),
+                            attribute: None,
+                            span: span_provider.span(end_of_tag + leading_whitespace),
+                        });
+                    }
+                }
+            }
+            Event::Empty(e) if selectors.iter().any(|selector| selector.matches(&e)) => {
+                let end_of_empty_tag: usize =
+                    reader.buffer_position().try_into().unwrap_or_default();
+                extract_selected_attributes(
+                    &e,
+                    selectors,
+                    span_provider.span(end_of_empty_tag),
+                    &mut uris,
+                );
+            }
             Event::Start(e) => match e.name().as_ref() {
                 b"loc" /* sitemap */ | b"link" /* RSS */ => {
                     let start_of_text_offset: usize = reader.buffer_position().try_into().unwrap_or_default();
@@ -29,47 +197,108 @@ pub(crate) fn extract_xml<S: SpanProvider>(input: &str, span_provider: &S) -> Ve
                         });
                     }
                 },
+                b"style" /* SVG */ => {
+                    let start_of_text_offset: usize = reader.buffer_position().try_into().unwrap_or_default();
+                    let css = reader.read_text(e.name()).unwrap_or_default();
+                    uris.extend(extract_css(&css, &OffsetSpanProvider {
+                        offset: start_of_text_offset,
+                        inner: span_provider,
+                    }));
+                },
+                _ if is_svg_link(&e) => {
+                    let end_of_tag: usize = reader.buffer_position().try_into().unwrap_or_default();
+                    extract_href(&e, span_provider.span(end_of_tag), &mut uris);
+                },
                 _ => {}
             },
-            Event::Empty(e) if e.name().as_ref() == b"link" => {
-                for attr in e.attributes().flatten() {
-                    if attr.key.as_ref() == b"href" {
-                        let text = std::str::from_utf8(attr.value.as_ref())
-                            .unwrap_or("")
-                            .to_string();
-                        let element = std::str::from_utf8(e.name().as_ref())
-                            .unwrap_or("")
-                            .to_string();
-                        let end_of_empty_tag: usize =
-                            reader.buffer_position().try_into().unwrap_or_default();
-                        // Span is a bit imprecise, as it points to the end of the element. However, quick_xml does not provide the position of attributes, so this is the best we can do.
-                        let span = span_provider.span(end_of_empty_tag);
-
-                        if !text.is_empty() && !element.is_empty() {
-                            uris.push(RawUri {
-                                text,
-                                element: Some(element),
-                                attribute: Some("href".to_string()),
-                                span,
-                            });
-                        }
-                    }
-                }
+            Event::Empty(e) if e.name().as_ref() == b"link" || is_svg_link(&e) => {
+                let end_of_empty_tag: usize =
+                    reader.buffer_position().try_into().unwrap_or_default();
+                extract_href(&e, span_provider.span(end_of_empty_tag), &mut uris);
             }
             Event::Eof => break,
             _ => {}
         }
     }
 
-    if uris.is_empty() {
+    if uris.is_empty() && is_svg != Some(true) {
         warn!(
-            "No URLs found in XML input. Currently, lychee only supports extracting URLs from sitemaps, RSS and Atom feeds. If your XML contains links in a different format, please consider submitting a feature request or contributing support for additional XML formats."
+            "No URLs found in XML input. Currently, lychee only supports extracting URLs from sitemaps, RSS and Atom feeds, and SVG. If your XML contains links in a different format, select the elements or attributes with the links using `--xml-selector`."
         );
     }
 
     uris
 }
 
+fn is_svg_link(element: &BytesStart) -> bool {
+    SVG_LINK_ELEMENTS.contains(&element.local_name().as_ref())
+}
+
+/// Extract the attributes of the given element which are selected by any of the given selectors.
+///
+/// As for `href` attributes, the span points to the end of the element.
+fn extract_selected_attributes(
+    element: &BytesStart,
+    selectors: &[XmlSelector],
+    span: RawUriSpan,
+    uris: &mut Vec<RawUri>,
+) {
+    let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+    for attr in element.attributes().flatten() {
+        let key = String::from_utf8_lossy(attr.key.as_ref());
+        let selected = selectors.iter().any(|selector| {
+            selector.attribute.as_deref() == Some(&key) && selector.matches(element)
+        });
+        if !selected {
+            continue;
+        }
+        let text = unescape_or_raw(&String::from_utf8_lossy(attr.value.as_ref()));
+        if !text.is_empty() {
+            uris.push(RawUri {
+                text,
+                element: Some(name.clone()),
+                attribute: Some(key.into_owned()),
+                span,
+            });
+        }
+    }
+}
+
+/// Resolve entities like `&amp;`, keeping the text as is if it's malformed
+fn unescape_or_raw(text: &str) -> String {
+    unescape(text).map_or_else(|_| text.to_string(), Cow::into_owned)
+}
+
+/// Extract the `href` and `xlink:href` attributes of the given element.
+///
+/// The span is a bit imprecise, as it points to the end of the element. However, `quick_xml` does
+/// not provide the position of attributes, so this is the best we can do.
+fn extract_href(element: &BytesStart, span: RawUriSpan, uris: &mut Vec<RawUri>) {
+    let name = std::str::from_utf8(element.name().as_ref())
+        .unwrap_or("")
+        .to_string();
+    for attr in element.attributes().flatten() {
+        let (b"href" | b"xlink:href") = attr.key.as_ref() else {
+            continue;
+        };
+        let text = std::str::from_utf8(attr.value.as_ref())
+            .unwrap_or("")
+            .to_string();
+        let attribute = std::str::from_utf8(attr.key.as_ref())
+            .unwrap_or("")
+            .to_string();
+
+        if !text.is_empty() && !name.is_empty() {
+            uris.push(RawUri {
+                text,
+                element: Some(name.clone()),
+                attribute: Some(attribute),
+                span,
+            });
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::types::uri::raw::{SourceSpanProvider, span};
@@ -77,7 +306,7 @@ mod tests {
     use super::*;
 
     fn extract(input: &str) -> Vec<RawUri> {
-        extract_xml(input, &SourceSpanProvider::from_input(input))
+        extract_xml(input, &SourceSpanProvider::from_input(input), &[])
     }
 
     #[test]
@@ -202,4 +431,142 @@ mod tests {
 
         assert_eq!(uris, expected);
     }
+
+    #[test]
+    fn test_extract_svg_links() {
+        let input = r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <style>
+    @font-face { src: url("https://example.com/font.woff2"); }
+  </style>
+  <a href="https://example.com/a"><text>Link</text></a>
+  <image xlink:href="images/photo.png" />
+  <use href="#icon" />
+  <filter><feImage href="https://example.com/texture.png" /></filter>
+  <rect fill="red" />
+</svg>"##;
+
+        let links: Vec<_> = extract(input)
+            .into_iter()
+            .map(|uri| (uri.text, uri.element, uri.attribute, uri.span))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (
+                    "https://example.com/font.woff2".to_string(),
+                    Some("style".to_string()),
+                    Some("url".to_string()),
+                    span(3, 23)
+                ),
+                (
+                    "https://example.com/a".to_string(),
+                    Some("a".to_string()),
+                    Some("href".to_string()),
+                    span(5, 35)
+                ),
+                (
+                    "images/photo.png".to_string(),
+                    Some("image".to_string()),
+                    Some("xlink:href".to_string()),
+                    span(6, 42)
+                ),
+                (
+                    "#icon".to_string(),
+                    Some("use".to_string()),
+                    Some("href".to_string()),
+                    span(7, 23)
+                ),
+                (
+                    "https://example.com/texture.png".to_string(),
+                    Some("feImage".to_string()),
+                    Some("href".to_string()),
+                    span(8, 61)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_malformed_svg_links() {
+        let input =
+            r#"<svg><a href="https://example.com/a"></g><a href="https://example.com/b"/></svg>"#;
+
+        let links: Vec<_> = extract(input).into_iter().map(|uri| uri.text).collect();
+        assert_eq!(links, ["https://example.com/a"]);
+    }
+
+    #[test]
+    fn test_parse_xml_selector() {
+        assert_eq!(
+            "ulink@url".parse(),
+            Ok(XmlSelector {
+                element: "ulink".to_string(),
+                attribute: Some("url".to_string()),
+            })
+        );
+        assert_eq!(
+            " uri ".parse(),
+            Ok(XmlSelector {
+                element: "uri".to_string(),
+                attribute: None,
+            })
+        );
+        assert_eq!(
+            "@url".parse::<XmlSelector>(),
+            Err(XmlSelectorParseError::EmptyElement)
+        );
+        assert_eq!(
+            "ulink@".parse::<XmlSelector>(),
+            Err(XmlSelectorParseError::EmptyAttribute)
+        );
+    }
+
+    #[test]
+    fn test_extract_selected_links() {
+        let input = r#"<book xmlns:xi="http://www.w3.org/2001/XInclude">
+  <para>See <ulink url="https://example.com/manual?a=1&amp;b=2">the manual</ulink>.</para>
+  <xi:include href="chapter.xml" />
+  <uri> https://example.com/uri </uri>
+  <link>https://example.com/not-selected</link>
+</book>"#;
+        let selectors = [
+            "ulink@url".parse().unwrap(),
+            "include@href".parse().unwrap(),
+            "uri".parse().unwrap(),
+        ];
+
+        let links: Vec<_> = extract_xml(input, &SourceSpanProvider::from_input(input), &selectors)
+            .into_iter()
+            .map(|uri| (uri.text, uri.element, uri.attribute, uri.span))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (
+                    "https://example.com/manual?a=1&b=2".to_string(),
+                    Some("ulink".to_string()),
+                    Some("url".to_string()),
+                    span(2, 65)
+                ),
+                (
+                    "chapter.xml".to_string(),
+                    Some("xi:include".to_string()),
+                    Some("href".to_string()),
+                    span(3, 36)
+                ),
+                (
+                    "https://example.com/uri".to_string(),
+                    Some("uri".to_string()),
+                    None,
+                    span(4, 9)
+                ),
+                (
+                    "https://example.com/not-selected".to_string(),
+                    Some("link".to_string()),
+                    None,
+                    span(5, 9)
+                ),
+            ]
+        );
+    }
 }
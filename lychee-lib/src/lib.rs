@@ -61,6 +61,7 @@ mod checker;
 mod client;
 /// A pool of clients, to handle concurrent checks
 pub mod collector;
+pub mod crawl;
 mod quirks;
 mod retry;
 mod types;
@@ -90,6 +91,8 @@ pub use crate::{
     basic_auth::BasicAuthExtractor,
     // Expose the `Handler` trait to allow defining external handlers (plugins)
     chain::{ChainResult, Handler},
+    // Expose the `Checker` trait to allow checking custom URI schemes
+    checker::{Checker, Checkers},
     // Constants get exposed so that the CLI can use the same defaults as the library
     client::{
         Client, ClientBuilder, DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_RETRIES,
@@ -97,13 +100,14 @@ pub use crate::{
         FragmentCheckerOptions, check,
     },
     collector::Collector,
+    crawl::{CrawlScope, Crawler},
     filter::{Excludes, Filter, Includes},
     remap::Remap,
     types::{
         BaseInfo, BasicAuthCredentials, BasicAuthSelector, CacheStatus, CookieJar, ErrorKind,
-        FileExtensions, FileType, Input, InputContent, InputResolver, InputSource, LycheeResult,
-        Preprocessor, Redirect, Redirects, Request, RequestError, ResolvedInputSource, Response,
-        ResponseBody, Result, Status, StatusCodeSelector, StatusRange, StatusRangeError,
-        uri::raw::RawUri, uri::raw::RawUriSpan, uri::valid::Uri,
+        FileExtensions, FileType, Input, InputContent, InputResolver, InputSource, Language,
+        LycheeResult, Preprocessor, Redirect, Redirects, Request, RequestError,
+        ResolvedInputSource, Response, ResponseBody, Result, Status, StatusCodeSelector,
+        StatusRange, StatusRangeError, uri::raw::RawUri, uri::raw::RawUriSpan, uri::valid::Uri,
     },
 };
@@ -11,16 +11,19 @@
 //! - [`HostPool`]: Coordinates multiple hosts and routes requests appropriately
 //! - [`HostConfig`]: Configuration for per-host behavior
 //! - [`HostStats`]: Statistics tracking for each host
+//! - [`Recording`]: Recorded HTTP responses, which can be replayed without network access
 
 mod config;
 mod headers;
 mod host;
 mod pool;
+mod recording;
 
 pub use config::{HostConfig, HostConfigs, RateLimitConfig};
 pub use host::{Host, HostKey, HostStats, HostStatsMap};
 use http::HeaderMap;
 pub use pool::{ClientMap, HostPool};
+pub use recording::Recording;
 use reqwest::Response;
 use url::Url;
 
@@ -6,8 +6,9 @@ use std::sync::Arc;
 
 use crate::ratelimit::{
     CacheableResponse, Host, HostConfigs, HostKey, HostStats, HostStatsMap, RateLimitConfig,
+    Recording,
 };
-use crate::types::Result;
+use crate::types::{Result, redirect_history::RedirectHistory};
 use crate::{ErrorKind, Uri};
 
 /// Keep track of host-specific [`reqwest::Client`]s
@@ -40,6 +41,10 @@ pub struct HostPool {
 
     /// Host-specific clients
     client_map: ClientMap,
+
+    /// Responses to record or replay, along with the redirect history
+    /// of the clients, which tracks the redirects of each request
+    recording: Option<(Arc<Recording>, RedirectHistory)>,
 }
 
 impl HostPool {
@@ -57,9 +62,22 @@ impl HostPool {
             host_configs,
             default_client,
             client_map,
+            recording: None,
         }
     }
 
+    /// Record all responses in the given [`Recording`],
+    /// or serve them from it if it is replayed
+    #[must_use]
+    pub(crate) fn with_recording(
+        mut self,
+        recording: Arc<Recording>,
+        redirect_history: RedirectHistory,
+    ) -> Self {
+        self.recording = Some((recording, redirect_history));
+        self
+    }
+
     /// Try to execute a [`Request`] with appropriate per-host rate limiting.
     ///
     /// # Errors
@@ -67,10 +85,43 @@ impl HostPool {
     /// Fails if:
     /// - The request URL has no valid hostname
     /// - The underlying HTTP request fails
+    /// - A recording is replayed and the response was not recorded
     pub(crate) async fn execute_request(
         &self,
         request: Request,
         needs_body: bool,
+    ) -> Result<CacheableResponse> {
+        let Some((recording, redirect_history)) = &self.recording else {
+            return self.execute_host_request(request, needs_body).await;
+        };
+
+        let method = request.method().clone();
+        let url = request.url().clone();
+        if recording.is_replay() {
+            match recording.get(&method, &url, needs_body) {
+                Ok((response, redirects)) => {
+                    redirect_history.insert(redirects);
+                    return Ok(response);
+                }
+                Err(e) if !recording.is_record() => return Err(e),
+                Err(_) => {}
+            }
+        }
+
+        let response = self.execute_host_request(request, needs_body).await?;
+        recording.add(
+            &method,
+            &url,
+            &response,
+            redirect_history.resolve(&url).as_ref(),
+        );
+        Ok(response)
+    }
+
+    async fn execute_host_request(
+        &self,
+        request: Request,
+        needs_body: bool,
     ) -> Result<CacheableResponse> {
         let url = request.url();
         let host_key = HostKey::try_from(url)?;
@@ -0,0 +1,351 @@
+//! Recording and replaying of HTTP responses.
+//!
+//! A [`Recording`] in record mode remembers every response which passes
+//! through the [`HostPool`](super::HostPool), including its headers, body (if
+//! it was fetched) and redirect chain. Saved to a file, the recording can be
+//! replayed later: all responses are then served from the recording instead
+//! of the network. This makes runs deterministic, e.g. to reproduce a failed
+//! CI run locally or to check links in sandboxed builds without network access.
+//!
+//! Both can be combined to replay the known responses and record the missing
+//! ones, which updates an existing recording.
+
+use std::{fs, path::Path};
+
+use dashmap::DashMap;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::CacheableResponse;
+use crate::{
+    ErrorKind, Result, Uri,
+    types::{Redirect, Redirects},
+};
+
+/// Requests are identified by their method and their URL without fragment
+type RequestKey = (String, Url);
+
+/// HTTP responses which are recorded or replayed by the
+/// [`HostPool`](super::HostPool)
+#[derive(Debug)]
+pub struct Recording {
+    /// Serve the recorded responses instead of sending requests
+    replay: bool,
+    /// Remember the responses to all requests which are sent
+    record: bool,
+    responses: DashMap<RequestKey, RecordedResponse>,
+}
+
+/// A request and its response as stored in the recording file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RecordedExchange {
+    method: String,
+    url: Url,
+    #[serde(flatten)]
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    /// URL of the response after following all redirects
+    final_url: Url,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    redirects: Vec<RecordedRedirect>,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    /// Only present if the body was needed when the response was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RecordedRedirect {
+    url: Url,
+    status: u16,
+}
+
+impl Recording {
+    /// Create an empty recording, which records all responses
+    #[must_use]
+    pub fn record() -> Self {
+        Self {
+            replay: false,
+            record: true,
+            responses: DashMap::new(),
+        }
+    }
+
+    /// Load a recording from the given file, to replay its responses
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid recording
+    pub fn replay(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ErrorKind::ReadFileInput(e, path.to_path_buf()))?;
+        let exchanges: Vec<RecordedExchange> = serde_json::from_str(&content).map_err(|e| {
+            ErrorKind::Recording(format!("Cannot parse recording {}: {e}", path.display()))
+        })?;
+
+        Ok(Self {
+            replay: true,
+            record: false,
+            responses: exchanges
+                .into_iter()
+                .map(|exchange| ((exchange.method, exchange.url), exchange.response))
+                .collect(),
+        })
+    }
+
+    /// Write all recorded responses to the given file as JSON,
+    /// replacing its contents
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut exchanges: Vec<RecordedExchange> = self
+            .responses
+            .iter()
+            .map(|entry| {
+                let (method, url) = entry.key().clone();
+                RecordedExchange {
+                    method,
+                    url,
+                    response: entry.value().clone(),
+                }
+            })
+            .collect();
+        exchanges.sort_by(|a, b| (&a.url, &a.method).cmp(&(&b.url, &b.method)));
+
+        let json = serde_json::to_string_pretty(&exchanges)
+            .map_err(|e| ErrorKind::Recording(format!("Cannot serialize recording: {e}")))?;
+        fs::write(path, json + "\n").map_err(|e| {
+            ErrorKind::Recording(format!("Cannot write recording {}: {e}", path.display()))
+        })
+    }
+
+    /// Send the requests which are missing from a replayed recording and
+    /// record their responses, instead of failing them
+    #[must_use]
+    pub const fn record_missing(mut self) -> Self {
+        self.record = true;
+        self
+    }
+
+    /// Returns `true` if responses are served from this recording
+    /// instead of the network
+    #[must_use]
+    pub const fn is_replay(&self) -> bool {
+        self.replay
+    }
+
+    /// Returns `true` if responses from the network are added to this recording
+    #[must_use]
+    pub const fn is_record(&self) -> bool {
+        self.record
+    }
+
+    /// Number of recorded responses
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Returns `true` if no responses are recorded
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    /// Remember the response to a request.
+    /// A response without body never replaces a recorded response with body,
+    /// so that the body is available for all requests which need it.
+    pub(crate) fn add(
+        &self,
+        method: &Method,
+        url: &Url,
+        response: &CacheableResponse,
+        redirects: Option<&Redirects>,
+    ) {
+        let recorded = RecordedResponse {
+            status: response.status.as_u16(),
+            final_url: response.url.clone(),
+            redirects: redirects
+                .map(|redirects| {
+                    redirects
+                        .redirects()
+                        .iter()
+                        .map(|redirect| RecordedRedirect {
+                            url: redirect.url.clone(),
+                            status: redirect.code.as_u16(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            headers: response
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body: response.text.clone(),
+        };
+
+        let key = request_key(method, url);
+        if self
+            .responses
+            .get(&key)
+            .is_some_and(|existing| existing.body.is_some() && recorded.body.is_none())
+        {
+            return;
+        }
+        self.responses.insert(key, recorded);
+    }
+
+    /// Look up the recorded response to a request, together with the
+    /// redirects which were followed for it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request was not recorded, or if it was recorded
+    /// without body but the body is needed now.
+    pub(crate) fn get(
+        &self,
+        method: &Method,
+        url: &Url,
+        needs_body: bool,
+    ) -> Result<(CacheableResponse, Redirects)> {
+        let not_recorded = || ErrorKind::NotRecorded(Uri::from(url.clone()));
+        let recorded = self
+            .responses
+            .get(&request_key(method, url))
+            .ok_or_else(not_recorded)?;
+        if needs_body && recorded.body.is_none() {
+            return Err(not_recorded());
+        }
+
+        let status = StatusCode::from_u16(recorded.status)
+            .map_err(|_| ErrorKind::InvalidStatusCode(recorded.status))?;
+
+        let mut redirects = Redirects::new(url.clone());
+        for redirect in &recorded.redirects {
+            redirects.push(Redirect {
+                url: redirect.url.clone(),
+                code: StatusCode::from_u16(redirect.status)
+                    .map_err(|_| ErrorKind::InvalidStatusCode(redirect.status))?,
+            });
+        }
+
+        let headers: HeaderMap = recorded
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect();
+
+        let response = CacheableResponse {
+            status,
+            text: recorded.body.clone(),
+            headers,
+            url: recorded.final_url.clone(),
+        };
+        Ok((response, redirects))
+    }
+}
+
+fn request_key(method: &Method, url: &Url) -> RequestKey {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    (method.to_string(), url)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::CONTENT_TYPE;
+
+    use super::*;
+
+    fn response(body: Option<&str>) -> CacheableResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html"));
+        CacheableResponse {
+            status: StatusCode::OK,
+            text: body.map(String::from),
+            headers,
+            url: Url::parse("https://example.com/new").unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.json");
+        let url = Url::parse("https://example.com/old#section").unwrap();
+
+        let mut redirects = Redirects::new(url.clone());
+        redirects.push(Redirect {
+            url: Url::parse("https://example.com/new").unwrap(),
+            code: StatusCode::MOVED_PERMANENTLY,
+        });
+
+        let recording = Recording::record();
+        recording.add(&Method::GET, &url, &response(Some("<p>")), Some(&redirects));
+        // A later response without body doesn't replace the body
+        recording.add(&Method::GET, &url, &response(None), Some(&redirects));
+        recording.save(&path).unwrap();
+
+        let replay = Recording::replay(&path).unwrap();
+        assert!(replay.is_replay());
+        assert_eq!(replay.len(), 1);
+
+        let other_fragment = Url::parse("https://example.com/old#other").unwrap();
+        let (replayed, replayed_redirects) =
+            replay.get(&Method::GET, &other_fragment, true).unwrap();
+        assert_eq!(replayed.status, StatusCode::OK);
+        assert_eq!(replayed.text.as_deref(), Some("<p>"));
+        assert_eq!(replayed.url.as_str(), "https://example.com/new");
+        assert_eq!(replayed.headers.get(CONTENT_TYPE).unwrap(), "text/html");
+        assert_eq!(replayed_redirects.count(), 1);
+    }
+
+    #[test]
+    fn test_replay_and_record_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.json");
+        std::fs::write(&path, "[]").unwrap();
+
+        let recording = Recording::replay(&path).unwrap();
+        assert!(recording.is_replay() && !recording.is_record());
+        assert!(recording.is_empty());
+
+        let recording = recording.record_missing();
+        assert!(recording.is_replay() && recording.is_record());
+    }
+
+    #[test]
+    fn test_replay_unrecorded_request() {
+        let url = Url::parse("https://example.com/").unwrap();
+        let recording = Recording::record();
+        recording.add(&Method::HEAD, &url, &response(None), None);
+
+        // Different method
+        assert!(matches!(
+            recording.get(&Method::GET, &url, false),
+            Err(ErrorKind::NotRecorded(_))
+        ));
+        // Body needed, but not recorded
+        assert!(matches!(
+            recording.get(&Method::HEAD, &url, true),
+            Err(ErrorKind::NotRecorded(_))
+        ));
+        assert!(recording.get(&Method::HEAD, &url, false).is_ok());
+    }
+}
@@ -105,6 +105,22 @@ impl BaseInfo {
         }
     }
 
+    /// Returns a [`BaseInfo`] which resolves links relative to the given URL
+    /// instead, e.g. for documents with an HTML `<base href>` element.
+    ///
+    /// If the URL lies within the origin of a [`BaseInfo::Full`], that origin
+    /// is kept so that root-relative links still resolve to the same root.
+    /// Otherwise, this behaves like [`BaseInfo::from_source_url`].
+    #[must_use]
+    pub fn rebase(&self, url: &Url) -> Self {
+        if let Self::Full { origin, .. } = self
+            && let Some(path) = url.as_str().strip_prefix(origin.as_str())
+        {
+            return Self::full(origin.clone(), path.to_string());
+        }
+        Self::from_source_url(url)
+    }
+
     /// Split URL into its origin and path, if possible. Will fail and return
     /// `None` for URLs which *cannot be a base*.
     fn split_url_origin_and_path(url: &Url) -> Option<(Url, String)> {
@@ -654,6 +670,33 @@ mod tests {
         assert_eq!(none.or_fallback(&none), &none);
     }
 
+    #[test]
+    fn test_rebase() {
+        let https = BaseInfo::try_from("https://a.com/b/page.html").unwrap();
+        let rebased = https.rebase(&Url::parse("https://a.com/c/").unwrap());
+        assert_eq!(rebased.url().unwrap().as_str(), "https://a.com/c/");
+        assert_eq!(
+            rebased.parse_url_text("/root").unwrap().as_str(),
+            "https://a.com/root"
+        );
+
+        // Root-relative links keep resolving within the root dir
+        let root = BaseInfo::from_path(&PathBuf::from("/root/")).unwrap();
+        let rebased = root.rebase(&Url::parse("file:///root/docs/").unwrap());
+        assert_eq!(
+            rebased,
+            BaseInfo::full(Url::parse("file:///root/").unwrap(), "docs/".to_string())
+        );
+        assert_eq!(
+            rebased.parse_url_text("/a.html").unwrap().as_str(),
+            "file:///root/a.html"
+        );
+
+        // Bases outside of the origin are taken as-is
+        let rebased = root.rebase(&Url::parse("https://b.com/x/").unwrap());
+        assert_eq!(rebased, BaseInfo::try_from("https://b.com/x/").unwrap());
+    }
+
     #[test]
     fn test_try_from_rejects_invalid_bases() {
         // Prevent data: URLs and relative paths from silently becoming a base in the future
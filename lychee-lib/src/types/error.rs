@@ -3,6 +3,7 @@ use serde::{Serialize, Serializer};
 use std::error::Error;
 use std::hash::Hash;
 use std::{convert::Infallible, path::PathBuf};
+use strum::IntoStaticStr;
 use thiserror::Error;
 use tokio::task::JoinError;
 
@@ -11,8 +12,9 @@ use crate::types::StatusCodeSelectorError;
 use crate::{Uri, basic_auth::BasicAuthExtractorError, utils};
 
 /// Kinds of status errors
-/// Note: The error messages can change over time, so don't match on the output
-#[derive(Error, Debug)]
+/// Note: The error messages can change over time, so don't match on the output.
+/// The name of the variant can be obtained as a `&'static str` instead.
+#[derive(Error, Debug, IntoStaticStr)]
 #[non_exhaustive]
 pub enum ErrorKind {
     /// Network error while handling request.
@@ -190,6 +192,14 @@ pub enum ErrorKind {
     /// Invalid base URL for `WikiLink` checking
     #[error("Invalid base URL for WikiLink checking: {0}")]
     WikilinkInvalidBase(String),
+
+    /// Error while loading or saving a recording of HTTP responses
+    #[error("Recording error: {0}")]
+    Recording(String),
+
+    /// The response to a request is missing from the replayed recording
+    #[error("No recorded response for '{0}' to replay")]
+    NotRecorded(Uri),
 }
 
 impl ErrorKind {
@@ -288,6 +298,9 @@ impl ErrorKind {
             ErrorKind::StatusCodeSelectorError(_) => {
                 format!("{self}. Check 'accept' and 'cache_exclude_status' configuration")
             }
+            ErrorKind::NotRecorded(_) => {
+                format!("{self}. Record the responses again with --record")
+            }
             ErrorKind::InvalidIndexFile(index_files) => {
                 let details = match &index_files[..] {
                     [] => "Directory links are rejected because index_files is empty".into(),
@@ -311,6 +324,7 @@ impl ErrorKind {
             | ErrorKind::ReadResponseBody(_)
             | ErrorKind::RuntimeJoin(_)
             | ErrorKind::WikilinkInvalidBase(_)
+            | ErrorKind::Recording(_)
             | ErrorKind::Channel(_)
             | ErrorKind::InsecureURL(_)
             | ErrorKind::ReadStdinInput(_)
@@ -389,6 +403,8 @@ impl PartialEq for ErrorKind {
                 e1.to_string() == e2.to_string()
             }
             (Self::Cookies(e1), Self::Cookies(e2)) => e1 == e2,
+            (Self::Recording(e1), Self::Recording(e2)) => e1 == e2,
+            (Self::NotRecorded(u1), Self::NotRecorded(u2)) => u1 == u2,
             (Self::InvalidInput(s1), Self::InvalidInput(s2)) => s1 == s2,
             (Self::InvalidFilePath(u1), Self::InvalidFilePath(u2)) => u1 == u2,
             (Self::InvalidFragment(u1), Self::InvalidFragment(u2)) => u1 == u2,
@@ -454,6 +470,8 @@ impl Hash for ErrorKind {
             Self::PreprocessorError { command, reason } => (command, reason).hash(state),
             Self::WikilinkNotFound(uri, pathbuf) => (uri, pathbuf).hash(state),
             Self::WikilinkInvalidBase(e) => e.hash(state),
+            Self::Recording(e) => e.hash(state),
+            Self::NotRecorded(u) => u.hash(state),
         }
     }
 }
@@ -491,4 +509,10 @@ mod tests {
                 .contains(r#"(configurable with "accept" option)"#)
         );
     }
+
+    #[test]
+    fn test_error_kind_name() {
+        let name: &str = (&ErrorKind::RejectedStatusCode(http::StatusCode::NOT_FOUND)).into();
+        assert_eq!(name, "RejectedStatusCode");
+    }
 }
@@ -1,6 +1,7 @@
 use ignore::types::{Types, TypesBuilder};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::{path::Path, sync::LazyLock};
 use url::Url;
 
 /// Represents an ordered list of file extensions.
@@ -84,6 +85,13 @@ impl From<FileType> for FileExtensions {
             FileType::Css => FileType::css_extensions(),
             FileType::Plaintext => FileType::plaintext_extensions(),
             FileType::Xml => FileType::xml_extensions(),
+            FileType::Rst => FileType::rst_extensions(),
+            FileType::AsciiDoc => FileType::asciidoc_extensions(),
+            FileType::Notebook => FileType::notebook_extensions(),
+            FileType::Json => FileType::json_extensions(),
+            FileType::Yaml => FileType::yaml_extensions(),
+            FileType::Toml => FileType::toml_extensions(),
+            FileType::SourceCode(language) => language.extensions(),
         }
     }
 }
@@ -119,8 +127,22 @@ pub enum FileType {
     Markdown,
     /// File in CSS format
     Css,
-    /// File in XML format (used for sitemaps)
+    /// File in XML format (used for sitemaps, feeds and SVG images)
     Xml,
+    /// File in reStructuredText format
+    Rst,
+    /// File in AsciiDoc format
+    AsciiDoc,
+    /// Jupyter notebook
+    Notebook,
+    /// File in JSON format
+    Json,
+    /// File in YAML format
+    Yaml,
+    /// File in TOML format
+    Toml,
+    /// Source code file, where only comments are checked
+    SourceCode(Language),
     /// Generic text file without syntax-specific parsing
     #[default]
     Plaintext,
@@ -134,6 +156,13 @@ impl std::fmt::Display for FileType {
             FileType::Css => write!(f, "CSS"),
             FileType::Plaintext => write!(f, "plaintext"),
             FileType::Xml => write!(f, "XML"),
+            FileType::Rst => write!(f, "reStructuredText"),
+            FileType::AsciiDoc => write!(f, "AsciiDoc"),
+            FileType::Notebook => write!(f, "Jupyter notebook"),
+            FileType::Json => write!(f, "JSON"),
+            FileType::Yaml => write!(f, "YAML"),
+            FileType::Toml => write!(f, "TOML"),
+            FileType::SourceCode(language) => write!(f, "{language} source code"),
         }
     }
 }
@@ -147,16 +176,42 @@ impl FileType {
     /// All known HTML extensions
     const HTML_EXTENSIONS: &'static [&'static str] = &["htm", "html"];
 
+    /// XHTML extensions, which are parsed as HTML but aren't checked by
+    /// default. EPUB books select their XHTML documents by their manifest.
+    const XHTML_EXTENSIONS: &'static [&'static str] = &["xhtml"];
+
     /// All known CSS extensions
     const CSS_EXTENSIONS: &'static [&'static str] = &["css"];
 
     /// All known plaintext extensions
     const PLAINTEXT_EXTENSIONS: &'static [&'static str] = &["txt"];
 
-    /// All known XML extensions
-    const XML_EXTENSIONS: &'static [&'static str] = &["xml"];
+    /// All known XML extensions, including SVG
+    const XML_EXTENSIONS: &'static [&'static str] = &["svg", "xml"];
+
+    /// All known reStructuredText extensions
+    const RST_EXTENSIONS: &'static [&'static str] = &["rst"];
+
+    /// All known AsciiDoc extensions
+    const ASCIIDOC_EXTENSIONS: &'static [&'static str] = &["asciidoc", "adoc"];
+
+    /// All known Jupyter notebook extensions
+    const NOTEBOOK_EXTENSIONS: &'static [&'static str] = &["ipynb"];
 
-    /// Default extensions which are checked by lychee
+    /// All known JSON extensions
+    const JSON_EXTENSIONS: &'static [&'static str] = &["json"];
+
+    /// All known YAML extensions
+    const YAML_EXTENSIONS: &'static [&'static str] = &["yml", "yaml"];
+
+    /// All known TOML extensions
+    const TOML_EXTENSIONS: &'static [&'static str] = &["toml"];
+
+    /// Default extensions which are checked by lychee.
+    ///
+    /// Structured data like JSON, YAML and TOML isn't checked by default,
+    /// because files like lockfiles contain many links which aren't meant
+    /// to be checked. The same goes for source code.
     #[must_use]
     pub fn default_extensions() -> FileExtensions {
         let mut extensions = FileExtensions::empty();
@@ -165,6 +220,9 @@ impl FileType {
         extensions.extend(Self::css_extensions());
         extensions.extend(Self::plaintext_extensions());
         extensions.extend(Self::xml_extensions());
+        extensions.extend(Self::rst_extensions());
+        extensions.extend(Self::asciidoc_extensions());
+        extensions.extend(Self::notebook_extensions());
         extensions
     }
 
@@ -213,13 +271,79 @@ impl FileType {
             .collect()
     }
 
+    /// All known reStructuredText extensions
+    #[must_use]
+    pub fn rst_extensions() -> FileExtensions {
+        Self::RST_EXTENSIONS
+            .iter()
+            .map(|&s| s.to_string())
+            .collect()
+    }
+
+    /// All known AsciiDoc extensions
+    #[must_use]
+    pub fn asciidoc_extensions() -> FileExtensions {
+        Self::ASCIIDOC_EXTENSIONS
+            .iter()
+            .map(|&s| s.to_string())
+            .collect()
+    }
+
+    /// All known Jupyter notebook extensions
+    #[must_use]
+    pub fn notebook_extensions() -> FileExtensions {
+        Self::NOTEBOOK_EXTENSIONS
+            .iter()
+            .map(|&s| s.to_string())
+            .collect()
+    }
+
+    /// All known JSON extensions
+    #[must_use]
+    pub fn json_extensions() -> FileExtensions {
+        Self::JSON_EXTENSIONS
+            .iter()
+            .map(|&s| s.to_string())
+            .collect()
+    }
+
+    /// All known YAML extensions
+    #[must_use]
+    pub fn yaml_extensions() -> FileExtensions {
+        Self::YAML_EXTENSIONS
+            .iter()
+            .map(|&s| s.to_string())
+            .collect()
+    }
+
+    /// All known TOML extensions
+    #[must_use]
+    pub fn toml_extensions() -> FileExtensions {
+        Self::TOML_EXTENSIONS
+            .iter()
+            .map(|&s| s.to_string())
+            .collect()
+    }
+
+    /// All known source code extensions
+    #[must_use]
+    pub fn source_code_extensions() -> FileExtensions {
+        let mut extensions = FileExtensions::empty();
+        for language in Language::ALL {
+            extensions.extend(language.extensions());
+        }
+        extensions
+    }
+
     /// Get the [`FileType`] from an extension string
     #[must_use]
     pub fn from_extension(extension: &str) -> Option<Self> {
         let ext = extension.to_lowercase();
         if Self::MARKDOWN_EXTENSIONS.contains(&ext.as_str()) {
             Some(Self::Markdown)
-        } else if Self::HTML_EXTENSIONS.contains(&ext.as_str()) {
+        } else if Self::HTML_EXTENSIONS.contains(&ext.as_str())
+            || Self::XHTML_EXTENSIONS.contains(&ext.as_str())
+        {
             Some(Self::Html)
         } else if Self::CSS_EXTENSIONS.contains(&ext.as_str()) {
             Some(Self::Css)
@@ -227,6 +351,179 @@ impl FileType {
             Some(Self::Plaintext)
         } else if Self::XML_EXTENSIONS.contains(&ext.as_str()) {
             Some(Self::Xml)
+        } else if Self::RST_EXTENSIONS.contains(&ext.as_str()) {
+            Some(Self::Rst)
+        } else if Self::ASCIIDOC_EXTENSIONS.contains(&ext.as_str()) {
+            Some(Self::AsciiDoc)
+        } else if Self::NOTEBOOK_EXTENSIONS.contains(&ext.as_str()) {
+            Some(Self::Notebook)
+        } else if Self::JSON_EXTENSIONS.contains(&ext.as_str()) {
+            Some(Self::Json)
+        } else if Self::YAML_EXTENSIONS.contains(&ext.as_str()) {
+            Some(Self::Yaml)
+        } else if Self::TOML_EXTENSIONS.contains(&ext.as_str()) {
+            Some(Self::Toml)
+        } else {
+            Language::from_extension(&ext).map(Self::SourceCode)
+        }
+    }
+}
+
+/// Programming languages of [`FileType::SourceCode`] files
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Language {
+    /// Rust, whose doc comments are Markdown
+    Rust,
+    /// Python, including docstrings
+    Python,
+    /// Go
+    Go,
+    /// JavaScript
+    JavaScript,
+    /// TypeScript
+    TypeScript,
+    /// Java
+    Java,
+    /// C
+    C,
+    /// C++
+    Cpp,
+    /// Shell scripts
+    Shell,
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::Rust => write!(f, "Rust"),
+            Language::Python => write!(f, "Python"),
+            Language::Go => write!(f, "Go"),
+            Language::JavaScript => write!(f, "JavaScript"),
+            Language::TypeScript => write!(f, "TypeScript"),
+            Language::Java => write!(f, "Java"),
+            Language::C => write!(f, "C"),
+            Language::Cpp => write!(f, "C++"),
+            Language::Shell => write!(f, "shell"),
+        }
+    }
+}
+
+impl Language {
+    /// All known languages
+    pub const ALL: [Self; 9] = [
+        Self::Rust,
+        Self::Python,
+        Self::Go,
+        Self::JavaScript,
+        Self::TypeScript,
+        Self::Java,
+        Self::C,
+        Self::Cpp,
+        Self::Shell,
+    ];
+
+    const fn extension_list(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => &["rs"],
+            Self::Python => &["pyi", "py"],
+            Self::Go => &["go"],
+            // JSX and TSX files aren't included, since their markup has links
+            Self::JavaScript => &["cjs", "mjs", "js"],
+            Self::TypeScript => &["cts", "mts", "ts"],
+            Self::Java => &["java"],
+            Self::C => &["h", "c"],
+            Self::Cpp => &["hxx", "hh", "hpp", "cxx", "cc", "cpp"],
+            Self::Shell => &["zsh", "bash", "sh"],
+        }
+    }
+
+    /// All known extensions of the language
+    #[must_use]
+    pub fn extensions(self) -> FileExtensions {
+        self.extension_list()
+            .iter()
+            .map(|&s| s.to_string())
+            .collect()
+    }
+
+    /// Get the [`Language`] from a lowercase extension string
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|language| language.extension_list().contains(&extension))
+    }
+}
+
+impl FileType {
+    /// Get the [`FileType`] from a MIME type like `text/html; charset=utf-8`,
+    /// e.g. from the `Content-Type` header of a response.
+    ///
+    /// Returns `None` for unknown and generic MIME types like `text/plain` or
+    /// `application/octet-stream`, which servers also use for Markdown and
+    /// other text files.
+    #[must_use]
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        let essence = mime_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let file_type = match essence.as_str() {
+            "text/html" | "application/xhtml+xml" => Self::Html,
+            "text/markdown" | "text/x-markdown" => Self::Markdown,
+            "text/css" => Self::Css,
+            "text/x-rst" => Self::Rst,
+            "text/asciidoc" | "text/x-asciidoc" => Self::AsciiDoc,
+            "application/x-ipynb+json" => Self::Notebook,
+            "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => Self::Yaml,
+            "application/toml" => Self::Toml,
+            "application/xml" | "text/xml" | "image/svg+xml" => Self::Xml,
+            "application/json" => Self::Json,
+            // e.g. `application/rss+xml`, `application/atom+xml`, `application/ld+json`
+            essence if essence.ends_with("+xml") => Self::Xml,
+            essence if essence.ends_with("+json") => Self::Json,
+            _ => return None,
+        };
+        Some(file_type)
+    }
+
+    /// Guess the [`FileType`] from the content, for content without a known
+    /// extension like stdin.
+    ///
+    /// HTML and XML are detected by their doctype, prologue or root element,
+    /// and CSS by a leading at-rule or rule set. Markdown is detected by
+    /// its link syntax, or by at least two other kinds of Markdown syntax like
+    /// headings, lists and fenced code blocks, because headings alone look like
+    /// the comments of many plain text formats.
+    ///
+    /// Only the start of the content is inspected.
+    pub(crate) fn sniff(content: &str) -> Option<Self> {
+        const SNIFF_LENGTH: usize = 4096;
+
+        let content = content.trim_start_matches('\u{feff}').trim_start();
+        let end = content
+            .char_indices()
+            .nth(SNIFF_LENGTH)
+            .map_or(content.len(), |(end, _)| end);
+        let content = &content[..end];
+
+        let start = content.get(..64).unwrap_or(content).to_ascii_lowercase();
+        if ["<!doctype html", "<html", "<head", "<body"]
+            .iter()
+            .any(|tag| start.starts_with(tag))
+        {
+            Some(Self::Html)
+        } else if ["<?xml", "<rss", "<feed", "<urlset", "<sitemapindex", "<svg"]
+            .iter()
+            .any(|tag| start.starts_with(tag))
+        {
+            Some(Self::Xml)
+        } else if CSS_START.is_match(content) {
+            Some(Self::Css)
+        } else if looks_like_markdown(content) {
+            Some(Self::Markdown)
         } else {
             None
         }
@@ -250,6 +547,44 @@ impl<P: AsRef<Path>> From<P> for FileType {
     }
 }
 
+/// A leading at-rule like `@import` or a rule set like `a { color: red; }`,
+/// optionally after a comment
+static CSS_START: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"^(?:/\*(?s:.*?)\*/\s*)*(?:@(?:charset|import|media|font-face|supports|layer|namespace)\b|[^{};<"'()=\n]+\{\s*[\w-]+\s*:[^;{}]+[;}])"#,
+    )
+    .unwrap()
+});
+
+/// Inline links and images like `[text](target)`, and reference definitions
+/// like `[text]: target`
+static MARKDOWN_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)\[[^\]\n]+\]\([^()\s]+(?:\s+\x22[^\x22\n]*\x22)?\)|^ {0,3}\[[^\]\n]+\]:\s+\S")
+        .unwrap()
+});
+
+static MARKDOWN_HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^#{1,6} \S|^(?:=+|-+)\s*$").unwrap());
+
+static MARKDOWN_LIST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^ {0,3}(?:[-*+]|\d+\.) \S").unwrap());
+
+static MARKDOWN_FENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^ {0,3}(?:```|~~~)").unwrap());
+
+fn looks_like_markdown(content: &str) -> bool {
+    if content.starts_with("#!") {
+        // Scripts with a shebang have comments which look like headings
+        return false;
+    }
+    MARKDOWN_LINK.is_match(content)
+        || [&MARKDOWN_HEADING, &MARKDOWN_LIST, &MARKDOWN_FENCE]
+            .iter()
+            .filter(|regex| regex.is_match(content))
+            .count()
+            >= 2
+}
+
 /// Helper function to check if a path is likely a URL.
 fn is_url(path: &Path) -> bool {
     path.to_str()
@@ -288,7 +623,14 @@ mod tests {
         assert!(extensions.contains("html"));
         assert!(extensions.contains("markdown"));
         assert!(extensions.contains("htm"));
+        assert!(!extensions.contains("xhtml"));
         assert!(extensions.contains("css"));
+        assert!(extensions.contains("rst"));
+        assert!(extensions.contains("svg"));
+        assert!(extensions.contains("adoc"));
+        assert!(extensions.contains("ipynb"));
+        assert!(!extensions.contains("json"));
+        assert!(!extensions.contains("rs"));
         // Test that the count matches our static arrays
         let all_extensions: Vec<_> = extensions.into();
         assert_eq!(
@@ -298,6 +640,9 @@ mod tests {
                 + FileType::CSS_EXTENSIONS.len()
                 + FileType::PLAINTEXT_EXTENSIONS.len()
                 + FileType::XML_EXTENSIONS.len()
+                + FileType::RST_EXTENSIONS.len()
+                + FileType::ASCIIDOC_EXTENSIONS.len()
+                + FileType::NOTEBOOK_EXTENSIONS.len()
         );
     }
 
@@ -325,12 +670,81 @@ mod tests {
         assert!(!is_url(Path::new("ftp://foo.com")));
     }
 
+    #[test]
+    fn test_from_mime_type() {
+        assert_eq!(
+            FileType::from_mime_type("text/html; charset=utf-8"),
+            Some(FileType::Html)
+        );
+        assert_eq!(
+            FileType::from_mime_type("Text/Markdown"),
+            Some(FileType::Markdown)
+        );
+        assert_eq!(FileType::from_mime_type("text/css"), Some(FileType::Css));
+        assert_eq!(
+            FileType::from_mime_type("application/rss+xml"),
+            Some(FileType::Xml)
+        );
+        assert_eq!(
+            FileType::from_mime_type("application/atom+xml"),
+            Some(FileType::Xml)
+        );
+        assert_eq!(
+            FileType::from_mime_type("application/ld+json"),
+            Some(FileType::Json)
+        );
+        assert_eq!(FileType::from_mime_type("text/plain"), None);
+        assert_eq!(FileType::from_mime_type("application/octet-stream"), None);
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(
+            FileType::sniff("\u{feff}\n  <!DOCTYPE html><html></html>"),
+            Some(FileType::Html)
+        );
+        assert_eq!(FileType::sniff("<html lang=\"en\">"), Some(FileType::Html));
+        assert_eq!(
+            FileType::sniff("<?xml version=\"1.0\"?><rss></rss>"),
+            Some(FileType::Xml)
+        );
+        assert_eq!(
+            FileType::sniff("<body><a href=\"/\">"),
+            Some(FileType::Html)
+        );
+        assert_eq!(
+            FileType::sniff("/* theme */\n@import url(\"base.css\");"),
+            Some(FileType::Css)
+        );
+        assert_eq!(
+            FileType::sniff("body > .main {\n  color: red;\n}"),
+            Some(FileType::Css)
+        );
+        assert_eq!(
+            FileType::sniff("See [the docs](docs/index.md) for details."),
+            Some(FileType::Markdown)
+        );
+        assert_eq!(
+            FileType::sniff("# Title\n\n- item https://example.com\n"),
+            Some(FileType::Markdown)
+        );
+
+        // Comments of plain text formats look like headings
+        assert_eq!(FileType::sniff("# Title"), None);
+        assert_eq!(FileType::sniff("#!/bin/sh\n# Setup\n- not a list"), None);
+        assert_eq!(FileType::sniff("{\"key\": \"value\"}"), None);
+        assert_eq!(FileType::sniff("function f() { return a; }"), None);
+        assert_eq!(FileType::sniff("just text https://example.com"), None);
+        assert_eq!(FileType::sniff(""), None);
+    }
+
     #[test]
     fn test_from_extension() {
         // Valid extensions
         assert_eq!(FileType::from_extension("html"), Some(FileType::Html));
         assert_eq!(FileType::from_extension("HTML"), Some(FileType::Html));
         assert_eq!(FileType::from_extension("htm"), Some(FileType::Html));
+        assert_eq!(FileType::from_extension("xhtml"), Some(FileType::Html));
         assert_eq!(
             FileType::from_extension("markdown"),
             Some(FileType::Markdown)
@@ -339,6 +753,29 @@ mod tests {
         assert_eq!(FileType::from_extension("MD"), Some(FileType::Markdown));
         assert_eq!(FileType::from_extension("txt"), Some(FileType::Plaintext));
         assert_eq!(FileType::from_extension("TXT"), Some(FileType::Plaintext));
+        assert_eq!(FileType::from_extension("rst"), Some(FileType::Rst));
+        assert_eq!(FileType::from_extension("adoc"), Some(FileType::AsciiDoc));
+        assert_eq!(FileType::from_extension("ipynb"), Some(FileType::Notebook));
+        assert_eq!(FileType::from_extension("json"), Some(FileType::Json));
+        assert_eq!(FileType::from_extension("yml"), Some(FileType::Yaml));
+        assert_eq!(FileType::from_extension("toml"), Some(FileType::Toml));
+        assert_eq!(
+            FileType::from_extension("rs"),
+            Some(FileType::SourceCode(Language::Rust))
+        );
+        assert_eq!(
+            FileType::from_extension("PY"),
+            Some(FileType::SourceCode(Language::Python))
+        );
+        assert_eq!(
+            FileType::from_extension("hpp"),
+            Some(FileType::SourceCode(Language::Cpp))
+        );
+        assert_eq!(FileType::from_extension("tsx"), None);
+        assert_eq!(
+            FileType::from_extension("asciidoc"),
+            Some(FileType::AsciiDoc)
+        );
 
         // Unknown extension
         assert_eq!(FileType::from_extension("unknown"), None);
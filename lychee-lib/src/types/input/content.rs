@@ -14,17 +14,46 @@ pub struct InputContent {
     pub source: ResolvedInputSource,
     /// File type of given input
     pub file_type: FileType,
+    /// MIME type of the content as reported by the server, for remote inputs
+    pub(crate) content_type: Option<String>,
     /// Raw UTF-8 string content
     pub content: String,
 }
 
 impl InputContent {
+    /// Create an instance of `InputContent` from the content of a source
+    #[must_use]
+    pub const fn new(source: ResolvedInputSource, file_type: FileType, content: String) -> Self {
+        Self {
+            source,
+            file_type,
+            content_type: None,
+            content,
+        }
+    }
+
+    /// Set the MIME type of the content, as reported by the server it was
+    /// fetched from
+    #[must_use]
+    pub fn with_content_type(mut self, content_type: Option<String>) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// The MIME type of the content, as reported by the server it was
+    /// fetched from
+    #[must_use]
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
     #[must_use]
     /// Create an instance of `InputContent` from an input string
     pub fn from_string(s: &str, file_type: FileType) -> Self {
         Self {
             source: ResolvedInputSource::String(Cow::Owned(s.to_owned())),
             file_type,
+            content_type: None,
             content: s.to_owned(),
         }
     }
@@ -36,6 +65,7 @@ impl InputContent {
         Self {
             source: ResolvedInputSource::String(cow.clone()),
             file_type,
+            content_type: None,
             content: cow.into_owned(),
         }
     }
@@ -9,9 +9,11 @@ use super::source::{InputSource, ResolvedInputSource};
 use crate::Preprocessor;
 use crate::filter::PathExcludes;
 use crate::types::{FileType, RequestError, file::FileExtensions, resolver::UrlContentResolver};
+use crate::utils::archive::{self, Archive, ArchiveFormat, Compression, Member};
+use crate::utils::encoding;
 use crate::{ErrorKind, LycheeResult};
 use async_stream::try_stream;
-use futures::stream::{Stream, StreamExt};
+use futures::stream::{self, Stream, StreamExt};
 use log::debug;
 use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
@@ -152,7 +154,7 @@ impl Input {
             // Handle complex cases that need resolution (FsPath, FsGlob)
             let mut sources_stream = InputResolver::resolve(
                 &self,
-                file_extensions,
+                file_extensions.clone(),
                 skip_hidden,
                 skip_ignored,
                 &excluded_paths,
@@ -163,37 +165,48 @@ impl Input {
             while let Some(source_result) = sources_stream.next().await {
                 match source_result {
                     Ok(source) => {
-                        let content_result = match source {
+                        let mut content_results = match source {
+                            // Archives are checked as if they were directories
+                            ResolvedInputSource::FsPath(path) if ArchiveFormat::from_path(&path).is_some() => {
+                                let file_extensions = file_extensions.clone();
+                                let excluded_paths = excluded_paths.clone();
+                                Self::archive_contents(path, self.file_type_hint, move |path, archive| {
+                                    InputResolver::archive_member_filter(path, archive, file_extensions, skip_hidden, excluded_paths)
+                                })
+                                .boxed()
+                            }
                             ResolvedInputSource::FsPath(path) => {
-                                Self::path_content(&path, preprocessor.as_ref()).await
+                                stream::iter([Self::path_content_with_hint(&path, preprocessor.as_ref(), self.file_type_hint).await]).boxed()
                             },
                             ResolvedInputSource::RemoteUrl(url) => {
-                                resolver.url_contents(*url).await
+                                stream::iter([resolver.url_contents(*url).await]).boxed()
                             }
                             ResolvedInputSource::Stdin => {
-                                Self::stdin_content(self.file_type_hint).await
+                                stream::iter([Self::stdin_content(self.file_type_hint).await]).boxed()
                             }
                             ResolvedInputSource::String(s) => {
-                                Ok(Self::string_content(&s, self.file_type_hint))
+                                stream::iter([Ok(Self::string_content(&s, self.file_type_hint))]).boxed()
                             }
                         };
 
-                        match content_result {
-                            Err(_) if skip_missing => (),
-                            Err(e) if matches!(&e, ErrorKind::ReadFileInput(io_err, _) if io_err.kind() == std::io::ErrorKind::InvalidData) =>
-                            {
-                                // If the file contains invalid UTF-8 (e.g. binary), we skip it
-                                if let ErrorKind::ReadFileInput(_, path) = &e {
-                                    log::warn!(
-                                        "Skipping file with invalid UTF-8 content: {}",
-                                        path.display()
-                                    );
+                        while let Some(content_result) = content_results.next().await {
+                            match content_result {
+                                Err(_) if skip_missing => (),
+                                Err(e) if matches!(&e, ErrorKind::ReadFileInput(io_err, _) if io_err.kind() == std::io::ErrorKind::InvalidData) =>
+                                {
+                                    // If the file contains invalid UTF-8 (e.g. binary), we skip it
+                                    if let ErrorKind::ReadFileInput(_, path) = &e {
+                                        log::warn!(
+                                            "Skipping file with invalid UTF-8 content: {}",
+                                            path.display()
+                                        );
+                                    }
+                                }
+                                Err(e) => Err(discovered_input_error(e))?,
+                                Ok(content) => {
+                                    sources_empty = false;
+                                    yield content
                                 }
-                            }
-                            Err(e) => Err(discovered_input_error(e))?,
-                            Ok(content) => {
-                                sources_empty = false;
-                                yield content
                             }
                         }
                     }
@@ -213,6 +226,7 @@ impl Input {
     /// - Remote URLs are returned as is, in their full form
     /// - Glob patterns are expanded and each matched entry is returned
     /// - Absolute or relative filepaths are returned as-is
+    /// - Archives are expanded and each matched file in them is returned
     /// - Stdin input is returned as the special string "<stdin>"
     /// - A raw string input is returned as the special string "<raw string>"
     ///
@@ -228,11 +242,18 @@ impl Input {
     ) -> impl Stream<Item = LycheeResult<String>> {
         InputResolver::resolve(
             &self,
-            file_extensions,
+            file_extensions.clone(),
             skip_hidden,
             skip_ignored,
             excluded_paths,
         )
+        .flat_map(move |res| match res {
+            Ok(ResolvedInputSource::FsPath(path)) if ArchiveFormat::from_path(&path).is_some() => {
+                Self::archive_sources(path, file_extensions.clone(), skip_hidden, excluded_paths)
+                    .boxed()
+            }
+            res => stream::iter([res]).boxed(),
+        })
         .map(|res| {
             res.map(|src| match src {
                 ResolvedInputSource::FsPath(path) => path.to_string_lossy().to_string(),
@@ -243,8 +264,32 @@ impl Input {
         })
     }
 
+    /// The sources of the files in an archive which match the given file
+    /// extensions and aren't excluded
+    fn archive_sources(
+        archive: PathBuf,
+        file_extensions: FileExtensions,
+        skip_hidden: bool,
+        excluded_paths: &PathExcludes,
+    ) -> impl Stream<Item = LycheeResult<ResolvedInputSource>> + use<> {
+        let excluded_paths = excluded_paths.clone();
+        archive::stream_archive(archive.clone(), false, move |path, archive| {
+            InputResolver::archive_member_filter(
+                path,
+                archive,
+                file_extensions,
+                skip_hidden,
+                excluded_paths,
+            )
+        })
+        .map(move |member| Ok(ResolvedInputSource::FsPath(archive.join(member?.path))))
+    }
+
     /// Get the content for a given path.
     ///
+    /// The file type is guessed from the content if the path
+    /// has no known extension.
+    ///
     /// # Errors
     ///
     /// Returns an error if the file cannot be read
@@ -252,35 +297,90 @@ impl Input {
     pub async fn path_content<P: Into<PathBuf> + AsRef<Path> + Clone>(
         path: P,
         preprocessor: Option<&Preprocessor>,
+    ) -> LycheeResult<InputContent> {
+        Self::path_content_with_hint(path, preprocessor, None).await
+    }
+
+    /// Get the content for a given path, using the file type hint
+    /// if the path has no known extension.
+    async fn path_content_with_hint<P: Into<PathBuf> + AsRef<Path> + Clone>(
+        path: P,
+        preprocessor: Option<&Preprocessor>,
+        file_type_hint: Option<FileType>,
     ) -> LycheeResult<InputContent> {
         let path = path.into();
         let content = Self::get_content(&path, preprocessor).await?;
+        // The file type of compressed files is that of the file they contain
+        let file_type = match Compression::from_path(&path) {
+            Some(_) => Self::file_type(&path.with_extension(""), file_type_hint, &content),
+            None => Self::file_type(&path, file_type_hint, &content),
+        };
 
         Ok(InputContent {
-            file_type: FileType::from(&path),
+            file_type,
+            content_type: None,
             source: ResolvedInputSource::FsPath(path),
             content,
         })
     }
 
+    /// Stream the content of the files in an archive which are selected by
+    /// the filter.
+    ///
+    /// The files are identified by their path into the archive, like
+    /// `docs.zip/index.html`, as if the archive was a directory.
+    fn archive_contents<F: FnMut(&Path) -> bool>(
+        archive: PathBuf,
+        file_type_hint: Option<FileType>,
+        filter: impl FnOnce(&Path, &mut Archive) -> LycheeResult<F> + Send + 'static,
+    ) -> impl Stream<Item = LycheeResult<InputContent>> {
+        archive::stream_archive(archive.clone(), true, filter).map(move |member| {
+            let Member { path, content } = member?;
+            let content = encoding::decode(content.unwrap_or_default())
+                .map_err(|e| ErrorKind::ReadFileInput(e, archive.join(&path)))?;
+            Ok(InputContent {
+                file_type: Self::file_type(&path, file_type_hint, &content),
+                content_type: None,
+                source: ResolvedInputSource::FsPath(archive.join(path)),
+                content,
+            })
+        })
+    }
+
+    /// The file type of a file by its extension, falling back to the file
+    /// type hint and then to the file type guessed from its content.
+    fn file_type(path: &Path, file_type_hint: Option<FileType>, content: &str) -> FileType {
+        path.extension()
+            .and_then(|extension| FileType::from_extension(&extension.to_string_lossy()))
+            .or(file_type_hint)
+            .or_else(|| FileType::sniff(content))
+            .unwrap_or_default()
+    }
+
     /// Create `InputContent` from stdin.
     ///
+    /// Without a file type hint, the file type is guessed from the content.
+    ///
     /// # Errors
     ///
     /// Returns an error if stdin cannot be read
     pub async fn stdin_content(file_type_hint: Option<FileType>) -> LycheeResult<InputContent> {
-        let mut content = String::new();
+        let mut bytes = Vec::new();
         let mut stdin = stdin();
 
         if std::io::stdin().is_terminal() {
             // useful info when nothing piped and process blocks
             debug!("Reading content from stdin");
         }
-        stdin.read_to_string(&mut content).await?;
+        stdin.read_to_end(&mut bytes).await?;
+        let content = encoding::decode(bytes)?;
 
         let input_content = InputContent {
             source: ResolvedInputSource::Stdin,
-            file_type: file_type_hint.unwrap_or_default(),
+            file_type: file_type_hint
+                .or_else(|| FileType::sniff(&content))
+                .unwrap_or_default(),
+            content_type: None,
             content,
         };
 
@@ -293,19 +393,27 @@ impl Input {
         InputContent::from_string(s, file_type_hint.unwrap_or_default())
     }
 
-    /// Get content of file.
+    /// Get content of file, decoded from its declared encoding if it isn't UTF-8.
+    /// Compressed files are decompressed.
     /// Get preprocessed file content if [`Preprocessor`] is [`Some`]
     async fn get_content(
         path: &PathBuf,
         preprocessor: Option<&Preprocessor>,
     ) -> LycheeResult<String> {
         if let Some(pre) = preprocessor {
-            pre.process(path)
-        } else {
-            Ok(tokio::fs::read_to_string(path)
-                .await
-                .map_err(|e| ErrorKind::ReadFileInput(e, path.clone()))?)
+            return pre.process(path);
         }
+
+        let bytes = match Compression::from_path(path) {
+            Some(compression) => {
+                let path = path.clone();
+                archive::blocking(move || archive::read_compressed(&path, compression)).await
+            }
+            None => tokio::fs::read(path).await,
+        };
+        bytes
+            .and_then(encoding::decode)
+            .map_err(|e| ErrorKind::ReadFileInput(e, path.clone()))
     }
 }
 
@@ -327,6 +435,37 @@ mod tests {
         excluded_paths.is_match(&path.to_string_lossy())
     }
 
+    #[tokio::test]
+    async fn test_path_content_file_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let html = "<!DOCTYPE html>\n<a href=\"page.html\">Page</a>";
+
+        let without_extension = dir.path().join("index");
+        std::fs::write(&without_extension, html).unwrap();
+        let content = Input::path_content(&without_extension, None).await.unwrap();
+        assert_eq!(content.file_type, FileType::Html);
+
+        // A hint takes precedence over the content
+        let content =
+            Input::path_content_with_hint(&without_extension, None, Some(FileType::Markdown))
+                .await
+                .unwrap();
+        assert_eq!(content.file_type, FileType::Markdown);
+
+        // A known extension takes precedence over the hint and the content
+        let with_extension = dir.path().join("index.txt");
+        std::fs::write(&with_extension, html).unwrap();
+        let content = Input::path_content_with_hint(&with_extension, None, Some(FileType::Html))
+            .await
+            .unwrap();
+        assert_eq!(content.file_type, FileType::Plaintext);
+
+        let unknown = dir.path().join("notes");
+        std::fs::write(&unknown, "Visit https://example.com").unwrap();
+        let content = Input::path_content(&unknown, None).await.unwrap();
+        assert_eq!(content.file_type, FileType::Plaintext);
+    }
+
     #[test]
     fn test_input_handles_real_relative_paths() {
         let test_file = "./Cargo.toml";
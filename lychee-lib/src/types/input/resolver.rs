@@ -5,17 +5,24 @@
 
 use super::input::Input;
 use super::source::{InputSource, ResolvedInputSource};
+use crate::ErrorKind;
 use crate::Result;
 use crate::filter::PathExcludes;
 use crate::types::file::FileExtensions;
+use crate::utils::{
+    archive::{Archive, ArchiveFormat},
+    epub,
+};
 use async_stream::try_stream;
 use futures::stream::Stream;
-use futures::stream::once;
-use glob::glob_with;
+use futures::stream::{empty, iter, once};
+use glob::{Pattern, glob_with};
 use ignore::{Walk, WalkBuilder};
 use shellexpand::tilde;
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::Arc;
 
 /// Resolves input sources into concrete, processable sources.
 ///
@@ -67,6 +74,125 @@ impl InputResolver {
         )
     }
 
+    /// Resolve an input like [`InputResolver::resolve`], but only into those
+    /// of the given files which the input contains.
+    ///
+    /// This is meant for checking files again after they changed. Instead
+    /// of walking all of a directory, only the directories on the way to
+    /// the given files are traversed, and globs are matched against the
+    /// files directly. The given files must be canonical paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error (within the stream) for the same reasons as
+    /// [`InputResolver::resolve`].
+    #[must_use]
+    pub fn resolve_files<'a>(
+        input: &Input,
+        files: &HashSet<PathBuf>,
+        file_extensions: FileExtensions,
+        skip_hidden: bool,
+        skip_ignored: bool,
+        excluded_paths: &'a PathExcludes,
+    ) -> Pin<Box<dyn Stream<Item = Result<ResolvedInputSource>> + Send + 'a>> {
+        match &input.source {
+            InputSource::FsGlob {
+                pattern,
+                ignore_case,
+            } => {
+                let mut match_opts = glob::MatchOptions::new();
+                match_opts.case_sensitive = !ignore_case;
+                match_opts.require_literal_leading_dot = skip_hidden;
+                // The files are absolute, so the pattern has to be as well
+                let pattern = Path::new(tilde(pattern.as_str()).as_ref()).to_path_buf();
+                let pattern = match std::path::absolute(&pattern).map_err(ErrorKind::from) {
+                    Ok(pattern) => {
+                        Pattern::new(&pattern.to_string_lossy()).map_err(ErrorKind::from)
+                    }
+                    Err(e) => Err(e),
+                };
+                let matched: Vec<PathBuf> = match pattern {
+                    Ok(pattern) => files
+                        .iter()
+                        .filter(|file| pattern.matches_path_with(file, match_opts))
+                        .filter(|file| file.is_file())
+                        .filter(|file| !Self::is_excluded_path(file, excluded_paths))
+                        .cloned()
+                        .collect(),
+                    Err(e) => return Box::pin(once(async move { Err(e) })),
+                };
+                Box::pin(iter(
+                    matched
+                        .into_iter()
+                        .map(|file| Ok(ResolvedInputSource::FsPath(file))),
+                ))
+            }
+            InputSource::FsPath(path) if path.is_dir() => {
+                let Ok(root) = path.canonicalize() else {
+                    return Box::pin(empty());
+                };
+                let files: Arc<Vec<PathBuf>> = Arc::new(
+                    files
+                        .iter()
+                        .filter(|file| file.starts_with(&root))
+                        .cloned()
+                        .collect(),
+                );
+                if files.is_empty() {
+                    return Box::pin(empty());
+                }
+
+                let walk =
+                    match Self::walk_builder(&root, file_extensions, skip_hidden, skip_ignored) {
+                        Ok(mut builder) => builder
+                            // Only descend into the directories which contain the files
+                            .filter_entry({
+                                let files = files.clone();
+                                move |entry| files.iter().any(|file| file.starts_with(entry.path()))
+                            })
+                            .build(),
+                        Err(e) => return Box::pin(once(async move { Err(e) })),
+                    };
+                let path = path.clone();
+                Box::pin(try_stream! {
+                    for entry in walk {
+                        let entry = entry?;
+                        if !entry.file_type().is_some_and(|file_type| file_type.is_file())
+                            || !files.iter().any(|file| file == entry.path())
+                        {
+                            continue;
+                        }
+                        // Keep the path of the file relative to the input, like `resolve`
+                        let file = match entry.path().strip_prefix(&root) {
+                            Ok(relative) => path.join(relative),
+                            Err(_) => entry.path().to_path_buf(),
+                        };
+                        if Self::is_excluded_path(&file, excluded_paths) {
+                            continue;
+                        }
+                        yield ResolvedInputSource::FsPath(file);
+                    }
+                })
+            }
+            InputSource::FsPath(path) => {
+                if path.canonicalize().is_ok_and(|path| files.contains(&path)) {
+                    Self::resolve_input(
+                        input,
+                        file_extensions,
+                        skip_hidden,
+                        skip_ignored,
+                        excluded_paths,
+                    )
+                } else {
+                    Box::pin(empty())
+                }
+            }
+            InputSource::RemoteUrl(_) | InputSource::Stdin | InputSource::String(_) => {
+                Box::pin(empty())
+            }
+        }
+    }
+
     /// Create a [`Walk`] iterator for directory traversal
     ///
     /// # Errors
@@ -78,7 +204,18 @@ impl InputResolver {
         skip_hidden: bool,
         skip_ignored: bool,
     ) -> Result<Walk> {
-        Ok(WalkBuilder::new(path)
+        Ok(Self::walk_builder(path, file_extensions, skip_hidden, skip_ignored)?.build())
+    }
+
+    /// Configure a [`WalkBuilder`] for directory traversal
+    fn walk_builder(
+        path: &Path,
+        file_extensions: FileExtensions,
+        skip_hidden: bool,
+        skip_ignored: bool,
+    ) -> Result<WalkBuilder> {
+        let mut builder = WalkBuilder::new(path);
+        builder
             // Skip over files which are ignored by git or `.ignore` if necessary
             .git_ignore(skip_ignored)
             .git_global(skip_ignored)
@@ -88,8 +225,44 @@ impl InputResolver {
             // Ignore hidden files if necessary
             .hidden(skip_hidden)
             // Configure the file types filter to only include files with matching extensions
-            .types(file_extensions.build(skip_hidden)?)
-            .build())
+            .types(file_extensions.build(skip_hidden)?);
+        Ok(builder)
+    }
+
+    /// Create a filter for the files in an archive, which applies the file
+    /// extensions and path exclusions to them as if the archive was a directory.
+    ///
+    /// For EPUB books, the content documents listed in the package document
+    /// are selected instead of files with matching extensions. The package
+    /// document is read from the given open `reader` of the archive.
+    ///
+    /// # Errors
+    ///
+    /// Fails if [`FileExtensions`] cannot be converted, or if the package
+    /// document of an EPUB book cannot be read
+    pub(crate) fn archive_member_filter(
+        archive: &Path,
+        reader: &mut Archive,
+        file_extensions: FileExtensions,
+        skip_hidden: bool,
+        excluded_paths: PathExcludes,
+    ) -> Result<impl Fn(&Path) -> bool + use<>> {
+        let documents = match (ArchiveFormat::from_path(archive), reader.as_zip()) {
+            (Some(ArchiveFormat::Epub), Some(zip)) => Some(
+                epub::content_documents(archive, zip)
+                    .map_err(|e| ErrorKind::ReadFileInput(e, archive.to_path_buf()))?,
+            ),
+            _ => None,
+        };
+        let types = file_extensions.build(skip_hidden)?;
+        let archive = archive.to_path_buf();
+        Ok(move |member: &Path| {
+            let selected = match &documents {
+                Some(documents) => documents.contains(member),
+                None => types.matched(member, false).is_whitelist(),
+            };
+            selected && !Self::is_excluded_path(&archive.join(member), &excluded_paths)
+        })
     }
 
     /// Internal method for resolving input sources.
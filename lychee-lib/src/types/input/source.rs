@@ -12,6 +12,7 @@
 //!   extension
 //! - Glob patterns are expanded to matching file paths, which are then walked
 //!   and filtered by extension
+//! - Archives are read and the files in them are filtered by extension
 //! - URLs, raw strings, and standard input (`stdin`) are read directly
 
 use crate::BaseInfo;
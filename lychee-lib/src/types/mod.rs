@@ -24,7 +24,7 @@ pub use basic_auth::{BasicAuthCredentials, BasicAuthSelector};
 pub use cache::CacheStatus;
 pub use cookies::CookieJar;
 pub use error::ErrorKind;
-pub use file::{FileExtensions, FileType};
+pub use file::{FileExtensions, FileType, Language};
 pub use input::{Input, InputContent, InputResolver, InputSource, ResolvedInputSource};
 pub use preprocessor::Preprocessor;
 pub use redirect_history::{Redirect, Redirects};
@@ -63,6 +63,30 @@ impl Redirects {
     pub fn push(&mut self, redirect: Redirect) {
         self.redirects.push(redirect);
     }
+
+    /// The redirects in the order they were followed
+    pub(crate) fn redirects(&self) -> &[Redirect] {
+        &self.redirects
+    }
+
+    /// The URL reached by following only the permanent redirects
+    /// (301 and 308) from the start of the chain.
+    ///
+    /// Returns `None` if the first redirect is not permanent, because the
+    /// original URL is still the one to use in that case.
+    #[must_use]
+    pub fn permanent_destination(&self) -> Option<&Url> {
+        self.redirects
+            .iter()
+            .take_while(|redirect| {
+                matches!(
+                    redirect.code,
+                    StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+                )
+            })
+            .last()
+            .map(|redirect| &redirect.url)
+    }
 }
 
 /// Keep track of HTTP redirections for reporting
@@ -93,6 +117,13 @@ impl RedirectHistory {
         }
     }
 
+    /// Store a complete redirect chain, e.g. one replayed from a recording,
+    /// using its origin as the key.
+    pub(crate) fn insert(&self, redirects: Redirects) {
+        let mut map = self.0.lock().unwrap();
+        map.insert(redirects.origin.clone(), redirects);
+    }
+
     /// Resolve the redirect chain for the given URL, if any.
     pub(crate) fn resolve(&self, url: &Url) -> Option<Redirects> {
         self.get_resolved(url)
@@ -108,3 +139,40 @@ impl Default for RedirectHistory {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redirects(chain: &[(&str, StatusCode)]) -> Redirects {
+        let mut redirects = Redirects::new(Url::parse("https://a.com/").unwrap());
+        for (url, code) in chain {
+            redirects.push(Redirect {
+                url: Url::parse(url).unwrap(),
+                code: *code,
+            });
+        }
+        redirects
+    }
+
+    #[test]
+    fn test_permanent_destination() {
+        let chain = redirects(&[
+            ("https://b.com/", StatusCode::MOVED_PERMANENTLY),
+            ("https://c.com/", StatusCode::PERMANENT_REDIRECT),
+            ("https://d.com/", StatusCode::FOUND),
+            ("https://e.com/", StatusCode::MOVED_PERMANENTLY),
+        ]);
+        assert_eq!(
+            chain.permanent_destination(),
+            Some(&Url::parse("https://c.com/").unwrap())
+        );
+
+        let chain = redirects(&[
+            ("https://b.com/", StatusCode::TEMPORARY_REDIRECT),
+            ("https://c.com/", StatusCode::MOVED_PERMANENTLY),
+        ]);
+        assert_eq!(chain.permanent_destination(), None);
+        assert_eq!(redirects(&[]).permanent_destination(), None);
+    }
+}
@@ -5,6 +5,7 @@ use crate::ratelimit::HostPool;
 use crate::utils::request;
 use crate::{BasicAuthExtractor, Result, Uri};
 use http::HeaderMap;
+use log::warn;
 use reqwest::{Request, Url};
 
 /// Structure to fetch remote content.
@@ -34,13 +35,11 @@ impl UrlContentResolver {
     ///
     /// This method is not intended to check if a URL is functional but
     /// to get a URL's content and process the content.
+    ///
+    /// The [`FileType`] of the content is determined by the `Content-Type`
+    /// header of the response, or by the URL if the header is missing or
+    /// generic like `text/plain`.
     pub async fn url_contents(&self, url: Url) -> Result<InputContent> {
-        // Assume HTML for default paths
-        let file_type = match url.path() {
-            path if path.is_empty() || path == "/" => FileType::Html,
-            _ => FileType::from(url.as_str()),
-        };
-
         let credentials = request::extract_credentials(
             self.basic_auth_extractor.as_ref(),
             &Uri { url: url.clone() },
@@ -55,16 +54,25 @@ impl UrlContentResolver {
             return Err(crate::ErrorKind::ReadInputUrlStatusCode(response.status));
         }
 
+        let content_type = response
+            .headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
         // SAFETY: needs_body=true above guarantees text is populated on success.
         let content = response.text.unwrap_or_else(|| {
             unreachable!("execute_request with needs_body=true always returns text")
         });
 
-        Ok(InputContent {
-            source: ResolvedInputSource::RemoteUrl(Box::new(url)),
+        let file_type = file_type(&url, content_type.as_deref(), &content);
+
+        Ok(InputContent::new(
+            ResolvedInputSource::RemoteUrl(Box::new(url)),
             file_type,
             content,
-        })
+        )
+        .with_content_type(content_type))
     }
 
     fn build_request(
@@ -84,3 +92,135 @@ impl UrlContentResolver {
         Ok(request)
     }
 }
+
+/// Determine the [`FileType`] of remote content from its `Content-Type`,
+/// falling back to the URL.
+fn file_type(url: &Url, content_type: Option<&str>, content: &str) -> FileType {
+    let Some(file_type) = content_type.and_then(FileType::from_mime_type) else {
+        // Assume HTML for default paths
+        return match url.path() {
+            path if path.is_empty() || path == "/" => FileType::Html,
+            _ => FileType::from(url.as_str()),
+        };
+    };
+
+    if let Some(sniffed) =
+        contradicting_file_type(content_type.unwrap_or_default(), file_type, content)
+    {
+        warn!(
+            "Content-Type of {url} is {file_type}, but the content looks like {sniffed}. Extracting links as {file_type}"
+        );
+    }
+    file_type
+}
+
+/// The file type which the content looks like, if it contradicts the file
+/// type of its `Content-Type`.
+///
+/// Formats based on XML, like XHTML (`application/xhtml+xml`), may start
+/// with an XML prologue, so HTML and XML content are alike for them.
+fn contradicting_file_type(
+    content_type: &str,
+    file_type: FileType,
+    content: &str,
+) -> Option<FileType> {
+    let sniffed = FileType::sniff(content)?;
+    let is_xml_based = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+        .ends_with("+xml");
+    let is_alike = is_xml_based
+        && matches!(
+            (file_type, sniffed),
+            (FileType::Html, FileType::Xml) | (FileType::Xml, FileType::Html)
+        );
+    (sniffed != file_type && !is_alike).then_some(sniffed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_file_type_from_content_type() {
+        assert_eq!(
+            file_type(
+                &url("https://example.com/docs/intro"),
+                Some("text/html; charset=utf-8"),
+                "<!doctype html>"
+            ),
+            FileType::Html
+        );
+        assert_eq!(
+            file_type(
+                &url("https://example.com/feed"),
+                Some("application/rss+xml"),
+                "<?xml version=\"1.0\"?>"
+            ),
+            FileType::Xml
+        );
+        // The header wins over the extension
+        assert_eq!(
+            file_type(
+                &url("https://example.com/README.md"),
+                Some("text/html"),
+                "<html>"
+            ),
+            FileType::Html
+        );
+
+        // XHTML starts with an XML prologue
+        let xhtml = "<?xml version=\"1.0\"?>\n<html xmlns=\"http://www.w3.org/1999/xhtml\">";
+        assert_eq!(
+            file_type(
+                &url("https://example.com/index"),
+                Some("application/xhtml+xml; charset=utf-8"),
+                xhtml
+            ),
+            FileType::Html
+        );
+        assert_eq!(
+            contradicting_file_type("application/xhtml+xml", FileType::Html, xhtml),
+            None
+        );
+        assert_eq!(
+            contradicting_file_type("text/html", FileType::Html, xhtml),
+            Some(FileType::Xml)
+        );
+        assert_eq!(
+            contradicting_file_type("text/html", FileType::Html, "# Title"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_file_type_falls_back_to_url() {
+        assert_eq!(
+            file_type(
+                &url("https://example.com/README.md"),
+                Some("text/plain"),
+                "# Title"
+            ),
+            FileType::Markdown
+        );
+        assert_eq!(
+            file_type(&url("https://example.com/"), None, ""),
+            FileType::Html
+        );
+        assert_eq!(
+            file_type(&url("https://example.com/docs/intro"), None, ""),
+            FileType::Html
+        );
+        assert_eq!(
+            file_type(&url("https://example.com/notes.txt"), None, ""),
+            FileType::Plaintext
+        );
+    }
+}
@@ -7,6 +7,7 @@ use crate::ratelimit::CacheableResponse;
 use http::StatusCode;
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
+use strum::IntoStaticStr;
 
 const ICON_OK: &str = "✔";
 const ICON_EXCLUDED: &str = "?";
@@ -17,8 +18,10 @@ const ICON_TIMEOUT: &str = "⧖";
 const ICON_CACHED: &str = "↻";
 
 /// Response status of the request.
+///
+/// The name of the variant can be obtained as a `&'static str`.
 #[allow(variant_size_differences)]
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Debug, Hash, PartialEq, Eq, IntoStaticStr)]
 pub enum Status {
     /// Request was successful
     Ok(StatusCode),
@@ -23,6 +23,15 @@ pub struct RawUri {
     pub span: RawUriSpan,
 }
 
+impl RawUri {
+    /// Returns `true` if this URI is the `href` of an HTML `<base>` element,
+    /// which sets the base URL for all other relative links in the document.
+    #[must_use]
+    pub(crate) fn is_base_href(&self) -> bool {
+        self.element.as_deref() == Some("base") && self.attribute.as_deref() == Some("href")
+    }
+}
+
 impl Display for RawUri {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?} (Attribute: {:?})", self.text, self.attribute)
@@ -44,7 +53,9 @@ impl From<(&str, RawUriSpan)> for RawUri {
 /// A span of a [`RawUri`] in the document.
 ///
 /// The span can be used to give more precise error messages.
+/// Create spans with [`RawUriSpan::new`], as more fields may be added.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
 pub struct RawUriSpan {
     /// The line of the URI.
     ///
@@ -56,10 +67,40 @@ pub struct RawUriSpan {
     /// This is `None`, if the column can't be computed exactly,
     /// e.g. when it comes from the `html5ever` parser.
     pub column: Option<NonZeroUsize>,
+    /// The cell of the URI, if the document is a Jupyter notebook.
+    ///
+    /// The cell is 1-based. Line and column are relative to the cell then.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<NonZeroUsize>,
+    /// The output of the notebook cell, if the URI is in an output of the
+    /// cell rather than in its source.
+    ///
+    /// The output is 1-based. Line and column are relative to the output then.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<NonZeroUsize>,
+}
+
+impl RawUriSpan {
+    /// Create a span at the given line and column of a document
+    #[must_use]
+    pub const fn new(line: NonZeroUsize, column: Option<NonZeroUsize>) -> Self {
+        Self {
+            line,
+            column,
+            cell: None,
+            output: None,
+        }
+    }
 }
 
 impl Display for RawUriSpan {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(cell) = self.cell {
+            write!(f, "cell {cell}, ")?;
+        }
+        if let Some(output) = self.output {
+            write!(f, "output {output}, ")?;
+        }
         if let Some(column) = self.column {
             write!(f, "{}:{}", self.line, column)
         } else {
@@ -71,19 +112,16 @@ impl Display for RawUriSpan {
 /// Test helper to create [`RawUriSpan`]s easily.
 #[cfg(test)]
 pub(crate) const fn span(line: usize, column: usize) -> RawUriSpan {
-    RawUriSpan {
-        line: NonZeroUsize::new(line).unwrap(),
-        column: Some(NonZeroUsize::new(column).unwrap()),
-    }
+    RawUriSpan::new(
+        NonZeroUsize::new(line).unwrap(),
+        Some(NonZeroUsize::new(column).unwrap()),
+    )
 }
 
 /// Test helper to create a [`RawUriSpan`] from just the line and leave the column unset.
 #[cfg(test)]
 pub(crate) const fn span_line(line: usize) -> RawUriSpan {
-    RawUriSpan {
-        line: std::num::NonZeroUsize::new(line).unwrap(),
-        column: None,
-    }
+    RawUriSpan::new(std::num::NonZeroUsize::new(line).unwrap(), None)
 }
 
 /// A trait for calculating a [`RawUriSpan`] at a given byte offset in the document.
@@ -143,11 +181,8 @@ impl SpanProvider for SourceSpanProvider<'_> {
             // columns are 1-based
             .map(|v| ONE.saturating_add(v.chars().count()));
 
-        RawUriSpan {
-            // lines are 1-based
-            line: ONE.saturating_add(line),
-            column,
-        }
+        // lines are 1-based
+        RawUriSpan::new(ONE.saturating_add(line), column)
     }
 }
 
@@ -0,0 +1,565 @@
+//! Read compressed files and the files in archives.
+//!
+//! Compressed files (`.gz`, `.zst`) are decompressed transparently, and the
+//! files in archives (`.zip`, `.tar`, `.tar.gz`, `.tar.zst`) and EPUB books
+//! are read without extracting them to disk.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{self, BufReader, Read},
+    ops::ControlFlow,
+    path::{Component, Path, PathBuf},
+};
+
+use flate2::read::MultiGzDecoder;
+use futures::{Stream, stream};
+use tokio::sync::{OnceCell, mpsc};
+use zip::ZipArchive;
+
+use crate::{ErrorKind, Result, utils::encoding};
+
+/// Compression format of a single file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Compression {
+    /// gzip (`.gz`)
+    Gzip,
+    /// Zstandard (`.zst`)
+    Zstd,
+}
+
+impl Compression {
+    /// The compression format of the file at the given path, by its extension
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gz" => Some(Self::Gzip),
+            "zst" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Wrap the given reader to decompress what is read from it
+    fn decoder<'a, R: Read + 'a>(self, reader: R) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::Gzip => Box::new(MultiGzDecoder::new(reader)),
+            Self::Zstd => Box::new(zstd::Decoder::with_buffer(BufReader::new(reader))?),
+        })
+    }
+}
+
+/// Read and decompress the compressed file at the given path
+pub(crate) fn read_compressed(path: &Path, compression: Compression) -> io::Result<Vec<u8>> {
+    let mut content = Vec::new();
+    compression
+        .decoder(BufReader::new(File::open(path)?))?
+        .read_to_end(&mut content)?;
+    Ok(content)
+}
+
+/// Format of an archive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ArchiveFormat {
+    /// Tarball, which may be compressed as a whole
+    Tar(Option<Compression>),
+    /// ZIP archive
+    Zip,
+    /// EPUB book, which is a ZIP archive with a package document listing
+    /// its content documents
+    Epub,
+}
+
+impl ArchiveFormat {
+    /// The archive format of the file at the given path, by its extension
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        let (stem, extension) = name.rsplit_once('.')?;
+        match extension {
+            "zip" => Some(Self::Zip),
+            "epub" => Some(Self::Epub),
+            "tar" => Some(Self::Tar(None)),
+            "tgz" => Some(Self::Tar(Some(Compression::Gzip))),
+            "tzst" => Some(Self::Tar(Some(Compression::Zstd))),
+            "gz" | "zst" if Path::new(stem).extension().is_some_and(|ext| ext == "tar") => {
+                Some(Self::Tar(Compression::from_path(path)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A file in an archive
+#[derive(Debug)]
+pub(crate) struct Member {
+    /// Path of the file, relative to the root of the archive
+    pub(crate) path: PathBuf,
+    /// Content of the file, if it was read
+    pub(crate) content: Option<Vec<u8>>,
+}
+
+/// An archive which is open for reading
+pub(crate) enum Archive {
+    /// Tarball, with the decompressing reader if it is compressed
+    Tar(tar::Archive<Box<dyn Read>>),
+    /// ZIP archive, including EPUB books
+    Zip(ZipArchive<BufReader<File>>),
+}
+
+impl Archive {
+    /// Open the archive at the given path
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let format = ArchiveFormat::from_path(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "unknown archive format"))?;
+        let reader = BufReader::new(File::open(path)?);
+        Ok(match format {
+            ArchiveFormat::Tar(compression) => {
+                let reader = match compression {
+                    Some(compression) => compression.decoder(reader)?,
+                    None => Box::new(reader),
+                };
+                Self::Tar(tar::Archive::new(reader))
+            }
+            ArchiveFormat::Zip | ArchiveFormat::Epub => Self::Zip(ZipArchive::new(reader)?),
+        })
+    }
+
+    /// The ZIP archive, whose files can be read in any order. `None` for
+    /// tarballs, which can only be read once from start to end.
+    pub(crate) const fn as_zip(&mut self) -> Option<&mut ZipArchive<BufReader<File>>> {
+        match self {
+            Self::Zip(archive) => Some(archive),
+            Self::Tar(_) => None,
+        }
+    }
+
+    /// Read the files in the archive, one at a time.
+    ///
+    /// `read_member` is called with the path of each file and a reader for
+    /// its content, which is only decompressed if it is read. Reading stops
+    /// early if it returns [`ControlFlow::Break`]. Directories, links and
+    /// files with paths outside of the archive are skipped.
+    pub(crate) fn read_members(
+        self,
+        mut read_member: impl FnMut(PathBuf, &mut dyn Read) -> io::Result<ControlFlow<()>>,
+    ) -> io::Result<()> {
+        match self {
+            Self::Tar(mut archive) => {
+                for entry in archive.entries()? {
+                    let mut entry = entry?;
+                    if !entry.header().entry_type().is_file() {
+                        continue;
+                    }
+                    if let Some(path) = member_path(&entry.path()?)
+                        && read_member(path, &mut entry)?.is_break()
+                    {
+                        break;
+                    }
+                }
+            }
+            Self::Zip(mut archive) => {
+                for index in 0..archive.len() {
+                    let mut file = archive.by_index(index)?;
+                    if !file.is_file() {
+                        continue;
+                    }
+                    if let Some(path) = member_path(Path::new(file.name()))
+                        && read_member(path, &mut file)?.is_break()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the files in the archive at the given path, one at a time, like
+/// [`Archive::read_members`].
+pub(crate) fn read_archive(
+    path: &Path,
+    read_member: impl FnMut(PathBuf, &mut dyn Read) -> io::Result<ControlFlow<()>>,
+) -> io::Result<()> {
+    Archive::open(path)?.read_members(read_member)
+}
+
+/// Stream the files in the archive at the given path, which are read on a
+/// blocking thread.
+///
+/// Only files for which the filter returns `true` are returned, and their
+/// content is only read if `read_content` is set. The filter is created from
+/// the open archive, as creating it may read files of the archive, too.
+pub(crate) fn stream_archive<F>(
+    path: PathBuf,
+    read_content: bool,
+    filter: impl FnOnce(&Path, &mut Archive) -> Result<F> + Send + 'static,
+) -> impl Stream<Item = Result<Member>> + Send
+where
+    F: FnMut(&Path) -> bool,
+{
+    // A single file is buffered, so that the archive is read only as fast
+    // as its files are processed
+    let (sender, receiver) = mpsc::channel(1);
+    tokio::task::spawn_blocking(move || {
+        let read = || -> Result<()> {
+            let read_error = |e| ErrorKind::ReadFileInput(e, path.clone());
+            let mut archive = Archive::open(&path).map_err(read_error)?;
+            let mut include = filter(&path, &mut archive)?;
+            archive
+                .read_members(|member, file| {
+                    if !include(&member) {
+                        return Ok(ControlFlow::Continue(()));
+                    }
+                    let content = if read_content {
+                        let mut content = Vec::new();
+                        file.read_to_end(&mut content)?;
+                        Some(content)
+                    } else {
+                        None
+                    };
+                    let member = Member {
+                        path: member,
+                        content,
+                    };
+                    // The receiver is gone if the stream was dropped
+                    Ok(match sender.blocking_send(Ok(member)) {
+                        Ok(()) => ControlFlow::Continue(()),
+                        Err(_) => ControlFlow::Break(()),
+                    })
+                })
+                .map_err(read_error)
+        };
+        if let Err(e) = read() {
+            let _ = sender.blocking_send(Err(e));
+        }
+    });
+
+    stream::unfold(receiver, |mut receiver| async move {
+        let member = receiver.recv().await?;
+        Some((member, receiver))
+    })
+}
+
+/// Run blocking I/O, like reading an archive, on a thread where blocking is
+/// acceptable.
+pub(crate) async fn blocking<T: Send + 'static>(
+    read: impl FnOnce() -> io::Result<T> + Send + 'static,
+) -> io::Result<T> {
+    tokio::task::spawn_blocking(read)
+        .await
+        .unwrap_or_else(|e| Err(io::Error::other(e)))
+}
+
+/// Normalize the path of a file in an archive.
+///
+/// Returns `None` for paths which point outside of the archive.
+fn member_path(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::CurDir | Component::RootDir => {}
+            Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!normalized.as_os_str().is_empty()).then_some(normalized)
+}
+
+/// Split a path which points into an archive on disk, like
+/// `docs.tar.gz/guide/index.html`, into the path of the archive and the path
+/// within the archive.
+///
+/// A trailing separator, like in `docs.tar.gz/`, points to the root directory
+/// of the archive, rather than to the archive file itself.
+pub(crate) fn split_archive_path(path: &Path) -> Option<(&Path, &Path)> {
+    let is_dir_path = path
+        .as_os_str()
+        .as_encoded_bytes()
+        .last()
+        .is_some_and(|&byte| std::path::is_separator(byte.into()));
+    let skip = usize::from(!is_dir_path);
+    path.ancestors().skip(skip).find_map(|ancestor| {
+        // Drop the trailing separator, which would make the file look like a directory
+        let ancestor = ancestor.components().as_path();
+        ArchiveFormat::from_path(ancestor)?;
+        let member = path.strip_prefix(ancestor).ok()?;
+        ancestor.is_file().then_some((ancestor, member))
+    })
+}
+
+/// An archive which links point into. Its files are read on a blocking
+/// thread the first time they are needed.
+#[derive(Debug)]
+pub(crate) struct ArchiveIndex {
+    /// Path of the archive on disk
+    path: PathBuf,
+    /// Files and directories in the archive
+    members: OnceCell<Members>,
+    /// Decoded content of the files which is kept
+    contents: OnceCell<HashMap<PathBuf, String>>,
+}
+
+/// The files and directories in an archive
+#[derive(Debug)]
+pub(crate) struct Members {
+    /// Files in the archive
+    files: HashSet<PathBuf>,
+    /// Directories which contain files, including the root directory
+    directories: HashSet<PathBuf>,
+}
+
+impl Members {
+    /// List the files in the archive at the given path, without reading
+    /// their content
+    fn read(path: &Path) -> io::Result<Self> {
+        let mut files = HashSet::new();
+        let mut directories = HashSet::from([PathBuf::new()]);
+        read_archive(path, |member, _| {
+            directories.extend(member.ancestors().skip(1).map(Path::to_path_buf));
+            files.insert(member);
+            Ok(ControlFlow::Continue(()))
+        })?;
+        Ok(Self { files, directories })
+    }
+
+    /// Whether the archive contains a file at the given path
+    pub(crate) fn is_file(&self, path: &Path) -> bool {
+        self.files.contains(path)
+    }
+
+    /// Whether the archive contains a directory at the given path
+    pub(crate) fn is_dir(&self, path: &Path) -> bool {
+        self.directories.contains(path)
+    }
+}
+
+impl ArchiveIndex {
+    /// Index the archive at the given path, without reading it yet
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            members: OnceCell::new(),
+            contents: OnceCell::new(),
+        }
+    }
+
+    /// The files and directories in the archive
+    pub(crate) async fn members(&self) -> io::Result<&Members> {
+        self.members
+            .get_or_try_init(|| {
+                let path = self.path.clone();
+                blocking(move || Members::read(&path))
+            })
+            .await
+    }
+
+    /// The content of the file at the given path, if it is kept and is text.
+    ///
+    /// The first call reads the content of all files for which
+    /// `keep_content` returns `true`, so the archive is read only once more
+    /// no matter how many of its files are needed.
+    pub(crate) async fn content(
+        &self,
+        path: &Path,
+        keep_content: fn(&Path) -> bool,
+    ) -> io::Result<Option<&str>> {
+        let contents = self
+            .contents
+            .get_or_try_init(|| {
+                let archive = self.path.clone();
+                blocking(move || Self::read_contents(&archive, keep_content))
+            })
+            .await?;
+        Ok(contents.get(path).map(String::as_str))
+    }
+
+    fn read_contents(
+        path: &Path,
+        keep_content: fn(&Path) -> bool,
+    ) -> io::Result<HashMap<PathBuf, String>> {
+        let mut contents = HashMap::new();
+        read_archive(path, |member, file| {
+            if keep_content(&member) {
+                let mut content = Vec::new();
+                file.read_to_end(&mut content)?;
+                if let Ok(content) = encoding::decode(content) {
+                    contents.insert(member, content);
+                }
+            }
+            Ok(ControlFlow::Continue(()))
+        })?;
+        Ok(contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression as GzLevel, write::GzEncoder};
+    use futures::StreamExt;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn tarball(dir: &TempDir, name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let path = dir.path().join(name);
+        let file = File::create(&path).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(file, GzLevel::default()));
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_formats_from_path() {
+        let format = |path: &str| ArchiveFormat::from_path(Path::new(path));
+        assert_eq!(format("docs.zip"), Some(ArchiveFormat::Zip));
+        assert_eq!(format("book.epub"), Some(ArchiveFormat::Epub));
+        assert_eq!(format("docs.tar"), Some(ArchiveFormat::Tar(None)));
+        assert_eq!(
+            format("docs.TAR.GZ"),
+            Some(ArchiveFormat::Tar(Some(Compression::Gzip)))
+        );
+        assert_eq!(
+            format("docs.tzst"),
+            Some(ArchiveFormat::Tar(Some(Compression::Zstd)))
+        );
+        assert_eq!(format("index.html.gz"), None);
+        assert_eq!(format("tar.gz"), None);
+
+        let compression = |path: &str| Compression::from_path(Path::new(path));
+        assert_eq!(compression("index.html.gz"), Some(Compression::Gzip));
+        assert_eq!(compression("index.html.zst"), Some(Compression::Zstd));
+        assert_eq!(compression("index.html"), None);
+    }
+
+    #[test]
+    fn test_read_compressed() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let gzip = dir.path().join("index.html.gz");
+        let mut encoder = GzEncoder::new(File::create(&gzip).unwrap(), GzLevel::default());
+        encoder.write_all(b"<a href=\"a.html\">").unwrap();
+        encoder.finish().unwrap();
+        assert_eq!(
+            read_compressed(&gzip, Compression::Gzip).unwrap(),
+            b"<a href=\"a.html\">"
+        );
+
+        let zstd = dir.path().join("index.html.zst");
+        std::fs::write(&zstd, zstd::encode_all(&b"[a](a.md)"[..], 0).unwrap()).unwrap();
+        assert_eq!(
+            read_compressed(&zstd, Compression::Zstd).unwrap(),
+            b"[a](a.md)"
+        );
+    }
+
+    #[test]
+    fn test_read_zip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("docs.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("site/", options).unwrap();
+        zip.start_file("site/index.html", options).unwrap();
+        zip.write_all(b"<a href=\"about.html\">").unwrap();
+        zip.start_file("../outside.html", options).unwrap();
+        zip.finish().unwrap();
+
+        let mut members = Vec::new();
+        read_archive(&path, |path, file| {
+            let mut content = String::new();
+            file.read_to_string(&mut content)?;
+            members.push((path, content));
+            Ok(ControlFlow::Continue(()))
+        })
+        .unwrap();
+        assert_eq!(
+            members,
+            [(
+                PathBuf::from("site/index.html"),
+                "<a href=\"about.html\">".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_tarball() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = tarball(
+            &dir,
+            "docs.tar.gz",
+            &[("./index.html", "index"), ("style.css", "css")],
+        );
+
+        let members: Vec<_> = stream_archive(path, false, |_, _| {
+            Ok(|path: &Path| path.extension().is_some_and(|ext| ext == "html"))
+        })
+        .collect()
+        .await;
+        assert_eq!(members.len(), 1);
+        let member = members[0].as_ref().unwrap();
+        assert_eq!(member.path, Path::new("index.html"));
+        assert_eq!(member.content, None);
+
+        let missing = dir.path().join("missing.tar.gz");
+        let members: Vec<_> = stream_archive(missing, true, |_, _| Ok(|_: &Path| true))
+            .collect()
+            .await;
+        assert!(matches!(members[..], [Err(ErrorKind::ReadFileInput(..))]));
+    }
+
+    #[tokio::test]
+    async fn test_archive_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = tarball(
+            &dir,
+            "docs.tar.gz",
+            &[
+                ("guide/intro/index.html", "<h1 id=\"a\">"),
+                ("logo.svg", ""),
+            ],
+        );
+
+        let target = path.join("guide/intro/index.html");
+        let (archive, member) = split_archive_path(&target).unwrap();
+        assert_eq!(archive, path);
+        assert_eq!(member, Path::new("guide/intro/index.html"));
+        assert_eq!(
+            split_archive_path(&dir.path().join("docs.zip/index.html")),
+            None
+        );
+        assert_eq!(split_archive_path(&path), None);
+        assert_eq!(
+            split_archive_path(&path.join("")),
+            Some((path.as_path(), Path::new("")))
+        );
+
+        let index = ArchiveIndex::new(archive.to_path_buf());
+        let members = index.members().await.unwrap();
+        assert!(members.is_file(member));
+        assert!(members.is_file(Path::new("logo.svg")));
+        assert!(members.is_dir(Path::new("")));
+        assert!(members.is_dir(Path::new("guide")));
+        assert!(members.is_dir(Path::new("guide/intro")));
+        assert!(!members.is_dir(member));
+        assert!(!members.is_file(Path::new("guide")));
+        let is_html = |path: &Path| path.extension().is_some_and(|ext| ext == "html");
+        assert_eq!(
+            index.content(member, is_html).await.unwrap(),
+            Some("<h1 id=\"a\">")
+        );
+        assert_eq!(
+            index.content(Path::new("logo.svg"), is_html).await.unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Decode the content of inputs which aren't encoded as UTF-8.
+//!
+//! The encoding is detected from a byte order mark, or from the
+//! `<meta charset>` of HTML and the encoding declaration of XML.
+//! Content without a known encoding isn't decoded, because it's usually
+//! a binary file.
+
+use std::{io, sync::LazyLock};
+
+use encoding_rs::{Encoding, UTF_8};
+use log::debug;
+use regex::bytes::Regex;
+
+/// Number of bytes which are searched for an encoding declaration,
+/// like browsers do
+const DECLARATION_SEARCH_LENGTH: usize = 1024;
+
+/// `<meta charset="...">` and `<meta http-equiv="Content-Type" content="text/html; charset=...">`
+static META_CHARSET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)<meta\s[^>]*?charset\s*=\s*["']?\s*([a-z0-9_:.+-]+)"#).unwrap()
+});
+
+/// `<?xml version="1.0" encoding="..."?>`
+static XML_ENCODING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*<\?xml\s[^>]*?encoding\s*=\s*["']([A-Za-z0-9._-]+)["']"#).unwrap()
+});
+
+/// Decode the given bytes to a string.
+///
+/// # Errors
+///
+/// Returns an [`io::ErrorKind::InvalidData`] error, like reading a file to a
+/// string does, if the bytes aren't valid UTF-8 and their encoding is unknown.
+pub(crate) fn decode(bytes: Vec<u8>) -> io::Result<String> {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(&bytes) {
+        let (content, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return Ok(content.into_owned());
+    }
+
+    let bytes = match String::from_utf8(bytes) {
+        Ok(content) => return Ok(content),
+        Err(e) => e.into_bytes(),
+    };
+
+    let encoding = declared_encoding(&bytes).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8 or declare a known encoding",
+        )
+    })?;
+    debug!("Decoding content as {}", encoding.name());
+    let (content, _) = encoding.decode_without_bom_handling(&bytes);
+    Ok(content.into_owned())
+}
+
+/// The encoding which is declared at the start of HTML or XML content.
+///
+/// Declarations of UTF-8 are ignored, because the content is known not to
+/// be valid UTF-8 at this point. Declarations of UTF-16 are treated the same
+/// way, since the declaration itself couldn't be read if it were true.
+fn declared_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    let start = &bytes[..bytes.len().min(DECLARATION_SEARCH_LENGTH)];
+    let label = XML_ENCODING
+        .captures(start)
+        .or_else(|| META_CHARSET.captures(start))?
+        .get(1)?;
+    let encoding = Encoding::for_label(label.as_bytes())?.output_encoding();
+    (encoding != UTF_8).then_some(encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_ok(bytes: Vec<u8>) -> Option<String> {
+        decode(bytes).ok()
+    }
+
+    #[test]
+    fn test_decode_utf8() {
+        assert_eq!(decode_ok("héllo".into()), Some("héllo".to_string()));
+        assert_eq!(
+            decode_ok(b"\xef\xbb\xbfh\xc3\xa9llo".to_vec()),
+            Some("héllo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decode_utf16_bom() {
+        let mut bytes = vec![0xff, 0xfe];
+        bytes.extend("<a href=\"é\">".encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(decode_ok(bytes), Some("<a href=\"é\">".to_string()));
+
+        let mut bytes = vec![0xfe, 0xff];
+        bytes.extend("ü".encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(decode_ok(bytes), Some("ü".to_string()));
+    }
+
+    #[test]
+    fn test_decode_meta_charset() {
+        let html = b"<html><head><meta charset=\"windows-1252\"></head>\n\x93Caf\xe9\x94";
+        assert_eq!(
+            decode_ok(html.to_vec()),
+            Some("<html><head><meta charset=\"windows-1252\"></head>\n\u{201c}Café\u{201d}".into())
+        );
+
+        let html =
+            b"<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=ISO-8859-1\">\xe9";
+        assert!(decode_ok(html.to_vec()).unwrap().ends_with('é'));
+
+        let html = b"<meta charset='Shift_JIS'>\x93\xfa\x96\x7b";
+        assert!(decode_ok(html.to_vec()).unwrap().ends_with("日本"));
+    }
+
+    #[test]
+    fn test_decode_xml_encoding() {
+        let xml =
+            b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n<loc>https://example.com/\xe9</loc>";
+        assert!(
+            decode_ok(xml.to_vec())
+                .unwrap()
+                .ends_with("<loc>https://example.com/é</loc>")
+        );
+    }
+
+    #[test]
+    fn test_decode_unknown_encoding() {
+        assert_eq!(decode_ok(vec![0x80, 0x81, 0x82, 0xff, 0xfe]), None);
+        // Wrong declarations of UTF-8 don't make invalid content valid
+        assert_eq!(decode_ok(b"<meta charset=\"utf-8\">\xe9".to_vec()), None);
+        assert_eq!(decode_ok(b"<meta charset=\"utf-16\">\xe9".to_vec()), None);
+    }
+}
@@ -0,0 +1,215 @@
+//! Read the structure of EPUB books.
+//!
+//! An EPUB is a ZIP archive, whose `META-INF/container.xml` points to the
+//! package document (OPF). The manifest of the package document lists the
+//! files of the book, and its spine lists the content documents in reading
+//! order.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io::{self, Read, Seek},
+    path::{Component, Path, PathBuf},
+};
+
+use log::warn;
+use percent_encoding::percent_decode_str;
+use quick_xml::{
+    Reader,
+    escape::unescape,
+    events::{BytesStart, Event},
+};
+use zip::ZipArchive;
+
+/// Path of the container file, which points to the package document
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+/// Media type of XHTML content documents
+const XHTML_MEDIA_TYPE: &str = "application/xhtml+xml";
+
+/// The content documents of the EPUB at the given path, as paths within the
+/// archive, which is read from the already opened `archive`.
+///
+/// These are the documents in the spine and all XHTML documents in the
+/// manifest, like the navigation document.
+pub(crate) fn content_documents<R: Read + Seek>(
+    path: &Path,
+    archive: &mut ZipArchive<R>,
+) -> io::Result<HashSet<PathBuf>> {
+    let package_path = package_path(&read_to_string(archive, CONTAINER_PATH)?)?;
+    let package = read_to_string(archive, &package_path)?;
+    let package_dir = Path::new(&package_path).parent().unwrap_or(Path::new(""));
+    package_documents(&package, package_dir, path)
+}
+
+fn read_to_string<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> io::Result<String> {
+    let mut content = String::new();
+    archive.by_name(name)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// The path of the package document, from the first `<rootfile>` of the
+/// container file
+fn package_path(container: &str) -> io::Result<String> {
+    let mut reader = Reader::from_str(container);
+    loop {
+        match reader.read_event().map_err(io::Error::other)? {
+            Event::Start(element) | Event::Empty(element)
+                if element.local_name().as_ref() == b"rootfile" =>
+            {
+                if let Some(path) = attribute(&element, b"full-path") {
+                    return Ok(path);
+                }
+            }
+            Event::Eof => {
+                return Err(io::Error::other(format!(
+                    "No package document in {CONTAINER_PATH}"
+                )));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The content documents listed in the package document
+fn package_documents(
+    package: &str,
+    package_dir: &Path,
+    epub: &Path,
+) -> io::Result<HashSet<PathBuf>> {
+    let mut manifest = HashMap::new();
+    let mut xhtml_items = Vec::new();
+    let mut spine = Vec::new();
+
+    let mut reader = Reader::from_str(package);
+    loop {
+        match reader.read_event().map_err(io::Error::other)? {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                b"item" => {
+                    let (Some(id), Some(href)) =
+                        (attribute(&element, b"id"), attribute(&element, b"href"))
+                    else {
+                        continue;
+                    };
+                    if attribute(&element, b"media-type").as_deref() == Some(XHTML_MEDIA_TYPE) {
+                        xhtml_items.push(id.clone());
+                    }
+                    manifest.insert(id, href);
+                }
+                b"itemref" => spine.extend(attribute(&element, b"idref")),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let mut documents = HashSet::new();
+    for id in spine.iter().chain(&xhtml_items) {
+        match manifest.get(id) {
+            Some(href) => documents.extend(resolve_href(package_dir, href)),
+            None => warn!(
+                "Spine of {} refers to item '{id}', which is missing from the manifest",
+                epub.display()
+            ),
+        }
+    }
+    Ok(documents)
+}
+
+/// The unescaped value of the attribute with the given local name
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    let attr = element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == name)?;
+    let value = String::from_utf8_lossy(&attr.value);
+    Some(unescape(&value).map_or_else(|_| value.to_string(), Cow::into_owned))
+}
+
+/// Resolve a relative URL in the package document to a path within the archive.
+///
+/// Returns `None` for remote resources and for paths outside of the archive.
+fn resolve_href(package_dir: &Path, href: &str) -> Option<PathBuf> {
+    if href.contains(':') {
+        return None;
+    }
+    let href = href.split(['#', '?']).next()?;
+    let href = percent_decode_str(href).decode_utf8().ok()?;
+
+    let mut path = package_dir.to_path_buf();
+    for component in Path::new(&*href).components() {
+        match component {
+            Component::Normal(name) => path.push(name),
+            Component::ParentDir if path.pop() => {}
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_path() {
+        let container = r#"<?xml version="1.0"?>
+            <container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+              <rootfiles>
+                <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+              </rootfiles>
+            </container>"#;
+        assert_eq!(package_path(container).unwrap(), "OEBPS/content.opf");
+        assert!(package_path("<container/>").is_err());
+    }
+
+    #[test]
+    fn test_package_documents() {
+        let package = r#"<?xml version="1.0"?>
+            <package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+              <manifest>
+                <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+                <item id="c1" href="text/chapter%201.xhtml" media-type="application/xhtml+xml"/>
+                <item id="c2" href="../chapter2.xhtml" media-type="application/xhtml+xml"/>
+                <item id="map" href="map.svg" media-type="image/svg+xml"/>
+                <item id="css" href="style.css" media-type="text/css"/>
+                <item id="remote" href="https://example.com/a.xhtml" media-type="application/xhtml+xml"/>
+              </manifest>
+              <spine>
+                <itemref idref="c1"/>
+                <itemref idref="map"/>
+                <itemref idref="missing"/>
+              </spine>
+            </package>"#;
+
+        let documents =
+            package_documents(package, Path::new("OEBPS"), Path::new("book.epub")).unwrap();
+        let expected: HashSet<PathBuf> = [
+            "OEBPS/nav.xhtml",
+            "OEBPS/text/chapter 1.xhtml",
+            "chapter2.xhtml",
+            "OEBPS/map.svg",
+        ]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+        assert_eq!(documents, expected);
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        let dir = Path::new("OEBPS");
+        assert_eq!(
+            resolve_href(dir, "./text/a.xhtml#top"),
+            Some(PathBuf::from("OEBPS/text/a.xhtml"))
+        );
+        assert_eq!(
+            resolve_href(Path::new(""), "a.xhtml"),
+            Some("a.xhtml".into())
+        );
+        assert_eq!(resolve_href(dir, "../../a.xhtml"), None);
+        assert_eq!(resolve_href(dir, "/a.xhtml"), None);
+    }
+}
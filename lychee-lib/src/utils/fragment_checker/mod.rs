@@ -11,8 +11,12 @@ use std::{
 
 use crate::{
     FragmentCheckerOptions, Result,
-    extract::{html::html5gum::extract_html_fragments, markdown::extract_markdown_fragments},
+    extract::{
+        asciidoc::extract_asciidoc_fragments, html::html5gum::extract_html_fragments,
+        markdown::extract_markdown_fragments,
+    },
     types::{ErrorKind, FileType},
+    utils::encoding,
 };
 use percent_encoding::percent_decode_str;
 use tokio::{fs, sync::Mutex};
@@ -28,8 +32,9 @@ pub(crate) struct FragmentInput<'a> {
 
 impl FragmentInput<'_> {
     pub(crate) async fn from_path(path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(path)
+        let content = fs::read(path)
             .await
+            .and_then(encoding::decode)
             .map_err(|err| ErrorKind::ReadFileInput(err, path.to_path_buf()))?;
         let file_type = FileType::from(path);
         Ok(Self {
@@ -121,7 +126,7 @@ impl FragmentChecker {
     /// Checks if the given [`FragmentInput`] contains the given fragment.
     ///
     /// Returns false, if there is a fragment in the link which is not empty or "top"
-    /// and the path is to a Markdown file, which doesn't contain the given fragment.
+    /// and the path is to a Markdown or AsciiDoc file, which doesn't contain the given fragment.
     /// (Empty # and #top fragments are always valid, triggering the browser to scroll to top.)
     ///
     /// For HTML files, also checks for text fragments, if the options specify to do so.
@@ -177,7 +182,16 @@ impl FragmentChecker {
         let extractor = match file_type {
             FileType::Markdown => extract_markdown_fragments,
             FileType::Html => extract_html_fragments,
-            FileType::Css | FileType::Plaintext | FileType::Xml => {
+            FileType::AsciiDoc => extract_asciidoc_fragments,
+            FileType::Css
+            | FileType::Plaintext
+            | FileType::Xml
+            | FileType::Rst
+            | FileType::Notebook
+            | FileType::Json
+            | FileType::Yaml
+            | FileType::Toml
+            | FileType::SourceCode(_) => {
                 info!("Skipping fragment check for {anchor_url} within a {file_type} file");
                 return Ok(true);
             }
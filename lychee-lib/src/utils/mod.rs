@@ -1,3 +1,6 @@
+pub(crate) mod archive;
+pub(crate) mod encoding;
+pub(crate) mod epub;
 pub(crate) mod fragment_checker;
 pub(crate) mod request;
 pub(crate) mod reqwest;
@@ -74,6 +74,23 @@ fn try_parse_into_uri(
     Ok(url.into())
 }
 
+/// Get the base for links of a document which contains an HTML `<base href>`
+/// element.
+///
+/// Like browsers, only the first `<base>` element is used and its `href` is
+/// resolved against the URL of the document itself. Invalid base URLs are
+/// ignored.
+fn document_base(uris: &[RawUri], root_dir: Option<&Path>, base: &BaseInfo) -> Option<BaseInfo> {
+    let href = uris.iter().find(|uri| uri.is_base_href())?;
+    let root_dir = root_dir.and_then(|x| Url::from_directory_path(x).ok());
+
+    let url = base
+        .parse_url_text_with_root_dir(&href.text, root_dir.as_ref())
+        .ok()
+        .filter(|url| !url.cannot_be_a_base())?;
+    Some(base.rebase(&url))
+}
+
 /// Create requests out of the collected URLs. Maps each [`RawUri`] to a parsed
 /// [`Request`] or a [`RequestError`], according to the given options.
 ///
@@ -104,9 +121,15 @@ pub(crate) fn create(
     // maybe change if base-url semantics are changed in future.
     let fallback_base = fallback_base.use_fs_root_as_origin();
     let base = source_base.or_fallback(&fallback_base);
+    let document_base = document_base(&uris, root_dir, base);
 
     uris.into_iter()
         .map(|raw_uri| {
+            // The `<base>` element itself is relative to the document URL
+            let base = match &document_base {
+                Some(document_base) if !raw_uri.is_base_href() => document_base,
+                _ => base,
+            };
             create_request(&raw_uri, source, root_dir, base, extractor).map_err(|e| {
                 RequestError::CreateRequestItem(raw_uri.clone().into(), source.clone(), e.into())
             })
@@ -125,10 +148,7 @@ mod tests {
 
     use super::*;
 
-    const SPAN: RawUriSpan = RawUriSpan {
-        line: NonZeroUsize::MIN,
-        column: Some(NonZeroUsize::MIN),
-    };
+    const SPAN: RawUriSpan = RawUriSpan::new(NonZeroUsize::MIN, Some(NonZeroUsize::MIN));
 
     /// Create requests from the given raw URIs and returns requests that were
     /// constructed successfully, silently ignoring link parsing errors.
@@ -403,6 +423,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_base_element_url_resolution() {
+        let base = BaseInfo::try_from("https://example.com/path/page.html").unwrap();
+        let source = ResolvedInputSource::String(Cow::Borrowed(""));
+
+        let base_href = RawUri {
+            element: Some("base".to_string()),
+            attribute: Some("href".to_string()),
+            ..raw_uri("../docs/")
+        };
+        let uris = vec![
+            base_href,
+            raw_uri("relative.html"),
+            raw_uri("/root-relative"),
+            raw_uri("#fragment"),
+        ];
+        let requests = create_ok_only(uris, &source, None, &base, None);
+
+        let resolved: Vec<_> = requests.iter().map(|r| r.uri.url.as_str()).collect();
+        assert_eq!(
+            resolved,
+            [
+                "https://example.com/docs/",
+                "https://example.com/docs/relative.html",
+                "https://example.com/root-relative",
+                "https://example.com/docs/#fragment",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_base_element_url_resolution_from_root_dir() {
+        let root_dir = PathBuf::from("/tmp/lychee");
+        let base = BaseInfo::from_path(&root_dir).unwrap();
+        let source = ResolvedInputSource::FsPath(PathBuf::from("/tmp/lychee/page.html"));
+
+        let base_href = RawUri {
+            element: Some("base".to_string()),
+            attribute: Some("href".to_string()),
+            ..raw_uri("/docs/")
+        };
+        let uris = vec![
+            base_href,
+            raw_uri("relative.html"),
+            raw_uri("/root-relative"),
+        ];
+        let requests = create_ok_only(uris, &source, Some(&root_dir), &base, None);
+
+        let resolved: Vec<_> = requests.iter().map(|r| r.uri.url.as_str()).collect();
+        assert_eq!(
+            resolved,
+            [
+                "file:///tmp/lychee/docs",
+                "file:///tmp/lychee/docs/relative.html",
+                "file:///tmp/lychee/root-relative",
+            ]
+        );
+    }
+
     #[test]
     fn test_no_base_url_resolution() {
         let source = ResolvedInputSource::String(Cow::Borrowed(""));